@@ -0,0 +1,85 @@
+use std::fmt;
+
+/* MARK: JSON
+  just enough to print one object per line for shell pipelines
+*/
+pub enum Json {
+  Bool(bool),
+  Number(f64),
+  Str(String),
+  Array(Vec<Json>),
+  Object(Vec<(String, Json)>),
+}
+
+impl Json {
+  pub fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+  }
+}
+
+impl From<&str> for Json {
+  fn from(s: &str) -> Json { Json::Str(s.to_string()) }
+}
+
+impl From<String> for Json {
+  fn from(s: String) -> Json { Json::Str(s) }
+}
+
+impl From<usize> for Json {
+  fn from(n: usize) -> Json { Json::Number(n as f64) }
+}
+
+impl From<f64> for Json {
+  fn from(n: f64) -> Json { Json::Number(n) }
+}
+
+impl From<bool> for Json {
+  fn from(b: bool) -> Json { Json::Bool(b) }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+  fn from(items: Vec<T>) -> Json { Json::Array(items.into_iter().map(Into::into).collect()) }
+}
+
+fn escape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+impl fmt::Display for Json {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Json::Bool(b) => write!(f, "{}", b),
+      Json::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+      Json::Number(n) => write!(f, "{}", n),
+      Json::Str(s) => write!(f, "\"{}\"", escape(s)),
+      Json::Array(items) => {
+        write!(f, "[")?;
+        for (i, item) in items.iter().enumerate() {
+          if i > 0 { write!(f, ",")?; }
+          write!(f, "{}", item)?;
+        }
+        write!(f, "]")
+      }
+      Json::Object(fields) => {
+        write!(f, "{{")?;
+        for (i, (key, value)) in fields.iter().enumerate() {
+          if i > 0 { write!(f, ",")?; }
+          write!(f, "\"{}\":{}", escape(key), value)?;
+        }
+        write!(f, "}}")
+      }
+    }
+  }
+}
@@ -0,0 +1,232 @@
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use puzzle_cube_core::solver::optimal::{self, Progress};
+use puzzle_cube_core::{cube, scramble, solver, Alg, Cube, Metric, Metrics};
+use rand_chacha::ChaCha8Rng;
+use rand_core::SeedableRng;
+
+use json::Json;
+
+mod json;
+
+const USAGE: &str = "usage: puzzle-cube-cli [--json] <command> [args]
+
+commands:
+  scramble [--seed N] [--count N] [--moves N]   random state scrambles, or N random moves
+  solve <facelets|scramble>                     solution from 54 URFDLB facelets or a scramble
+  optimal <facelets|scramble> [--qtm]           shortest solution in HTM (or QTM), can take hours
+  apply <alg> [--net]                           facelets (or a text net) after applying alg
+  invert <alg>                                  inverse of alg
+  simplify <alg>                                alg with rotations removed and same axis turns merged
+  order <alg>                                   repetitions of alg that return to solved
+
+arguments left out are read from stdin, one per line";
+
+/* MARK: OPTIONS
+*/
+#[derive(Default)]
+struct Options {
+  json: bool,
+  net: bool,
+  qtm: bool,
+  seed: Option<u64>,
+  count: Option<usize>,
+  moves: Option<usize>,
+  positional: Vec<String>,
+}
+
+// (plain text, json) for one processed input
+type Record = (String, Json);
+
+fn parse_options(args: Vec<String>) -> Result<Options, String> {
+  let mut options = Options::default();
+  let mut args = args.into_iter();
+  while let Some(arg) = args.next() {
+    let mut number = |name: &str| -> Result<u64, String> {
+      let value = args.next().ok_or(format!("{} needs a value", name))?;
+      value.parse().map_err(|_| format!("{} expects a number, found '{}'", name, value))
+    };
+    match arg.as_str() {
+      "--json" => options.json = true,
+      "--net" => options.net = true,
+      "--qtm" => options.qtm = true,
+      "--seed" => options.seed = Some(number("--seed")?),
+      "--count" => options.count = Some(number("--count")? as usize),
+      "--moves" => options.moves = Some(number("--moves")? as usize),
+      "-h" | "--help" => options.positional.insert(0, "help".to_string()),
+      _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+      _ => options.positional.push(arg),
+    }
+  }
+  Ok(options)
+}
+
+fn main() -> ExitCode {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  match run(args) {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(message) => {
+      eprintln!("error: {}", message);
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+  let mut options = parse_options(args)?;
+  if options.positional.is_empty() { return Err(USAGE.to_string()) }
+  let command = options.positional.remove(0);
+
+  match command.as_str() {
+    "scramble" => {
+      for record in scramble_command(&options) { print_record(&options, record); }
+      Ok(())
+    }
+    "solve" => each_input(&options, solve_command),
+    "optimal" => each_input(&options, |input| optimal_command(input, options.qtm)),
+    "apply" => each_input(&options, |input| apply_command(input, options.net)),
+    "invert" => each_input(&options, invert_command),
+    "simplify" => each_input(&options, simplify_command),
+    "order" => each_input(&options, order_command),
+    "help" => { println!("{}", USAGE); Ok(()) }
+    _ => Err(format!("unknown command '{}'\n\n{}", command, USAGE)),
+  }
+}
+
+fn print_record(options: &Options, (text, json): Record) {
+  if options.json { println!("{}", json); } else { println!("{}", text); }
+}
+
+// positional arguments form a single input, otherwise every non-empty stdin line is one. lines
+// are printed as they are done, a bad one is reported and skipped and fails the run at the end
+fn each_input(options: &Options, command: impl Fn(&str) -> Result<Record, String>) -> Result<(), String> {
+  if !options.positional.is_empty() {
+    print_record(options, command(&options.positional.join(" "))?);
+    return Ok(());
+  }
+  let mut failed = 0;
+  for line in io::stdin().lock().lines() {
+    let line = line.map_err(|e| e.to_string())?;
+    let input = line.trim();
+    if input.is_empty() { continue }
+    match command(input) {
+      Ok(record) => print_record(options, record),
+      Err(message) if options.json => {
+        failed += 1;
+        println!("{}", Json::object(vec![("input", input.into()), ("error", message.into())]));
+      }
+      Err(message) => {
+        failed += 1;
+        eprintln!("error: {}", message);
+      }
+    }
+  }
+  if failed > 0 { return Err(format!("{} input{} failed", failed, if failed == 1 { "" } else { "s" })) }
+  Ok(())
+}
+
+fn parse_alg(input: &str) -> Result<Alg, String> {
+  input.parse().map_err(|e| format!("'{}': {}", input, e))
+}
+
+// 54 facelet letters, or else a scramble applied to a solved cube
+fn parse_cube(input: &str) -> Result<Cube, String> {
+  let facelets = input.len() == cube::FACELETS && input.chars().all(|c| "URFDLB".contains(c));
+  if facelets {
+    Cube::from_facelet_string(input).map_err(|e| e.to_string())
+  } else {
+    Ok(Cube::from_alg(&parse_alg(input)?))
+  }
+}
+
+fn metrics_json(metrics: &Metrics) -> Json {
+  Json::Object(Metric::ALL.iter().map(|m| (m.name().to_lowercase(), metrics.get(*m).into())).collect())
+}
+
+/* MARK: COMMANDS
+*/
+fn scramble_command(options: &Options) -> Vec<Record> {
+  let seed = options.seed.unwrap_or_else(|| {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+  });
+  let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+  (0..options.count.unwrap_or(1)).map(|_| {
+    let alg = match options.moves {
+      Some(count) => scramble::random_moves(&mut rng, count),
+      None => scramble::random_state(&mut rng),
+    };
+    let json = Json::object(vec![
+      ("scramble", alg.to_string().into()),
+      ("moves", alg.len().into()),
+      ("facelets", Cube::from_alg(&alg).to_facelet_string().into()),
+    ]);
+    (alg.to_string(), json)
+  }).collect()
+}
+
+fn solve_command(input: &str) -> Result<Record, String> {
+  let cube = parse_cube(input)?;
+  let solution = solver::solve(&cube).map_err(|e| e.to_string())?;
+  let json = Json::object(vec![
+    ("input", input.into()),
+    ("solution", solution.to_string().into()),
+    ("moves", solution.len().into()),
+  ]);
+  Ok((solution.to_string(), json))
+}
+
+fn optimal_command(input: &str, qtm: bool) -> Result<Record, String> {
+  let cube = parse_cube(input)?;
+  let metric = if qtm { Metric::Qtm } else { Metric::Htm };
+  let solution = optimal::solve(&cube, metric, &Progress::default()).map_err(|e| e.to_string())?;
+  let json = Json::object(vec![
+    ("input", input.into()),
+    ("solution", solution.to_string().into()),
+    ("metric", metric.name().into()),
+    ("moves", solution.count(metric).into()),
+  ]);
+  Ok((solution.to_string(), json))
+}
+
+fn apply_command(input: &str, net: bool) -> Result<Record, String> {
+  let alg = parse_alg(input)?;
+  let cube = Cube::from_alg(&alg);
+  let mut fields = vec![
+    ("alg", alg.to_string().into()),
+    ("facelets", cube.to_facelet_string().into()),
+    ("solved", cube.is_solved().into()),
+  ];
+  if net { fields.push(("net", cube.to_net().into())); }
+  let text = if net { cube.to_net().trim_end().to_string() } else { cube.to_facelet_string() };
+  Ok((text, Json::object(fields)))
+}
+
+fn invert_command(input: &str) -> Result<Record, String> {
+  let alg = parse_alg(input)?;
+  let inverse = alg.inverse();
+  let json = Json::object(vec![("alg", alg.to_string().into()), ("inverse", inverse.to_string().into())]);
+  Ok((inverse.to_string(), json))
+}
+
+fn simplify_command(input: &str) -> Result<Record, String> {
+  let alg = parse_alg(input)?;
+  let simplified = alg.simplify();
+  let json = Json::object(vec![
+    ("alg", alg.to_string().into()),
+    ("simplified", simplified.to_string().into()),
+    ("moves", simplified.len().into()),
+    ("before", metrics_json(&alg.metrics())),
+    ("after", metrics_json(&simplified.metrics())),
+  ]);
+  Ok((simplified.to_string(), json))
+}
+
+fn order_command(input: &str) -> Result<Record, String> {
+  let alg = parse_alg(input)?;
+  let order = cube::order(&alg);
+  let json = Json::object(vec![("alg", alg.to_string().into()), ("order", order.into())]);
+  Ok((order.to_string(), json))
+}
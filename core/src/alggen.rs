@@ -0,0 +1,247 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::analysis::{CORNER_NAMES, EDGE_NAMES};
+use crate::cube::{tables, Cube};
+use crate::cubie::CubieCube;
+use crate::face::Face;
+use crate::moves::{Alg, Move, Turn};
+use crate::solver::{in_frame, pieces};
+
+/* MARK: ALG GEN
+  every way of solving the pieces a mask cares about with a chosen set of faces, up to some
+  length, ranked by how well they sit in the hands. faces and pieces are named as the cube is
+  held, the search itself runs on the cube with its centres home
+*/
+pub const DEFAULT_MAX_LENGTH: usize = 11;
+pub const MAX_LENGTH: usize = 16;
+// more than this are not searched for
+pub const LIMIT: usize = 500;
+// pieces per pruning table, 24^4 entries each
+const GROUP_SIZE: usize = 4;
+
+/* MARK: MOVE SET
+  <R,U,F>, or just RUF
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveSet(pub Vec<Face>);
+
+impl fmt::Display for MoveSet {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let faces: Vec<String> = self.0.iter().map(Face::to_string).collect();
+    write!(f, "<{}>", faces.join(","))
+  }
+}
+
+impl FromStr for MoveSet {
+  type Err = AlgGenError;
+
+  fn from_str(s: &str) -> Result<MoveSet, AlgGenError> {
+    let mut faces = Vec::new();
+    for c in s.chars().filter(|c| !matches!(c, '<' | '>' | ',' | ' ')) {
+      let face = Face::from_char(c.to_ascii_uppercase()).ok_or(AlgGenError::Turn(c))?;
+      if !faces.contains(&face) { faces.push(face); }
+    }
+    if faces.is_empty() { return Err(AlgGenError::NoTurns) }
+    Ok(MoveSet(faces))
+  }
+}
+
+/* MARK: MASK
+  the pieces that have to end up solved, by position as the cube is held. the rest may end
+  anywhere, twisted or flipped
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mask {
+  pub corners: [bool; 8],
+  pub edges: [bool; 12],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskPreset {
+  #[default]
+  Whole,
+  F2lCorners,
+  F2lEdges,
+  F2l,
+}
+
+impl MaskPreset {
+  pub const ALL: [MaskPreset; 4] = [MaskPreset::Whole, MaskPreset::F2lCorners, MaskPreset::F2lEdges, MaskPreset::F2l];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      MaskPreset::Whole => "whole cube",
+      MaskPreset::F2lCorners => "F2L and top corners",
+      MaskPreset::F2lEdges => "F2L and top edges",
+      MaskPreset::F2l => "F2L",
+    }
+  }
+
+  pub fn next(self) -> MaskPreset { MaskPreset::ALL[(self as usize + 1) % MaskPreset::ALL.len()] }
+
+  // top layer positions are the first four of each in the cubie order
+  pub fn mask(self) -> Mask {
+    let top_corners = matches!(self, MaskPreset::Whole | MaskPreset::F2lCorners);
+    let top_edges = matches!(self, MaskPreset::Whole | MaskPreset::F2lEdges);
+    Mask {
+      corners: std::array::from_fn(|i| i >= 4 || top_corners),
+      edges: std::array::from_fn(|i| i >= 4 || top_edges),
+    }
+  }
+}
+
+impl Mask {
+  // drops a piece named by its faces in any order, UF or FU, URF or FRU
+  pub fn ignore(&mut self, name: &str) -> Result<(), AlgGenError> {
+    let matches = |piece: &str| name.len() == piece.len() && name.to_ascii_uppercase().chars().all(|c| piece.contains(c));
+    if let Some(i) = CORNER_NAMES.iter().position(|piece| matches(piece)) {
+      self.corners[i] = false;
+    } else if let Some(i) = EDGE_NAMES.iter().position(|piece| matches(piece)) {
+      self.edges[i] = false;
+    } else {
+      return Err(AlgGenError::Piece(name.to_string()))
+    }
+    Ok(())
+  }
+
+  // groups of positions on the cube with its centres home
+  fn goal(&self, cube: &Cube) -> Vec<pieces::Group> {
+    let centers = cube.center_positions();
+    let tables = tables();
+    // position with the same faces once the held faces are named for their home centres
+    let home = |facelets: &[u8], pieces: &[Vec<u8>]| -> u8 {
+      let faces: Vec<Face> = facelets.iter().map(|facelet| {
+        let held = Face::from_index(*facelet as usize / 9);
+        Face::from_index(centers.iter().position(|f| *f == held).unwrap_or(0))
+      }).collect();
+      pieces.iter().position(|other| other.iter().all(|facelet| faces.contains(&Face::from_index(*facelet as usize / 9))))
+        .unwrap_or(0) as u8
+    };
+    let corners: Vec<u8> = (0..8).filter(|i| self.corners[*i]).map(|i| home(&tables.corners[i], &tables.corners)).collect();
+    let edges: Vec<u8> = (0..12).filter(|i| self.edges[*i]).map(|i| home(&tables.edges[i], &tables.edges)).collect();
+    corners.chunks(GROUP_SIZE).map(|chunk| pieces::Group { corners: chunk.to_vec(), edges: Vec::new() })
+      .chain(edges.chunks(GROUP_SIZE).map(|chunk| pieces::Group { corners: Vec::new(), edges: chunk.to_vec() }))
+      .collect()
+  }
+
+  pub fn ignored(&self) -> Vec<&'static str> {
+    let corners = (0..8).filter(|i| !self.corners[*i]).map(|i| CORNER_NAMES[i]);
+    corners.chain((0..12).filter(|i| !self.edges[*i]).map(|i| EDGE_NAMES[i])).collect()
+  }
+}
+
+/* MARK: ERRORS
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgGenError {
+  Turn(char),
+  NoTurns,
+  Piece(String),
+  Length(String),
+}
+
+impl fmt::Display for AlgGenError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AlgGenError::Turn(c) => write!(f, "'{}' is not a face, only outer turns can be searched", c),
+      AlgGenError::NoTurns => write!(f, "the move set needs at least one face"),
+      AlgGenError::Piece(name) => write!(f, "'{}' is not a corner or edge", name),
+      AlgGenError::Length(length) => write!(f, "'{}' is not a length from 1 to {}", length, MAX_LENGTH),
+    }
+  }
+}
+
+impl std::error::Error for AlgGenError {}
+
+/* MARK: REQUEST
+  what is typed in: the move set, then optionally the longest length and pieces to ignore,
+  <R,U,F> 10 UF UB
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+  pub moves: MoveSet,
+  pub max_length: usize,
+  pub mask: Mask,
+}
+
+impl Request {
+  pub fn parse(input: &str, preset: MaskPreset) -> Result<Request, AlgGenError> {
+    let mut words = input.split_whitespace().peekable();
+    let moves: MoveSet = words.next().unwrap_or("").parse()?;
+    let mut max_length = DEFAULT_MAX_LENGTH;
+    if let Some(word) = words.next_if(|word| word.starts_with(|c: char| c.is_ascii_digit())) {
+      max_length = word.parse().ok().filter(|n| (1..=MAX_LENGTH).contains(n)).ok_or(AlgGenError::Length(word.to_string()))?;
+    }
+    let mut mask = preset.mask();
+    for word in words { mask.ignore(word)?; }
+    Ok(Request { moves, max_length, mask })
+  }
+}
+
+/* MARK: GENERATE
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generated {
+  pub alg: Alg,
+  pub score: f32,
+}
+
+// best scoring first, an empty list when the masked pieces are already solved
+pub fn generate(cube: &Cube, request: &Request) -> Vec<Generated> {
+  let centers = cube.center_positions();
+  // held faces named for their home centres
+  let faces: Vec<Face> = request.moves.0.iter()
+    .map(|held| Face::from_index(centers.iter().position(|f| f == held).unwrap_or(0)))
+    .collect();
+  let goal = request.mask.goal(cube);
+  let solutions = pieces::all_solutions(&CubieCube::from_cube(cube), &goal, &faces, request.max_length, LIMIT);
+
+  let mut generated: Vec<Generated> = solutions.into_iter().filter(|alg| !alg.is_empty()).map(|alg| {
+    let alg = in_frame(cube, &alg);
+    Generated { score: ergonomics(&alg), alg }
+  }).collect();
+  generated.sort_by(|a, b| a.score.total_cmp(&b.score).then(a.alg.len().cmp(&b.alg.len())));
+  generated
+}
+
+/* MARK: ERGONOMICS
+  a rough cost of doing an algorithm by hand, lower is better. R and U turns are the cheapest,
+  F, D and L less so and B is awkward. half turns and regrips, a change between the
+  R/L and F/B hands, cost extra
+*/
+fn move_cost(mv: &Move) -> f32 {
+  let Turn::Face(face) = mv.turn else { return 2.0 };
+  let base = match face {
+    Face::R | Face::U => 1.0,
+    Face::L | Face::D => 1.5,
+    Face::F => 1.8,
+    Face::B => 3.0,
+  };
+  if mv.amount == 2 { base + 0.5 } else { base }
+}
+
+pub fn ergonomics(alg: &Alg) -> f32 {
+  let mut score: f32 = alg.iter().map(move_cost).sum();
+  // the hands are held for R and L, turns of F and B after them need a regrip
+  let side = |mv: &Move| match mv.turn {
+    Turn::Face(Face::R | Face::L) => Some(true),
+    Turn::Face(Face::F | Face::B) => Some(false),
+    _ => None,
+  };
+  let sides: Vec<bool> = alg.iter().filter_map(side).collect();
+  score += sides.windows(2).filter(|pair| pair[0] != pair[1]).count() as f32;
+  score
+}
+
+// one per line: the algorithm, its length and score, tab separated
+pub fn export(case: &Cube, request: &Request, generated: &[Generated]) -> String {
+  let mut lines = vec![
+    format!("# case {}", case),
+    format!("# moves {}, up to {}", request.moves, request.max_length),
+  ];
+  let ignored = request.mask.ignored();
+  if !ignored.is_empty() { lines.push(format!("# ignoring {}", ignored.join(" "))); }
+  lines.extend(generated.iter().map(|g| format!("{}\t{}\t{:.1}", g.alg, g.alg.len(), g.score)));
+  lines.join("\n") + "\n"
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use crate::cube::{order, sticker_coords, tables, Cube};
+use crate::cubie::CubieCube;
+use crate::moves::Alg;
+
+/* MARK: ANALYSIS
+  what an algorithm does to a solved cube, piece by piece: the cycles its corners and edges
+  move round, the twist or flip they pick up on the way, and the parity of it all
+*/
+pub const CORNER_NAMES: [&str; 8] = ["URF", "UFL", "ULB", "UBR", "DFR", "DLF", "DBL", "DRB"];
+pub const EDGE_NAMES: [&str; 12] = ["UR", "UF", "UL", "UB", "DR", "DF", "DL", "DB", "FR", "FL", "BL", "BR"];
+
+// positions a piece goes through, starting from the lowest, and the orientation it has
+// gained once it is back where it started: corner twists clockwise, edge flips
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+  pub positions: Vec<usize>,
+  pub twist: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+  // repetitions back to solved
+  pub order: usize,
+  pub corners: Vec<Cycle>,
+  pub edges: Vec<Cycle>,
+  // odd permutations, always both or neither for a real cube
+  pub corner_parity: bool,
+  pub edge_parity: bool,
+}
+
+impl Analysis {
+  pub fn of(alg: &Alg) -> Analysis {
+    let cube = CubieCube::from_cube(&Cube::from_alg(alg));
+    Analysis {
+      order: order(alg),
+      corners: cycles(&cube.cp, &cube.co, 3),
+      edges: cycles(&cube.ep, &cube.eo, 2),
+      corner_parity: cube.corner_parity(),
+      edge_parity: cube.edge_parity(),
+    }
+  }
+
+  pub fn affected_corners(&self) -> impl Iterator<Item = usize> + '_ {
+    self.corners.iter().flat_map(|cycle| cycle.positions.iter().copied())
+  }
+
+  pub fn affected_edges(&self) -> impl Iterator<Item = usize> + '_ {
+    self.edges.iter().flat_map(|cycle| cycle.positions.iter().copied())
+  }
+}
+
+// cycles of the pieces that move or turn, `perm[i]` being the piece now at position i
+fn cycles(perm: &[u8], orientation: &[u8], modulus: u8) -> Vec<Cycle> {
+  let mut seen = vec![false; perm.len()];
+  let mut cycles = Vec::new();
+  for start in 0..perm.len() {
+    if seen[start] { continue }
+    let mut positions = Vec::new();
+    let mut twist = 0;
+    let mut at = start;
+    while !seen[at] {
+      seen[at] = true;
+      positions.push(at);
+      twist = (twist + orientation[at]) % modulus;
+      // where the piece from here has gone
+      at = perm.iter().position(|piece| *piece as usize == at).unwrap_or(at);
+    }
+    if positions.len() > 1 || twist != 0 { cycles.push(Cycle { positions, twist }); }
+  }
+  cycles
+}
+
+fn write_cycles(f: &mut fmt::Formatter<'_>, cycles: &[Cycle], names: &[&str], marks: &[&str]) -> fmt::Result {
+  if cycles.is_empty() { return write!(f, "none") }
+  let cycles: Vec<String> = cycles.iter().map(|cycle| {
+    let names: Vec<&str> = cycle.positions.iter().map(|p| names[*p]).collect();
+    format!("({}){}", names.join(" "), marks[cycle.twist as usize])
+  }).collect();
+  write!(f, "{}", cycles.join(" "))
+}
+
+// order 3, corners (URF UBR ULB), edges none, parity even; + twists clockwise, - anticlockwise
+impl fmt::Display for Analysis {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "order {}", self.order)?;
+    write!(f, "corners ")?;
+    write_cycles(f, &self.corners, &CORNER_NAMES, &["", "+", "-"])?;
+    write!(f, "\nedges ")?;
+    write_cycles(f, &self.edges, &EDGE_NAMES, &["", "+"])?;
+    let parity = |odd: bool| if odd { "odd" } else { "even" };
+    write!(f, "\nparity {} corners, {} edges", parity(self.corner_parity), parity(self.edge_parity))
+  }
+}
+
+// cubie position, -1 to 1 along each axis, of a corner or edge position
+pub fn corner_position(corner: usize) -> [i8; 3] { sticker_coords(tables().corners[corner][0] as usize).0 }
+
+pub fn edge_position(edge: usize) -> [i8; 3] { sticker_coords(tables().edges[edge][0] as usize).0 }
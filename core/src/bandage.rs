@@ -0,0 +1,547 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use rand_core::RngCore;
+
+use crate::cube::{sticker_coords, tables, Cube, FACELETS};
+use crate::cubie::CubieCube;
+use crate::face::{Axis, Face};
+use crate::moves::{Alg, Move};
+use crate::solver::pieces::{code_moves, prune_table, Group};
+use crate::solver::SolveError;
+
+/* MARK: BANDAGING
+  neighbouring pieces fused into blocks that always move together. a layer can only turn when
+  every block is either wholly inside it or wholly outside, a turn that would pull a block apart
+  is blocked. pieces are named by their faces, UFR, DL or U, joined with + into a block, and
+  blocks are separated by spaces: UFR+UF DLB+DL+DB
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bandaging {
+  // home positions of the pieces in each block
+  blocks: Vec<Vec<[i8; 3]>>,
+  // neighbouring pieces in the same block, each as one of its facelets
+  bonds: Vec<(u8, u8)>,
+}
+
+pub struct Preset {
+  pub name: &'static str,
+  pub blocks: &'static str,
+}
+
+pub const BANDAGES: [Preset; 4] = [
+  Preset { name: "Fused pair", blocks: "UFR+UF" },
+  Preset { name: "1x2x2 block", blocks: "DFR+DF+DR+D" },
+  Preset { name: "2x2x2 block", blocks: "DLB+DL+DB+LB+D+L+B" },
+  Preset { name: "Two bars", blocks: "UFL+UF+UFR DBL+DB+DBR" },
+];
+
+fn neighbours(a: [i8; 3], b: [i8; 3]) -> bool { (0..3).map(|k| (a[k] - b[k]).abs()).sum::<i8>() == 1 }
+
+// any facelet of the piece at a position
+fn facelet_of(position: [i8; 3]) -> u8 {
+  (0..FACELETS).find(|i| sticker_coords(*i).0 == position).unwrap_or(0) as u8
+}
+
+fn piece_name(position: [i8; 3]) -> String {
+  [Axis::Y, Axis::Z, Axis::X].iter().filter(|axis| position[axis.index()] != 0)
+    .map(|axis| Face::from_axis(*axis, position[axis.index()] > 0).to_char())
+    .collect()
+}
+
+impl Bandaging {
+  pub fn blocks(&self) -> &[Vec<[i8; 3]>] { &self.blocks }
+
+  // where each piece of the bonds sits now, as pairs of cubie positions
+  pub fn bond_positions(&self, cube: &Cube) -> Vec<([i8; 3], [i8; 3])> {
+    let at = positions(cube);
+    self.bonds.iter().map(|(a, b)| (at[*a as usize], at[*b as usize])).collect()
+  }
+
+  // whole cube rotations always turn, anything else only if it splits no block
+  pub fn allows(&self, cube: &Cube, mv: Move) -> bool {
+    if mv.is_rotation() { return true }
+    let (axis, layers) = (mv.axis().index(), mv.layers());
+    let turned = |position: [i8; 3]| layers[(position[axis] + 1) as usize];
+    self.bond_positions(cube).iter().all(|(a, b)| turned(*a) == turned(*b))
+  }
+
+  // face turns that can be made from here
+  pub fn legal_moves(&self, cube: &Cube) -> Vec<Move> {
+    face_moves().into_iter().filter(|mv| self.allows(cube, *mv)).collect()
+  }
+
+  // random legal face turns from wherever the cube is, never the same face twice in a row or
+  // three on one axis. stops short when the bandaging leaves nothing to turn
+  pub fn random_moves(&self, rng: &mut impl RngCore, cube: &Cube, count: usize) -> Alg {
+    let mut cube = *cube;
+    let mut alg = Alg::new();
+    while alg.len() < count {
+      let previous: Vec<&Move> = alg.iter().rev().take(2).collect();
+      let repeats = |mv: &Move| previous.first().is_some_and(|p| p.turn == mv.turn) || (previous.len() == 2 && previous.iter().all(|p| p.axis() == mv.axis()));
+      let moves: Vec<Move> = self.legal_moves(&cube).into_iter().filter(|mv| !repeats(mv)).collect();
+      if moves.is_empty() { break }
+      let mv = moves[rng.next_u32() as usize % moves.len()];
+      cube.apply_move(mv);
+      alg.push(mv);
+    }
+    alg
+  }
+
+  /* MARK: SOLVE
+    legal face turns, found in stages: the blocks first, then the layer they lean towards and
+    the slots below it, each stage an IDA* search that keeps what the earlier ones solved. the
+    bounds come from the piece tables of the solvers, built from the faces that can ever turn,
+    and a legal turn can only make a solution longer so they still hold. the pieces left at
+    the end are looked up in a table of short sequences, see last_pieces
+  */
+  pub fn solve(&self, cube: &Cube) -> Result<Alg, SolveError> {
+    let fix = cube.orientation_fix();
+    let mut cubie = CubieCube::from_cube(&cube.normalized());
+    if !cubie.is_solvable() { return Err(SolveError::Unsolvable) }
+
+    let faces = self.movable_faces();
+    let mut search = Search {
+      bonds: self.bonds.iter().map(|(a, b)| (Piece::of(*a), Piece::of(*b))).collect(),
+      goal: Vec::new(),
+      tables: Vec::new(),
+      faces: faces.iter().map(|face| face.index()).collect(),
+      nodes: 0,
+    };
+    let (stages, last) = self.stages();
+    let mut solution = Alg::new();
+    for group in stages {
+      search.tables.push(prune_table(&group, &faces));
+      search.goal.push(group);
+      for mv in search.stage(&cubie)? {
+        cubie = cubie.multiply(&turns()[mv]);
+        solution.push(move_of(mv));
+      }
+    }
+
+    let table = self.last_pieces(&mut search, &last);
+    while last.index(&cubie) != last.solved() {
+      let Some(step) = table.next.get(&last.index(&cubie)) else { return Err(SolveError::NotFound(STEP_LENGTH)) };
+      for mv in &table.steps[*step as usize] {
+        cubie = cubie.multiply(&turns()[*mv]);
+        solution.push(move_of(*mv));
+      }
+    }
+    Ok(fix.concat(&solution))
+  }
+
+  // a face whose centre is fused to a piece outside its layer can never turn
+  fn movable_faces(&self) -> Vec<Face> {
+    Face::ALL.into_iter().filter(|face| {
+      let (axis, sign) = (face.axis().index(), face.normal()[face.axis().index()]);
+      !self.blocks.iter().any(|block| block.contains(&face.normal()) && block.iter().any(|p| p[axis] != sign))
+    }).collect()
+  }
+
+  // the pieces each stage brings home: every block, then the cross and slots of the side most
+  // of the blocks sit on, and the pieces left over for last_pieces. a block on the far side
+  // keeps its layer from turning on its own, there the corners go first and the edges last
+  fn stages(&self) -> (Vec<Group>, Group) {
+    let fused: Vec<[i8; 3]> = self.blocks.iter().flatten().copied().collect();
+    let first = Face::ALL.into_iter().max_by_key(|face| {
+      let (axis, sign) = (face.axis().index(), face.normal()[face.axis().index()]);
+      (fused.iter().filter(|p| p[axis] == sign).count(), 6 - face.index())
+    }).unwrap_or(Face::D);
+    let (axis, sign) = (first.axis().index(), first.normal()[first.axis().index()]);
+    let pieces: Vec<[i8; 3]> = cubie_positions().iter().map(|(position, _)| *position).collect();
+    let layer = |side: i8, edges: bool| pieces.iter().filter(|p| p[axis] == side && edge(**p) == edges).copied().collect::<Vec<_>>();
+
+    let mut layers: Vec<Vec<[i8; 3]>> = self.blocks.clone();
+    layers.push(layer(sign, true));
+    if fused.iter().any(|p| p[axis] == -sign) {
+      layers.extend([layer(sign, false), layer(-sign, false)]);
+    } else {
+      for corner in layer(sign, false) {
+        let mut middle = corner;
+        middle[axis] = 0;
+        layers.push(vec![corner, middle]);
+      }
+    }
+
+    // each piece in the first stage that has it, up to four to a table
+    let mut staged: Vec<[i8; 3]> = Vec::new();
+    let mut stages = Vec::new();
+    for layer in layers {
+      let fresh: Vec<[i8; 3]> = layer.into_iter().filter(|p| !staged.contains(p) && pieces.contains(p)).collect();
+      staged.extend(&fresh);
+      stages.extend(fresh.chunks(4).map(group_of));
+    }
+    let rest: Vec<[i8; 3]> = pieces.into_iter().filter(|p| !staged.contains(p)).collect();
+    (stages, group_of(&rest))
+  }
+
+  // every arrangement of the pieces left, with the step that brings it nearer solved. the
+  // steps are the legal sequences that keep the rest of the cube solved, the shortest for each
+  // thing they do, lengthened until they reach every arrangement or STEP_LENGTH. the blocks
+  // are home whenever a step starts, so one is legal however the pieces left lie
+  fn last_pieces(&self, search: &mut Search, last: &Group) -> Arc<LastPieces> {
+    static TABLES: OnceLock<Mutex<HashMap<String, Arc<LastPieces>>>> = OnceLock::new();
+    let tables = TABLES.get_or_init(|| Mutex::new(HashMap::new()));
+    let key = self.to_string();
+    if let Some(table) = tables.lock().unwrap().get(&key) { return table.clone() }
+
+    let mut found: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut table = LastPieces { steps: Vec::new(), next: HashMap::new() };
+    let mut path = Vec::new();
+    for length in 1..=STEP_LENGTH {
+      if table.next.len() + 1 >= arrangements(last) { break }
+      let before = found.len();
+      search.sequences(&CubieCube::SOLVED, length, &mut path, &mut |cube, path| {
+        let index = last.index(cube);
+        if index != last.solved() { found.entry(index).or_insert_with(|| path.to_vec()); }
+      });
+      if found.len() == before { continue }
+      table = LastPieces::new(last, found.values().cloned().collect());
+    }
+
+    let table = Arc::new(table);
+    tables.lock().unwrap().insert(key, table.clone());
+    table
+  }
+}
+
+struct LastPieces {
+  steps: Vec<Vec<usize>>,
+  // the step to make from each unsolved arrangement
+  next: HashMap<usize, u16>,
+}
+
+impl LastPieces {
+  // backwards from solved, each arrangement a step leads to is a step closer than where it started
+  fn new(last: &Group, steps: Vec<Vec<usize>>) -> LastPieces {
+    let undo: Vec<_> = steps.iter().map(|step| {
+      let effect = step.iter().fold(CubieCube::SOLVED, |cube, mv| cube.multiply(&turns()[*mv]));
+      code_moves(&effect.inverse())
+    }).collect();
+    let mut next = HashMap::new();
+    let mut queue = VecDeque::from([last.solved()]);
+    while let Some(index) = queue.pop_front() {
+      for (step, (corners, edges)) in undo.iter().enumerate() {
+        let before = last.follow(index, corners, edges);
+        if before == last.solved() || next.contains_key(&before) { continue }
+        next.insert(before, step as u16);
+        queue.push_back(before);
+      }
+    }
+    LastPieces { steps, next }
+  }
+}
+
+// how many ways the pieces can lie with the rest of the cube solved, twist, flip and parity
+// fixed by the others
+fn arrangements(group: &Group) -> usize {
+  let (corners, edges) = (group.corners.len(), group.edges.len());
+  let factorial = |n: usize| (1..=n).product::<usize>();
+  let parity = if corners >= 2 || edges >= 2 { 2 } else { 1 };
+  factorial(corners) * factorial(edges) / parity * 3usize.pow(corners.saturating_sub(1) as u32) * 2usize.pow(edges.saturating_sub(1) as u32)
+}
+
+fn group_of(positions: &[[i8; 3]]) -> Group {
+  let mut group = Group { corners: Vec::new(), edges: Vec::new() };
+  for position in positions {
+    let (_, cubie) = cubie_positions().iter().find(|(p, _)| p == position).copied().unwrap_or_default();
+    if edge(*position) { group.edges.push(cubie as u8) } else { group.corners.push(cubie as u8) }
+  }
+  group
+}
+
+// longest single stage, and the positions looked at across all of them, before giving up
+const STAGE_LIMIT: usize = 16;
+const NODE_LIMIT: usize = 20_000_000;
+// longest sequence tried as a step for the last pieces
+const STEP_LENGTH: usize = 12;
+
+// a fused piece as the solvers see it, centres never move
+#[derive(Clone, Copy)]
+enum Piece {
+  Corner(u8),
+  Edge(u8),
+  Center([i8; 3]),
+}
+
+impl Piece {
+  fn of(facelet: u8) -> Piece {
+    let tables = tables();
+    if let Some(corner) = tables.corners.iter().position(|c| c.contains(&facelet)) { return Piece::Corner(corner as u8) }
+    if let Some(edge) = tables.edges.iter().position(|e| e.contains(&facelet)) { return Piece::Edge(edge as u8) }
+    Piece::Center(sticker_coords(facelet as usize).0)
+  }
+
+  fn position(self, cube: &CubieCube) -> [i8; 3] {
+    let (corners, edges) = cubie_coords();
+    match self {
+      Piece::Corner(piece) => corners[cube.cp.iter().position(|p| *p == piece).unwrap_or(0)],
+      Piece::Edge(piece) => edges[cube.ep.iter().position(|p| *p == piece).unwrap_or(0)],
+      Piece::Center(position) => position,
+    }
+  }
+}
+
+struct Search {
+  bonds: Vec<(Piece, Piece)>,
+  // pieces that have to be home by the end of the stage, and their tables
+  goal: Vec<Group>,
+  tables: Vec<Arc<Vec<u8>>>,
+  faces: Vec<usize>,
+  nodes: usize,
+}
+
+impl Search {
+  // shortest legal turns to the goal that keep the earlier stages solved
+  fn stage(&mut self, cube: &CubieCube) -> Result<Vec<usize>, SolveError> {
+    let Some(bound) = self.distance(cube) else { return Err(SolveError::Unsolvable) };
+    let mut path = Vec::new();
+    for depth in bound..=STAGE_LIMIT {
+      if self.dfs(cube, depth, &mut path)? { return Ok(path) }
+    }
+    Err(SolveError::NotFound(STAGE_LIMIT))
+  }
+
+  fn distance(&self, cube: &CubieCube) -> Option<usize> {
+    let mut bound = 0;
+    for (group, table) in self.goal.iter().zip(&self.tables) {
+      let depth = table[group.index(cube)];
+      if depth == u8::MAX { return None }
+      bound = bound.max(depth as usize);
+    }
+    Some(bound)
+  }
+
+  fn dfs(&mut self, cube: &CubieCube, depth: usize, path: &mut Vec<usize>) -> Result<bool, SolveError> {
+    self.nodes += 1;
+    if self.nodes > NODE_LIMIT { return Err(SolveError::NotFound(STAGE_LIMIT)) }
+    let Some(bound) = self.distance(cube) else { return Ok(false) };
+    if bound > depth { return Ok(false) }
+    if depth == 0 { return Ok(true) }
+
+    for mv in self.next_moves(cube, path) {
+      path.push(mv);
+      if self.dfs(&cube.multiply(&turns()[mv]), depth - 1, path)? { return Ok(true) }
+      path.pop();
+    }
+    Ok(false)
+  }
+
+  // every legal sequence of exactly `depth` turns that ends with the goal solved
+  fn sequences(&self, cube: &CubieCube, depth: usize, path: &mut Vec<usize>, found: &mut impl FnMut(&CubieCube, &[usize])) {
+    let Some(bound) = self.distance(cube) else { return };
+    if bound > depth { return }
+    if depth == 0 { return found(cube, path) }
+    for mv in self.next_moves(cube, path) {
+      path.push(mv);
+      self.sequences(&cube.multiply(&turns()[mv]), depth - 1, path, found);
+      path.pop();
+    }
+  }
+
+  // legal turns after the path, never the same face twice or opposite faces in both orders
+  fn next_moves(&self, cube: &CubieCube, path: &[usize]) -> Vec<usize> {
+    let legal = self.legal(cube);
+    let last = path.last().map(|last| Face::from_index(last / 3));
+    self.faces.iter().map(|face| Face::from_index(*face))
+      .filter(|face| legal[face.index()])
+      .filter(|face| last.is_none_or(|last| *face != last && !(*face == last.opposite() && face.index() < last.index())))
+      .flat_map(|face| (0..3).map(move |amount| face.index() * 3 + amount))
+      .collect()
+  }
+
+  // faces every block lies wholly inside or outside of
+  fn legal(&self, cube: &CubieCube) -> [bool; 6] {
+    let bonds: Vec<([i8; 3], [i8; 3])> = self.bonds.iter().map(|(a, b)| (a.position(cube), b.position(cube))).collect();
+    Face::ALL.map(|face| {
+      let (axis, sign) = (face.axis().index(), face.normal()[face.axis().index()]);
+      bonds.iter().all(|(a, b)| (a[axis] == sign) == (b[axis] == sign))
+    })
+  }
+}
+
+// each face turn on the pieces, in the order the piece tables use: U U2 U' R ...
+fn turns() -> &'static [CubieCube; 18] {
+  static TURNS: OnceLock<[CubieCube; 18]> = OnceLock::new();
+  TURNS.get_or_init(|| std::array::from_fn(|mv| {
+    let quarter = CubieCube::face_turn(Face::from_index(mv / 3));
+    (0..mv % 3).fold(*quarter, |turn, _| turn.multiply(quarter))
+  }))
+}
+
+fn move_of(mv: usize) -> Move { Move::face(Face::from_index(mv / 3), (mv % 3) as u8 + 1) }
+
+// cubie coordinates of every corner and edge position
+type Coords = ([[i8; 3]; 8], [[i8; 3]; 12]);
+
+fn cubie_coords() -> &'static Coords {
+  static COORDS: OnceLock<Coords> = OnceLock::new();
+  COORDS.get_or_init(|| {
+    let tables = tables();
+    let at = |stickers: &Vec<u8>| sticker_coords(stickers[0] as usize).0;
+    (tables.corners.each_ref().map(at), tables.edges.each_ref().map(at))
+  })
+}
+
+// every corner and edge position with its place in the cubie order
+fn cubie_positions() -> Vec<([i8; 3], usize)> {
+  let (corners, edges) = cubie_coords();
+  corners.iter().enumerate().chain(edges.iter().enumerate()).map(|(i, p)| (*p, i)).collect()
+}
+
+fn edge(position: [i8; 3]) -> bool { position.iter().filter(|v| **v != 0).count() == 2 }
+
+// what scrambles and solutions are made of
+fn face_moves() -> Vec<Move> {
+  Face::ALL.iter().flat_map(|face| (1..4).map(|amount| Move::face(*face, amount))).collect()
+}
+
+// cubie position of every facelet id, wherever its piece has been turned to
+fn positions(cube: &Cube) -> [[i8; 3]; FACELETS] {
+  let mut at = [[0; 3]; FACELETS];
+  for (position, id) in cube.facelets().iter().enumerate() { at[*id as usize] = sticker_coords(position).0; }
+  at
+}
+
+impl fmt::Display for Bandaging {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let blocks: Vec<String> = self.blocks.iter().map(|block| {
+      block.iter().map(|position| piece_name(*position)).collect::<Vec<_>>().join("+")
+    }).collect();
+    write!(f, "{}", blocks.join(" "))
+  }
+}
+
+impl FromStr for Bandaging {
+  type Err = BandageError;
+
+  fn from_str(s: &str) -> Result<Bandaging, BandageError> {
+    let mut blocks: Vec<Vec<[i8; 3]>> = Vec::new();
+    let mut bonds = Vec::new();
+    for word in s.split_whitespace() {
+      let mut block = Vec::new();
+      for name in word.split('+') {
+        let mut position = [0; 3];
+        for c in name.chars() {
+          let face = Face::from_char(c.to_ascii_uppercase()).ok_or(BandageError::Piece(name.to_string()))?;
+          let k = face.axis().index();
+          if position[k] != 0 { return Err(BandageError::Piece(name.to_string())) }
+          position[k] = face.normal()[k];
+        }
+        if position == [0; 3] { return Err(BandageError::Piece(name.to_string())) }
+        if blocks.iter().chain([&block]).any(|b| b.contains(&position)) { return Err(BandageError::Twice(name.to_string())) }
+        block.push(position);
+      }
+      if block.len() < 2 { return Err(BandageError::Alone(word.to_string())) }
+
+      // every piece joined to the first through neighbours in the block
+      let mut joined = vec![block[0]];
+      while let Some(next) = block.iter().find(|p| !joined.contains(p) && joined.iter().any(|j| neighbours(*j, **p))) {
+        joined.push(*next);
+      }
+      if joined.len() < block.len() { return Err(BandageError::Apart(word.to_string())) }
+
+      for (i, a) in block.iter().enumerate() {
+        for b in &block[i + 1..] {
+          if neighbours(*a, *b) { bonds.push((facelet_of(*a), facelet_of(*b))); }
+        }
+      }
+      blocks.push(block);
+    }
+    if blocks.is_empty() { return Err(BandageError::Empty) }
+    Ok(Bandaging { blocks, bonds })
+  }
+}
+
+/* MARK: ERRORS
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BandageError {
+  Piece(String),
+  Twice(String),
+  Alone(String),
+  Apart(String),
+  Empty,
+}
+
+impl fmt::Display for BandageError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      BandageError::Piece(name) => write!(f, "'{}' is not a corner, edge or centre", name),
+      BandageError::Twice(name) => write!(f, "{} is in more than one block", name),
+      BandageError::Alone(block) => write!(f, "'{}' needs at least two pieces to fuse", block),
+      BandageError::Apart(block) => write!(f, "the pieces of '{}' do not all touch", block),
+      BandageError::Empty => write!(f, "no blocks given"),
+    }
+  }
+}
+
+impl std::error::Error for BandageError {}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+
+  fn turn(s: &str) -> Move { s.parse().unwrap() }
+
+  #[test]
+  fn reads_and_prints_blocks() {
+    for preset in BANDAGES {
+      let bandaging: Bandaging = preset.blocks.parse().unwrap();
+      assert_eq!(bandaging.to_string().parse(), Ok(bandaging));
+    }
+    assert_eq!("UFR+UX".parse::<Bandaging>(), Err(BandageError::Piece("UX".to_string())));
+    assert_eq!("UFR+UF UF+UL".parse::<Bandaging>(), Err(BandageError::Twice("UF".to_string())));
+    assert_eq!("UFR".parse::<Bandaging>(), Err(BandageError::Alone("UFR".to_string())));
+    assert_eq!("UFR+DB".parse::<Bandaging>(), Err(BandageError::Apart("UFR+DB".to_string())));
+    assert_eq!(" ".parse::<Bandaging>(), Err(BandageError::Empty));
+  }
+
+  #[test]
+  fn blocks_turns_that_split_a_block() {
+    let bandaging: Bandaging = "UFR+UF".parse().unwrap();
+    let solved = Cube::solved();
+    assert!(bandaging.allows(&solved, turn("U")));
+    assert!(bandaging.allows(&solved, turn("F2")));
+    assert!(bandaging.allows(&solved, turn("L'")));
+    assert!(!bandaging.allows(&solved, turn("R")));
+    assert!(!bandaging.allows(&solved, turn("M")));
+    assert!(bandaging.allows(&solved, turn("x")));
+    assert_eq!(bandaging.legal_moves(&solved).len(), 15);
+
+    // F takes the pair down to FR and DFR, wholly inside R and now across D
+    let turned = Cube::from_alg(&"F".parse().unwrap());
+    assert!(bandaging.allows(&turned, turn("R")));
+    assert!(bandaging.allows(&turned, turn("U")));
+    assert!(!bandaging.allows(&turned, turn("D")));
+  }
+
+  #[test]
+  fn solves_with_legal_turns_only() {
+    let mut rng = ChaCha8Rng::seed_from_u64(10);
+    for preset in BANDAGES {
+      let bandaging: Bandaging = preset.blocks.parse().unwrap();
+      for _ in 0..3 {
+        let scramble = bandaging.random_moves(&mut rng, &Cube::solved(), 40);
+        let mut cube = Cube::from_alg(&scramble);
+        assert!(scramble.iter().scan(Cube::solved(), |cube, mv| {
+          let allowed = bandaging.allows(cube, *mv);
+          cube.apply_move(*mv);
+          Some(allowed)
+        }).all(|allowed| allowed));
+
+        let solution = bandaging.solve(&cube).unwrap();
+        for mv in &solution {
+          assert!(bandaging.allows(&cube, *mv), "{} is blocked in the {} solution", mv, preset.name);
+          cube.apply_move(*mv);
+        }
+        assert!(cube.is_solved_any_orientation(), "the {} is not solved", preset.name);
+      }
+    }
+  }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cube::{tables, Cube};
+use crate::cubie::{CubieCube, DF, UF, ULB, UR, URF};
+
+/* MARK: LETTERING
+  one letter per corner and edge sticker. letters run face by face in the order U L F R B D,
+  clockwise round each face from the top left (U seen with B on top, D with F on top),
+  which makes the default Speffz
+*/
+const LETTER_FACES: [usize; 6] = [0, 4, 2, 1, 5, 3];
+const CORNER_SPOTS: [usize; 4] = [0, 2, 8, 6];
+const EDGE_SPOTS: [usize; 4] = [1, 5, 7, 3];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lettering {
+  pub corners: [char; 24],
+  pub edges: [char; 24],
+}
+
+impl Default for Lettering {
+  fn default() -> Self { Lettering::speffz() }
+}
+
+impl Lettering {
+  pub fn speffz() -> Lettering {
+    let letters = std::array::from_fn(|i| (b'A' + i as u8) as char);
+    Lettering { corners: letters, edges: letters }
+  }
+
+  fn letter(&self, piece: Piece, facelet: u8) -> char {
+    let index = letter_index(piece, facelet);
+    match piece { Piece::Corner => self.corners[index], Piece::Edge => self.edges[index] }
+  }
+}
+
+// place of a sticker in the lettering order
+fn letter_index(piece: Piece, facelet: u8) -> usize {
+  let (face, spot) = (facelet as usize / 9, facelet as usize % 9);
+  let spots = match piece { Piece::Corner => CORNER_SPOTS, Piece::Edge => EDGE_SPOTS };
+  let order = LETTER_FACES.iter().position(|f| *f == face).unwrap_or(0);
+  order * 4 + spots.iter().position(|s| *s == spot).unwrap_or(0)
+}
+
+// corner letters then edge letters, 24 of each
+impl FromStr for Lettering {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let [corners, edges] = words.as_slice() else { return Err("expected corner and edge letters".to_string()) };
+    let letters = |word: &str| -> Result<[char; 24], String> {
+      let chars: Vec<char> = word.chars().collect();
+      chars.try_into().map_err(|_| format!("'{}' is not 24 letters", word))
+    };
+    Ok(Lettering { corners: letters(corners)?, edges: letters(edges)? })
+  }
+}
+
+/* MARK: METHODS
+  which stickers the targets are shot from
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlindMethod {
+  #[default]
+  OldPochmann,
+  M2,
+  ThreeStyle,
+}
+
+impl BlindMethod {
+  pub const ALL: [BlindMethod; 3] = [BlindMethod::OldPochmann, BlindMethod::M2, BlindMethod::ThreeStyle];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      BlindMethod::OldPochmann => "Old Pochmann",
+      BlindMethod::M2 => "M2 / OP corners",
+      BlindMethod::ThreeStyle => "3-style",
+    }
+  }
+
+  pub fn next(self) -> BlindMethod { BlindMethod::ALL[(self as usize + 1) % BlindMethod::ALL.len()] }
+
+  // corner and edge buffer positions, always their U or D sticker
+  fn buffers(self) -> (usize, usize) {
+    match self {
+      BlindMethod::OldPochmann => (ULB, UR),
+      BlindMethod::M2 => (ULB, DF),
+      BlindMethod::ThreeStyle => (URF, UF),
+    }
+  }
+}
+
+impl fmt::Display for BlindMethod {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.name()) }
+}
+
+/* MARK: MEMO
+  targets in the order they are shot to the buffer, traced by doing the swaps on a copy:
+  whatever sits in the buffer goes home, and when the buffer holds its own piece a new
+  cycle starts at the first unsolved piece in letter order
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Memo {
+  pub edges: Vec<char>,
+  pub corners: Vec<char>,
+}
+
+impl Memo {
+  // an odd number of edge targets leaves two edges and two corners swapped for a parity alg
+  pub fn parity(&self) -> bool { self.edges.len() % 2 == 1 }
+}
+
+// letters grouped in pairs, "AB CD E"
+pub fn letter_pairs(letters: &[char]) -> String {
+  letters.chunks(2).map(|pair| pair.iter().collect::<String>()).collect::<Vec<_>>().join(" ")
+}
+
+impl fmt::Display for Memo {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "edges {}\ncorners {}", letter_pairs(&self.edges), letter_pairs(&self.corners))?;
+    if self.parity() { write!(f, "\nparity")?; }
+    Ok(())
+  }
+}
+
+pub fn memo(cube: &Cube, method: BlindMethod, lettering: &Lettering) -> Memo {
+  let cubie = CubieCube::from_cube(cube);
+  let (corner_buffer, edge_buffer) = method.buffers();
+  let mut corners = Pieces { kind: Piece::Corner, perm: cubie.cp.to_vec(), ori: cubie.co.to_vec() };
+  let mut edges = Pieces { kind: Piece::Edge, perm: cubie.ep.to_vec(), ori: cubie.eo.to_vec() };
+  Memo {
+    edges: edges.trace(edge_buffer, lettering),
+    corners: corners.trace(corner_buffer, lettering),
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece { Corner, Edge }
+
+struct Pieces {
+  kind: Piece,
+  perm: Vec<u8>,
+  ori: Vec<u8>,
+}
+
+impl Pieces {
+  fn twists(&self) -> u8 { match self.kind { Piece::Corner => 3, Piece::Edge => 2 } }
+
+  fn facelet(&self, position: usize, sticker: usize) -> u8 {
+    let tables = tables();
+    match self.kind { Piece::Corner => tables.corners[position][sticker], Piece::Edge => tables.edges[position][sticker] }
+  }
+
+  fn solved(&self, position: usize) -> bool { self.perm[position] as usize == position && self.ori[position] == 0 }
+
+  // home position and sticker of what shows on the buffer's U/D sticker
+  fn in_buffer(&self, buffer: usize) -> (usize, usize) {
+    let twists = self.twists();
+    (self.perm[buffer] as usize, ((twists - self.ori[buffer]) % twists) as usize)
+  }
+
+  // swap the buffer with a position, its U/D sticker trading places with `sticker` there.
+  // the buffer piece usually lands home, on a cycle break it is the buffer's own piece
+  fn shoot(&mut self, buffer: usize, position: usize, sticker: usize) {
+    let twists = self.twists();
+    let (piece, ori) = (self.perm[position], self.ori[position]);
+    self.perm[position] = self.perm[buffer];
+    self.ori[position] = (self.ori[buffer] + sticker as u8) % twists;
+    self.perm[buffer] = piece;
+    self.ori[buffer] = (ori + twists - sticker as u8) % twists;
+  }
+
+  fn trace(&mut self, buffer: usize, lettering: &Lettering) -> Vec<char> {
+    let mut letters = Vec::new();
+    // each shot solves a piece or starts a cycle, so this is never reached
+    for _ in 0..self.perm.len() * 3 {
+      let (mut position, mut sticker) = self.in_buffer(buffer);
+      if position == buffer {
+        let Some(start) = self.cycle_start(buffer) else { break };
+        (position, sticker) = start;
+      }
+      letters.push(lettering.letter(self.kind, self.facelet(position, sticker)));
+      self.shoot(buffer, position, sticker);
+    }
+    letters
+  }
+
+  // first unsolved sticker in letter order, skipping the buffer
+  fn cycle_start(&self, buffer: usize) -> Option<(usize, usize)> {
+    let twists = self.twists() as usize;
+    (0..self.perm.len())
+      .filter(|position| *position != buffer && !self.solved(*position))
+      .flat_map(|position| (0..twists).map(move |sticker| (position, sticker)))
+      .min_by_key(|(position, sticker)| letter_index(self.kind, self.facelet(*position, *sticker)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::moves::Alg;
+
+  fn memo_of(moves: &str, method: BlindMethod) -> Memo {
+    memo(&Cube::from_alg(&moves.parse::<Alg>().unwrap()), method, &Lettering::speffz())
+  }
+
+  fn letters(s: &str) -> Vec<char> { s.chars().collect() }
+
+  #[test]
+  fn solved_needs_nothing() {
+    for method in BlindMethod::ALL { assert_eq!(memo_of("", method), Memo::default()); }
+  }
+
+  #[test]
+  fn traces_a_u_turn_from_each_buffer() {
+    // UF -> UL -> UB -> UR and the same for the corners
+    let op = memo_of("U", BlindMethod::OldPochmann);
+    assert_eq!((op.edges, op.corners), (letters("ADC"), letters("DCB")));
+    // the DF buffer is solved, so its cycle starts at A and comes back to it
+    let m2 = memo_of("U", BlindMethod::M2);
+    assert_eq!((m2.edges, m2.corners), (letters("ADCBA"), letters("DCB")));
+    let three_style = memo_of("U", BlindMethod::ThreeStyle);
+    assert_eq!((three_style.edges, three_style.corners), (letters("BAD"), letters("BAD")));
+  }
+
+  #[test]
+  fn t_perm_has_parity() {
+    let memo = memo_of("R U R' U' R' F R2 U' R' U' R U R' F'", BlindMethod::OldPochmann);
+    // UL into the UR buffer, and UFR and UBR swapped as a cycle break from the solved ULB buffer
+    assert_eq!((memo.edges.clone(), memo.corners.clone()), (letters("D"), letters("BCB")));
+    assert!(memo.parity());
+    assert_eq!(memo.to_string(), "edges D\ncorners BC B\nparity");
+    // from the UFR buffer the corner swap is a single target
+    assert_eq!(memo_of("R U R' U' R' F R2 U' R' U' R U R' F'", BlindMethod::ThreeStyle).corners, letters("B"));
+  }
+
+  #[test]
+  fn u_perm_has_no_parity() {
+    let memo = memo_of("R2 U R U R' U' R' U' R' U R'", BlindMethod::OldPochmann);
+    assert_eq!((memo.edges.clone(), memo.corners.clone()), (letters("DC"), Vec::new()));
+    assert!(!memo.parity());
+  }
+
+  #[test]
+  fn reads_custom_letters() {
+    let lettering: Lettering = "abcdefghijklmnopqrstuvwx ABCDEFGHIJKLMNOPQRSTUVWX".parse().unwrap();
+    let memo = memo(&Cube::from_alg(&"U".parse::<Alg>().unwrap()), BlindMethod::OldPochmann, &lettering);
+    assert_eq!((memo.edges, memo.corners), (letters("ADC"), letters("dcb")));
+    assert!("abc ABC".parse::<Lettering>().is_err());
+    assert!("abcdefghijklmnopqrstuvwx".parse::<Lettering>().is_err());
+  }
+
+  #[test]
+  fn pairs_letters() {
+    assert_eq!(letter_pairs(&letters("ADCBA")), "AD CB A");
+    assert_eq!(letter_pairs(&[]), "");
+  }
+}
@@ -0,0 +1,328 @@
+use rand_core::RngCore;
+
+use crate::cube::Cube;
+use crate::cubie::CubieCube;
+use crate::face::{Axis, Face};
+use crate::moves::{Alg, Move, Turn};
+use crate::solver::pieces;
+
+/* MARK: CASES
+  named cases with one common algorithm each, solved with the D face down and F in front
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Case {
+  pub name: &'static str,
+  pub alg: &'static str,
+}
+
+impl Case {
+  pub fn alg(&self) -> Alg { self.alg.parse().unwrap_or_default() }
+
+  // turns from solved into the case, rotations in the algorithm folded into the moves
+  pub fn setup(&self) -> Alg { self.alg().without_rotations().inverse() }
+
+  // last layer seen from above: the U face in the middle, the top row of each side around it
+  // (back at the top), stickers named after the centre they match, None in the corners
+  pub fn top_layer(&self) -> [[Option<Face>; 5]; 5] {
+    let colors = Cube::from_alg(&self.setup()).colors();
+    let sticker = |face: Face, index: usize| Some(colors[face.index() * 9 + index]);
+    std::array::from_fn(|row| std::array::from_fn(|col| match (row, col) {
+      (1..=3, 1..=3) => sticker(Face::U, (row - 1) * 3 + col - 1),
+      (0, 1..=3) => sticker(Face::B, 3 - col),
+      (4, 1..=3) => sticker(Face::F, col - 1),
+      (1..=3, 0) => sticker(Face::L, row - 1),
+      (1..=3, 4) => sticker(Face::R, 3 - row),
+      _ => None,
+    }))
+  }
+
+  // the U, F and R faces unfolded around the front right corner: U at the top left,
+  // F below it and R to its right, enough to see where the front right pair is
+  pub fn front_right(&self) -> [[Option<Face>; 6]; 6] {
+    let colors = Cube::from_alg(&self.setup()).colors();
+    let sticker = |face: Face, index: usize| Some(colors[face.index() * 9 + index]);
+    std::array::from_fn(|row| std::array::from_fn(|col| match (row, col) {
+      (0..=2, 0..=2) => sticker(Face::U, row * 3 + col),
+      (3..=5, 0..=2) => sticker(Face::F, (row - 3) * 3 + col),
+      (0..=2, 3..=5) => sticker(Face::R, (col - 3) * 3 + 2 - row),
+      _ => None,
+    }))
+  }
+}
+
+/* MARK: PLL
+*/
+pub const PLL: [Case; 21] = [
+  Case { name: "Aa", alg: "x R' U R' D2 R U' R' D2 R2 x'" },
+  Case { name: "Ab", alg: "x R2 D2 R U R' D2 R U' R x'" },
+  Case { name: "E", alg: "x' R U' R' D R U R' D' R U R' D R U' R' D' x" },
+  Case { name: "F", alg: "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R" },
+  Case { name: "Ga", alg: "R2 U R' U R' U' R U' R2 U' D R' U R D'" },
+  Case { name: "Gb", alg: "R' U' R U D' R2 U R' U R U' R U' R2 D" },
+  Case { name: "Gc", alg: "R2 U' R U' R U R' U R2 U D' R U' R' D" },
+  Case { name: "Gd", alg: "R U R' U' D R2 U' R U' R' U R' U R2 D'" },
+  Case { name: "H", alg: "M2 U M2 U2 M2 U M2" },
+  Case { name: "Ja", alg: "x R2 F R F' R U2 r' U r U2 x'" },
+  Case { name: "Jb", alg: "R U R' F' R U R' U' R' F R2 U' R'" },
+  Case { name: "Na", alg: "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'" },
+  Case { name: "Nb", alg: "R' U R U' R' F' U' F R U R' F R' F' R U' R" },
+  Case { name: "Ra", alg: "R U' R' U' R U R D R' U' R D' R' U2 R'" },
+  Case { name: "Rb", alg: "R2 F R U R U' R' F' R U2 R' U2 R" },
+  Case { name: "T", alg: "R U R' U' R' F R2 U' R' U' R U R' F'" },
+  Case { name: "Ua", alg: "M2 U M U2 M' U M2" },
+  Case { name: "Ub", alg: "M2 U' M U2 M' U' M2" },
+  Case { name: "V", alg: "R' U R' U' y R' F' R2 U' R' U R' F R F" },
+  Case { name: "Y", alg: "F R U' R' U' R U R' F' R U R' U' R' F R F'" },
+  Case { name: "Z", alg: "M' U M2 U M2 U M' U2 M2" },
+];
+
+/* MARK: OLL
+  numbered as in the usual charts
+*/
+pub const OLL: [Case; 57] = [
+  Case { name: "1", alg: "R U2 R2 F R F' U2 R' F R F'" },
+  Case { name: "2", alg: "r U r' U2 r U2 R' U2 R U' r'" },
+  Case { name: "3", alg: "r' R2 U R' U r U2 r' U M'" },
+  Case { name: "4", alg: "M U' r U2 r' U' R U' R' M'" },
+  Case { name: "5", alg: "l' U2 L U L' U l" },
+  Case { name: "6", alg: "r U2 R' U' R U' r'" },
+  Case { name: "7", alg: "r U R' U R U2 r'" },
+  Case { name: "8", alg: "l' U' L U' L' U2 l" },
+  Case { name: "9", alg: "R U R' U' R' F R2 U R' U' F'" },
+  Case { name: "10", alg: "R U R' U R' F R F' R U2 R'" },
+  Case { name: "11", alg: "r U R' U R' F R F' R U2 r'" },
+  Case { name: "12", alg: "M' R' U' R U' R' U2 R U' R r'" },
+  Case { name: "13", alg: "F U R U' R2 F' R U R U' R'" },
+  Case { name: "14", alg: "R' F R U R' F' R F U' F'" },
+  Case { name: "15", alg: "l' U' l L' U' L U l' U l" },
+  Case { name: "16", alg: "r U r' R U R' U' r U' r'" },
+  Case { name: "17", alg: "F R' F' R2 r' U R U' R' U' M'" },
+  Case { name: "18", alg: "r U R' U R U2 r2 U' R U' R' U2 r" },
+  Case { name: "19", alg: "r' R U R U R' U' M' R' F R F'" },
+  Case { name: "20", alg: "r U R' U' M2 U R U' R' U' M'" },
+  Case { name: "21", alg: "R U2 R' U' R U R' U' R U' R'" },
+  Case { name: "22", alg: "R U2 R2 U' R2 U' R2 U2 R" },
+  Case { name: "23", alg: "R2 D' R U2 R' D R U2 R" },
+  Case { name: "24", alg: "r U R' U' r' F R F'" },
+  Case { name: "25", alg: "F' r U R' U' r' F R" },
+  Case { name: "26", alg: "R U2 R' U' R U' R'" },
+  Case { name: "27", alg: "R U R' U R U2 R'" },
+  Case { name: "28", alg: "r U R' U' r' R U R U' R'" },
+  Case { name: "29", alg: "R U R' U' R U' R' F' U' F R U R'" },
+  Case { name: "30", alg: "F R' F R2 U' R' U' R U R' F2" },
+  Case { name: "31", alg: "R' U' F U R U' R' F' R" },
+  Case { name: "32", alg: "L U F' U' L' U L F L'" },
+  Case { name: "33", alg: "R U R' U' R' F R F'" },
+  Case { name: "34", alg: "R U R2 U' R' F R U R U' F'" },
+  Case { name: "35", alg: "R U2 R2 F R F' R U2 R'" },
+  Case { name: "36", alg: "L' U' L U' L' U L U L F' L' F" },
+  Case { name: "37", alg: "F R' F' R U R U' R'" },
+  Case { name: "38", alg: "R U R' U R U' R' U' R' F R F'" },
+  Case { name: "39", alg: "L F' L' U' L U F U' L'" },
+  Case { name: "40", alg: "R' F R U R' U' F' U R" },
+  Case { name: "41", alg: "R U R' U R U2 R' F R U R' U' F'" },
+  Case { name: "42", alg: "R' U' R U' R' U2 R F R U R' U' F'" },
+  Case { name: "43", alg: "F' U' L' U L F" },
+  Case { name: "44", alg: "F U R U' R' F'" },
+  Case { name: "45", alg: "F R U R' U' F'" },
+  Case { name: "46", alg: "R' U' R' F R F' U R" },
+  Case { name: "47", alg: "R' U' R' F R F' R' F R F' U R" },
+  Case { name: "48", alg: "F R U R' U' R U R' U' F'" },
+  Case { name: "49", alg: "r U' r2 U r2 U r2 U' r" },
+  Case { name: "50", alg: "r' U r2 U' r2 U' r2 U r'" },
+  Case { name: "51", alg: "F U R U' R' U R U' R' F'" },
+  Case { name: "52", alg: "R U R' U R U' B U' B' R'" },
+  Case { name: "53", alg: "l' U2 L U L' U' L U L' U l" },
+  Case { name: "54", alg: "r U2 R' U' R U R' U' R U' r'" },
+  Case { name: "55", alg: "R' F R U R U' R2 F' R2 U' R' U R U R'" },
+  Case { name: "56", alg: "r' U' r U' R' U R U' R' U R r' U r" },
+  Case { name: "57", alg: "R U R' U' M' U R U' r'" },
+];
+
+/* MARK: F2L
+  the 41 ways the front right pair can sit with the cross and the other pairs solved, up to
+  U turns. numbered by where the pieces are rather than as in the usual charts: both in the
+  top layer (1-24), edge in the slot (25-30), corner in the slot (31-36), both in the slot
+  (37-41). each has its shortest <R, U, F> solution, <R, U> when that is as short
+*/
+pub const F2L: [Case; 41] = [
+  Case { name: "1", alg: "R U2 R' U' R U R'" },
+  Case { name: "2", alg: "U2 F2 U2 F U F' U F2" },
+  Case { name: "3", alg: "U2 R2 U2 R' U' R U' R2" },
+  Case { name: "4", alg: "F' U2 F U F' U' F" },
+  Case { name: "5", alg: "R U' R' U2 R U R'" },
+  Case { name: "6", alg: "U' F' U2 F2 R' F' R" },
+  Case { name: "7", alg: "U R U2 R2 F R F'" },
+  Case { name: "8", alg: "U2 R U R' F' U' F" },
+  Case { name: "9", alg: "U2 R2 U R' U R U2 R2" },
+  Case { name: "10", alg: "R U' R' U2 F' U' F" },
+  Case { name: "11", alg: "R' U2 R2 U R2 U R" },
+  Case { name: "12", alg: "U2 F' U2 F" },
+  Case { name: "13", alg: "U2 R U' R' U' R U R'" },
+  Case { name: "14", alg: "R' U' R F R' F' U R" },
+  Case { name: "15", alg: "R U R'" },
+  Case { name: "16", alg: "R2 U2 F R2 F' U2 R2" },
+  Case { name: "17", alg: "U R U' R'" },
+  Case { name: "18", alg: "F U2 F2 U' F2 U' F'" },
+  Case { name: "19", alg: "F' U F U2 R U R'" },
+  Case { name: "20", alg: "R U R2 F R F2 U' F" },
+  Case { name: "21", alg: "F2 U2 R' F2 R U2 F2" },
+  Case { name: "22", alg: "F' U' F" },
+  Case { name: "23", alg: "U' R U R' U2 R U' R'" },
+  Case { name: "24", alg: "U R U R' U2 F' U' F" },
+  Case { name: "25", alg: "R2 U R2 U R2 U2 R2" },
+  Case { name: "26", alg: "R U' R' F' U2 F" },
+  Case { name: "27", alg: "U R U R' U2 R U R'" },
+  Case { name: "28", alg: "U F' U' F U' R U R'" },
+  Case { name: "29", alg: "U' R U' R' U2 R U' R'" },
+  Case { name: "30", alg: "U2 R U R' U2 F' U2 F" },
+  Case { name: "31", alg: "R F R' U R U' F' R'" },
+  Case { name: "32", alg: "R U2 R' U2 F' U' F" },
+  Case { name: "33", alg: "R U' R2 F R F'" },
+  Case { name: "34", alg: "U2 R' F R F2 U' F" },
+  Case { name: "35", alg: "R U R' U' R U R'" },
+  Case { name: "36", alg: "U R U2 R' F' U2 F" },
+  Case { name: "37", alg: "R U' R U2 F R2 F' U2 R2" },
+  Case { name: "38", alg: "R2 U2 R' U' R U' R' U2 R'" },
+  Case { name: "39", alg: "R F U R U' R' F' U' R'" },
+  Case { name: "40", alg: "R U2 R U R' U R U2 R2" },
+  Case { name: "41", alg: "R U F R U R' U' F' R'" },
+];
+
+// first two layer slots, in the order the solver numbers its pairs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot { Fr, Fl, Bl, Br }
+
+impl Slot {
+  pub const ALL: [Slot; 4] = [Slot::Fr, Slot::Fl, Slot::Bl, Slot::Br];
+
+  pub fn name(self) -> &'static str { ["FR", "FL", "BL", "BR"][self as usize] }
+
+  // the same turns done on this slot instead of the front right one
+  pub fn conjugate(self, alg: &Alg) -> Alg {
+    let y = Move::new(Turn::Rotate(Axis::Y), [0, 3, 2, 1][self as usize]);
+    Alg(vec![y]).concat(alg).concat(&Alg(vec![y.inverse()])).without_rotations()
+  }
+
+  // the slot that sits here once the cube is turned by `turns` quarter y rotations
+  fn after_y(self, turns: u8) -> Slot { Slot::ALL[(self as usize + 4 - turns as usize % 4) % 4] }
+}
+
+// move sets the solutions after a drill are searched in
+const F2L_MOVE_SETS: [(&str, &[Face]); 3] = [
+  ("<R, U>", &[Face::R, Face::U]),
+  ("<R, U, F>", &[Face::R, Face::U, Face::F]),
+  ("any face", &Face::ALL),
+];
+const F2L_MAX_LENGTH: usize = 14;
+
+/* MARK: DRILL SETUP
+  case seen from a random angle: random U turns either side, optionally after a random y
+*/
+pub fn drill_setup(rng: &mut impl RngCore, case: &Case, random_y: bool) -> Alg {
+  let setup = auf_setup(rng, case);
+  with_random_y(rng, setup, random_y)
+}
+
+// an F2L case in the front right slot, with `extra` holding some other pair out of its slot as well
+pub fn f2l_setup(rng: &mut impl RngCore, case: &Case, extra: Option<Slot>, random_y: bool) -> Alg {
+  let mut setup = Alg::new();
+  if let Some(slot) = extra {
+    // the case setup only moves the top layer and the front right slot, so this pair stays out
+    let other = &F2L[rng.next_u32() as usize % F2L.len()];
+    setup = slot.conjugate(&other.setup());
+  }
+  let setup = setup.concat(&auf_setup(rng, case)).simplify();
+  with_random_y(rng, setup, random_y)
+}
+
+fn auf_setup(rng: &mut impl RngCore, case: &Case) -> Alg {
+  let before = Move::face(Face::U, (rng.next_u32() % 4) as u8);
+  let after = Move::face(Face::U, (rng.next_u32() % 4) as u8);
+  Alg(vec![before]).concat(&case.setup()).concat(&Alg(vec![after])).simplify()
+}
+
+fn with_random_y(rng: &mut impl RngCore, mut setup: Alg, random_y: bool) -> Alg {
+  let y = (rng.next_u32() % 4) as u8;
+  if random_y && y != 0 {
+    setup.0.insert(0, Move::new(Turn::Rotate(Axis::Y), y));
+  }
+  setup
+}
+
+// the drilled pair is back with the cross and every other pair but `extra`, however the cube is held
+pub fn f2l_done(cube: &Cube, setup: &Alg, extra: Option<Slot>) -> bool {
+  let turns = setup.iter().filter(|mv| mv.is_rotation()).map(|mv| mv.amount).sum();
+  let unsolved: Vec<usize> = extra.into_iter().map(|slot| slot.after_y(turns) as usize).collect();
+  pieces::is_solved(&CubieCube::from_cube(cube), &pieces::f2l_goal(&unsolved))
+}
+
+/* MARK: F2L SOLUTIONS
+  shortest ways to put the front right pair back after an F2L setup, one per move set that
+  finds something shorter. the turns after a random y are named for the cube as it ends up
+  held, so the search starts from them alone and its turns read the same way
+*/
+pub fn f2l_solutions(setup: &Alg, extra: Option<Slot>) -> Vec<(&'static str, Alg)> {
+  let turns = Alg(setup.iter().filter(|mv| !mv.is_rotation()).copied().collect());
+  let cube = CubieCube::from_cube(&Cube::from_alg(&turns));
+  let unsolved: Vec<usize> = extra.into_iter().map(|slot| slot as usize).collect();
+  let goal = pieces::f2l_goal(&unsolved);
+
+  let mut solutions: Vec<(&'static str, Alg)> = Vec::new();
+  for (name, faces) in F2L_MOVE_SETS {
+    let Some(alg) = pieces::solve_pieces(&cube, &goal, faces, F2L_MAX_LENGTH, 1).pop() else { continue };
+    if solutions.last().is_some_and(|(_, last)| last.len() <= alg.len()) { continue }
+    solutions.push((name, alg));
+  }
+  solutions
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+
+  fn assert_solves_setups(cases: &[Case]) {
+    for case in cases {
+      assert!(case.alg.parse::<Alg>().is_ok(), "{} does not parse", case.name);
+      let mut cube = Cube::from_alg(&case.setup());
+      assert!(!cube.is_solved(), "{} sets up nothing", case.name);
+      cube.apply_alg(&case.alg());
+      assert!(cube.is_solved_any_orientation(), "{} does not solve its setup", case.name);
+    }
+  }
+
+  #[test]
+  fn pll_algorithms_solve_their_setups() { assert_solves_setups(&PLL) }
+
+  #[test]
+  fn oll_algorithms_solve_their_setups() { assert_solves_setups(&OLL) }
+
+  #[test]
+  fn f2l_algorithms_solve_their_setups() { assert_solves_setups(&F2L) }
+
+  #[test]
+  fn f2l_solutions_put_the_pair_back() {
+    let mut rng = ChaCha8Rng::seed_from_u64(4);
+    for (i, case) in F2L.iter().enumerate() {
+      let extra = Slot::ALL.get(i % 5).copied().filter(|slot| *slot != Slot::Fr);
+      let setup = f2l_setup(&mut rng, case, extra, i % 2 == 0);
+      let cube = Cube::from_alg(&setup);
+      assert!(!f2l_done(&cube, &setup, extra), "{} starts solved", case.name);
+
+      let solutions = f2l_solutions(&setup, extra);
+      assert!(!solutions.is_empty(), "nothing for {}", case.name);
+      // each one found is shorter than the one before, the last no longer than the case's own
+      // with the random U turns either side of it
+      assert!(solutions.windows(2).all(|pair| pair[1].1.len() < pair[0].1.len()));
+      assert!(solutions.last().is_some_and(|(_, alg)| alg.len() <= case.alg().without_rotations().len() + 2));
+      for (_, alg) in &solutions {
+        let mut solved = cube;
+        solved.apply_alg(alg);
+        assert!(f2l_done(&solved, &setup, extra), "{} does not solve {}", alg, case.name);
+      }
+    }
+  }
+}
@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cube::{tables, Cube, FACELETS};
+use crate::face::Face;
+use crate::moves::{commutator, conjugate, Alg, Move, Slice, Turn};
+
+/* MARK: COMMUTATOR
+  [setup: [a, b]] kept in its parts, so it can be shown the way it is built as well as flat
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commutator {
+  pub setup: Alg,
+  pub a: Alg,
+  pub b: Alg,
+}
+
+impl Commutator {
+  pub fn expand(&self) -> Alg { conjugate(&self.setup, &commutator(&self.a, &self.b)) }
+}
+
+// [R U R', D] or [U: [R U R', D]]
+impl fmt::Display for Commutator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.setup.is_empty() { return write!(f, "[{}, {}]", self.a, self.b) }
+    write!(f, "[{}: [{}, {}]]", self.setup, self.a, self.b)
+  }
+}
+
+/* MARK: STICKERS
+  a corner or edge sticker named by the face it is on followed by the rest of its piece,
+  so UFR is the U sticker of the URF corner and FUR the F sticker of the same corner
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sticker {
+  pub corner: bool,
+  // position in the cubie order and which of its stickers, U/D first then clockwise
+  pub position: usize,
+  pub index: usize,
+}
+
+impl Sticker {
+  fn facelets(self) -> &'static [u8] {
+    let tables = tables();
+    if self.corner { &tables.corners[self.position] } else { &tables.edges[self.position] }
+  }
+
+  pub fn facelet(self) -> u8 { self.facelets()[self.index] }
+}
+
+impl fmt::Display for Sticker {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let facelets = self.facelets();
+    for k in 0..facelets.len() {
+      write!(f, "{}", Face::from_index(facelets[(self.index + k) % facelets.len()] as usize / 9))?;
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for Sticker {
+  type Err = CommutatorError;
+
+  fn from_str(s: &str) -> Result<Sticker, CommutatorError> {
+    let faces: Vec<Face> = s.chars().map(|c| Face::from_char(c.to_ascii_uppercase()))
+      .collect::<Option<_>>().ok_or(CommutatorError::Sticker(s.to_string()))?;
+    let tables = tables();
+    let (corner, pieces) = match faces.len() {
+      3 => (true, &tables.corners[..]),
+      2 => (false, &tables.edges[..]),
+      _ => return Err(CommutatorError::Sticker(s.to_string())),
+    };
+    let face_of = |facelet: &u8| Face::from_index(*facelet as usize / 9);
+    for (position, facelets) in pieces.iter().enumerate() {
+      if !faces.iter().all(|face| facelets.iter().any(|facelet| face_of(facelet) == *face)) { continue }
+      let index = facelets.iter().position(|facelet| face_of(facelet) == faces[0]).unwrap_or(0);
+      return Ok(Sticker { corner, position, index })
+    }
+    Err(CommutatorError::Sticker(s.to_string()))
+  }
+}
+
+/* MARK: ERRORS
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommutatorError {
+  Sticker(String),
+  Count(usize),
+  Mixed,
+  SamePiece,
+}
+
+impl fmt::Display for CommutatorError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      CommutatorError::Sticker(name) => write!(f, "'{}' is not a corner or edge sticker", name),
+      CommutatorError::Count(count) => write!(f, "expected three stickers, found {}", count),
+      CommutatorError::Mixed => write!(f, "the stickers must all be corners or all be edges"),
+      CommutatorError::SamePiece => write!(f, "the stickers must be on three different pieces"),
+    }
+  }
+}
+
+impl std::error::Error for CommutatorError {}
+
+/* MARK: THREE CYCLES
+  the piece on the first sticker goes to the second, the second to the third and the third back
+  to the first. found by brute force over [setup: [a, b]] with a of up to three moves, looking
+  each commutator up among the cycle conjugated by every setup. b starts as a single move with
+  setups of up to two, and the search widens until something is found. slices are only tried
+  for edges
+*/
+pub const FIND_LIMIT: usize = 12;
+
+const MAX_A: usize = 3;
+// longest setup and b of each search, tried in turn until one finds a commutator
+const SEARCHES: [(usize, usize); 3] = [(2, 1), (3, 1), (2, 2)];
+
+pub fn three_cycle(input: &str) -> Result<[Sticker; 3], CommutatorError> {
+  let stickers: Vec<Sticker> = input.split_whitespace().map(str::parse).collect::<Result<_, _>>()?;
+  let stickers: [Sticker; 3] = stickers.try_into().map_err(|v: Vec<Sticker>| CommutatorError::Count(v.len()))?;
+  if stickers.iter().any(|s| s.corner != stickers[0].corner) { return Err(CommutatorError::Mixed) }
+  let [a, b, c] = stickers.map(|s| s.position);
+  if a == b || b == c || a == c { return Err(CommutatorError::SamePiece) }
+  Ok(stickers)
+}
+
+// shortest commutators first by their moves once cancelled, at most FIND_LIMIT of them
+pub fn find(stickers: &[Sticker; 3]) -> Vec<Commutator> {
+  let target = cycle_effect(stickers);
+  let moves = move_set(!stickers[0].corner);
+  SEARCHES.iter().map(|(max_setup, max_b)| search(&target, &moves, *max_setup, *max_b))
+    .find(|found| !found.is_empty())
+    .unwrap_or_default()
+}
+
+fn search(target: &Effect, moves: &[Move], max_setup: usize, max_b: usize) -> Vec<Commutator> {
+  // body each setup needs for the whole thing to be the cycle: S' T S. only the shortest setups
+  // for each body are kept, the longer ones just turn pieces the cycle leaves alone
+  let mut bodies: HashMap<Effect, Vec<Alg>> = HashMap::new();
+  for setup in sequences(moves, max_setup) {
+    let effect = Effect::of(&setup);
+    let setups = bodies.entry(effect.inverse().then(target).then(&effect)).or_default();
+    if setups.first().is_none_or(|first| first.len() == setup.len()) { setups.push(setup); }
+  }
+  let bs: Vec<(Alg, Effect)> = sequences(moves, max_b).into_iter().filter(|b| !b.is_empty())
+    .map(|b| { let effect = Effect::of(&b); (b, effect) })
+    .collect();
+
+  let mut found: Vec<(Alg, Commutator)> = Vec::new();
+  for a in sequences(moves, MAX_A).into_iter().filter(|a| !a.is_empty()) {
+    let a_effect = Effect::of(&a);
+    let a_inverse = a_effect.inverse();
+    for (b, b_effect) in &bs {
+      let body = a_effect.then(b_effect).then(&a_inverse).then(&b_effect.inverse());
+      // the same pair the other way round is the inverse, which may be the one wanted
+      let body_inverse = body.inverse();
+      for (effect, a, b) in [(&body, &a, b), (&body_inverse, b, &a)] {
+        let Some(setups) = bodies.get(effect) else { continue };
+        for setup in setups {
+          let comm = Commutator { setup: setup.clone(), a: a.clone(), b: b.clone() };
+          found.push((comm.expand().simplify(), comm));
+        }
+      }
+    }
+  }
+
+  found.sort_by_key(|(moves, comm)| (moves.len(), comm.setup.len(), comm.a.len() + comm.b.len()));
+  let mut seen = HashSet::new();
+  found.into_iter()
+    .filter(|(moves, _)| seen.insert(moves.clone()))
+    .map(|(_, comm)| comm)
+    .take(FIND_LIMIT)
+    .collect()
+}
+
+// face turns, plus slices for edges
+fn move_set(slices: bool) -> Vec<Move> {
+  let mut turns: Vec<Turn> = Face::ALL.iter().map(|face| Turn::Face(*face)).collect();
+  if slices { turns.extend([Slice::M, Slice::E, Slice::S].map(Turn::Slice)); }
+  turns.iter().flat_map(|turn| (1..4).map(|amount| Move::new(*turn, amount))).collect()
+}
+
+// every sequence up to `length` moves that never turns the same layers twice in a row
+fn sequences(moves: &[Move], length: usize) -> Vec<Alg> {
+  let mut all = vec![Alg::new()];
+  let mut last = vec![Alg::new()];
+  for _ in 0..length {
+    let mut next = Vec::new();
+    for alg in &last {
+      for mv in moves {
+        if alg.0.last().is_some_and(|prev| prev.turn == mv.turn) { continue }
+        let mut longer = alg.clone();
+        longer.push(*mv);
+        next.push(longer);
+      }
+    }
+    all.extend(next.iter().cloned());
+    last = next;
+  }
+  all
+}
+
+// where every sticker ends up, as the cube holds it: effect[position] is the sticker there
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Effect([u8; FACELETS]);
+
+impl Effect {
+  fn of(alg: &Alg) -> Effect { Effect(*Cube::from_alg(alg).facelets()) }
+
+  fn then(&self, other: &Effect) -> Effect { Effect(std::array::from_fn(|i| self.0[other.0[i] as usize])) }
+
+  fn inverse(&self) -> Effect {
+    let mut inverse = [0; FACELETS];
+    for (position, sticker) in self.0.iter().enumerate() { inverse[*sticker as usize] = position as u8; }
+    Effect(inverse)
+  }
+}
+
+// each piece carried round so its named sticker lands on the next one's
+fn cycle_effect(stickers: &[Sticker; 3]) -> Effect {
+  let mut effect = Effect(*Cube::solved().facelets());
+  for k in 0..3 {
+    let (from, to) = (stickers[k], stickers[(k + 1) % 3]);
+    let (from_facelets, to_facelets) = (from.facelets(), to.facelets());
+    let count = from_facelets.len();
+    for n in 0..count {
+      effect.0[to_facelets[(to.index + n) % count] as usize] = from_facelets[(from.index + n) % count];
+    }
+  }
+  effect
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // every commutator found moves the named stickers round in order and leaves the rest solved
+  fn assert_cycles(input: &str) {
+    let stickers = three_cycle(input).unwrap();
+    let found = find(&stickers);
+    assert!(!found.is_empty(), "nothing found for {}", input);
+    let solved = Cube::solved();
+    let moved: Vec<u8> = stickers.iter().flat_map(|s| s.facelets().iter().copied()).collect();
+    for comm in found {
+      let facelets = *Cube::from_alg(&comm.expand()).facelets();
+      for k in 0..3 {
+        let (from, to) = (stickers[k], stickers[(k + 1) % 3]);
+        assert_eq!(facelets[to.facelet() as usize], from.facelet(), "{} for {}", comm, input);
+      }
+      for position in (0..FACELETS).filter(|p| !moved.contains(&(*p as u8))) {
+        assert_eq!(facelets[position], solved.facelets()[position], "{} for {}", comm, input);
+      }
+    }
+  }
+
+  #[test]
+  fn reads_stickers() {
+    // named from the face it is on, printed with the rest of its piece clockwise
+    let (ufr, fur): (Sticker, Sticker) = ("UFR".parse().unwrap(), "fur".parse().unwrap());
+    assert_eq!(ufr.position, fur.position);
+    assert_eq!((ufr.to_string(), fur.to_string()), ("URF".to_string(), "FUR".to_string()));
+    assert_ne!(ufr.facelet(), fur.facelet());
+    assert_eq!(three_cycle("UF UB"), Err(CommutatorError::Count(2)));
+    assert_eq!(three_cycle("UF UB UFR"), Err(CommutatorError::Mixed));
+    assert_eq!(three_cycle("UFR FUR DBL"), Err(CommutatorError::SamePiece));
+    assert_eq!(three_cycle("UF XY DB"), Err(CommutatorError::Sticker("XY".to_string())));
+  }
+
+  #[test]
+  fn finds_corner_cycles() {
+    assert_cycles("UFR UBR UBL");
+    // no setup of two moves with a single move b does this one
+    assert_cycles("UFR UBL DFL");
+  }
+
+  #[test]
+  fn finds_edge_cycles() {
+    assert_cycles("UF UR UB");
+    assert_cycles("UF UB DF");
+  }
+}
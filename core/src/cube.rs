@@ -0,0 +1,361 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::face::{Axis, Face};
+use crate::moves::{Alg, Move, Turn};
+
+pub const FACELETS: usize = 54;
+
+/* MARK: CUBE STATE
+  facelets[position] holds the id of the sticker sitting there, ids are the home positions
+  (face index * 9 + row * 3 + column, rows and columns as seen looking at the face)
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cube {
+  facelets: [u8; FACELETS],
+}
+
+impl Default for Cube {
+  fn default() -> Self { Cube::solved() }
+}
+
+impl Cube {
+  pub fn solved() -> Cube {
+    let mut facelets = [0; FACELETS];
+    for (i, id) in facelets.iter_mut().enumerate() { *id = i as u8; }
+    Cube { facelets }
+  }
+
+  pub fn from_alg(alg: &Alg) -> Cube {
+    let mut cube = Cube::solved();
+    cube.apply_alg(alg);
+    cube
+  }
+
+  pub fn facelets(&self) -> &[u8; FACELETS] { &self.facelets }
+
+  pub(crate) fn from_facelets(facelets: [u8; FACELETS]) -> Cube { Cube { facelets } }
+
+  pub fn apply_move(&mut self, mv: Move) {
+    let quarters = mv.quarter_turns().rem_euclid(4);
+    let tables = tables();
+    for (layer, turned) in mv.layers().iter().enumerate() {
+      if !turned { continue }
+      let perm = &tables.layers[mv.axis().index()][layer];
+      for _ in 0..quarters {
+        let old = self.facelets;
+        for (position, from) in perm.iter().enumerate() {
+          self.facelets[position] = old[*from as usize];
+        }
+      }
+    }
+  }
+
+  pub fn apply_alg(&mut self, alg: &Alg) {
+    for mv in alg { self.apply_move(*mv); }
+  }
+
+  // every sticker home, including orientation of the whole cube
+  pub fn is_solved(&self) -> bool { *self == Cube::solved() }
+
+  // every piece home next to the centres, told apart by sticker rather than colour
+  pub fn pieces_home(&self) -> bool { self.normalized() == Cube::solved() }
+
+  // every face a single colour, however the cube is held
+  pub fn is_solved_any_orientation(&self) -> bool {
+    let colors = self.colors();
+    colors.chunks(9).all(|face| face.iter().all(|c| *c == face[4]))
+  }
+
+  // face each centre currently sits on, indexed by the centre's home face
+  pub fn center_positions(&self) -> [Face; 6] {
+    let mut positions = [Face::U; 6];
+    for face in Face::ALL {
+      let home = self.facelets[face.index() * 9 + 4] as usize / 9;
+      positions[home] = face;
+    }
+    positions
+  }
+
+  // sticker colours, named after the face whose centre shares the colour
+  pub fn colors(&self) -> [Face; FACELETS] {
+    let centers = self.center_positions();
+    let mut colors = [Face::U; FACELETS];
+    for (color, id) in colors.iter_mut().zip(self.facelets.iter()) {
+      *color = centers[*id as usize / 9];
+    }
+    colors
+  }
+
+  // sticker colours named after their home face, ignoring where the centres are
+  pub fn home_colors(&self) -> [Face; FACELETS] {
+    let mut colors = [Face::U; FACELETS];
+    for (color, id) in colors.iter_mut().zip(self.facelets.iter()) {
+      *color = Face::from_index(*id as usize / 9);
+    }
+    colors
+  }
+
+  // whole cube rotation that brings every centre back home
+  pub fn orientation_fix(&self) -> Alg {
+    for rotation in rotations() {
+      let mut cube = *self;
+      cube.apply_alg(rotation);
+      if cube.center_positions() == [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B] {
+        return rotation.clone();
+      }
+    }
+    Alg::new()
+  }
+
+  // same state, held with every centre home
+  pub fn normalized(&self) -> Cube {
+    let mut cube = *self;
+    cube.apply_alg(&self.orientation_fix());
+    cube
+  }
+
+  // letters name the home face of each sticker, so a rotated cube keeps its orientation
+  pub fn to_facelet_string(&self) -> String {
+    self.home_colors().iter().map(|c| c.to_char()).collect()
+  }
+
+  // parse 54 URFDLB characters, each piece is identified by its set of colours
+  pub fn from_facelet_string(s: &str) -> Result<Cube, FaceletError> {
+    let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if chars.len() != FACELETS { return Err(FaceletError::Length(chars.len())) }
+
+    let mut colors = [Face::U; FACELETS];
+    for (i, c) in chars.iter().enumerate() {
+      colors[i] = Face::from_char(*c).ok_or(FaceletError::Color(*c))?;
+    }
+
+    let mut seen = [false; 6];
+    for face in Face::ALL {
+      let color = colors[face.index() * 9 + 4];
+      if seen[color.index()] { return Err(FaceletError::Centers) }
+      seen[color.index()] = true;
+    }
+
+    let tables = tables();
+    let mut facelets = [u8::MAX; FACELETS];
+    for face in Face::ALL {
+      facelets[face.index() * 9 + 4] = colors[face.index() * 9 + 4].index() as u8 * 9 + 4;
+    }
+
+    let mut used = [false; FACELETS];
+    for piece in tables.corners.iter().chain(tables.edges.iter()) {
+      let found: Vec<Face> = piece.iter().map(|p| colors[*p as usize]).collect();
+      let home = tables.corners.iter().chain(tables.edges.iter())
+        .find(|home| home.len() == piece.len() && rotation_of(home, &found).is_some())
+        .ok_or(FaceletError::Piece(found.iter().map(|c| c.to_char()).collect()))?;
+      let shift = rotation_of(home, &found).unwrap();
+      for (k, position) in piece.iter().enumerate() {
+        let id = home[(k + shift) % home.len()];
+        if used[id as usize] { return Err(FaceletError::Piece(found.iter().map(|c| c.to_char()).collect())) }
+        used[id as usize] = true;
+        facelets[*position as usize] = id;
+      }
+    }
+
+    let cube = Cube { facelets };
+    if !cube.is_solvable() { return Err(FaceletError::Unsolvable) }
+    Ok(cube)
+  }
+
+  /* text net, U above F with L F R B across the middle:
+          U U U
+          U U U
+          U U U
+    L L L F F F R R R B B B
+    ...
+  */
+  pub fn to_net(&self) -> String {
+    let colors = self.home_colors();
+    let row = |face: Face, r: usize| -> String {
+      (0..3).map(|c| colors[face.index() * 9 + r * 3 + c].to_char().to_string()).collect::<Vec<_>>().join(" ")
+    };
+    let mut net = String::new();
+    for r in 0..3 { net += &format!("      {}\n", row(Face::U, r)); }
+    for r in 0..3 {
+      let middle: Vec<String> = [Face::L, Face::F, Face::R, Face::B].iter().map(|f| row(*f, r)).collect();
+      net += &format!("{}\n", middle.join(" "));
+    }
+    for r in 0..3 { net += &format!("      {}\n", row(Face::D, r)); }
+    net
+  }
+
+  // parity, corner twist and edge flip all consistent
+  pub fn is_solvable(&self) -> bool { crate::cubie::CubieCube::from_cube(self).is_solvable() }
+}
+
+// repetitions of an algorithm needed to return a solved cube to solved
+pub fn order(alg: &Alg) -> usize {
+  let mut cube = Cube::solved();
+  let mut count = 0;
+  loop {
+    cube.apply_alg(alg);
+    count += 1;
+    if cube.is_solved() { return count }
+  }
+}
+
+// offset such that `found[k]` is the colour of home sticker `home[k + offset]`
+fn rotation_of(home: &[u8], found: &[Face]) -> Option<usize> {
+  (0..home.len()).find(|shift| {
+    found.iter().enumerate().all(|(k, c)| home[(k + shift) % home.len()] as usize / 9 == c.index())
+  })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaceletError {
+  Length(usize),
+  Color(char),
+  Centers,
+  Piece(String),
+  Unsolvable,
+}
+
+impl fmt::Display for FaceletError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FaceletError::Length(len) => write!(f, "expected 54 facelets, found {}", len),
+      FaceletError::Color(c) => write!(f, "'{}' is not one of URFDLB", c),
+      FaceletError::Centers => write!(f, "every centre must be a different colour"),
+      FaceletError::Piece(colors) => write!(f, "no piece or a duplicate piece with colours {}", colors),
+      FaceletError::Unsolvable => write!(f, "cube cannot be solved, a piece is twisted, flipped or swapped"),
+    }
+  }
+}
+
+impl std::error::Error for FaceletError {}
+
+impl FromStr for Cube {
+  type Err = FaceletError;
+  fn from_str(s: &str) -> Result<Cube, FaceletError> { Cube::from_facelet_string(s) }
+}
+
+impl fmt::Display for Cube {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.to_facelet_string()) }
+}
+
+impl fmt::Debug for Cube {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "Cube({})", self.to_facelet_string()) }
+}
+
+/* MARK: GEOMETRY
+  stickers get an integer position (cubie coordinates -1..=1) and outward normal,
+  turning a layer rotates both and looks up where the sticker ended up
+*/
+pub(crate) struct Tables {
+  // [axis][layer] positions after a +90° (right handed) turn: new[i] = old[perm[i]]
+  pub layers: [[[u8; FACELETS]; 3]; 3],
+  // facelet positions of each corner and edge, in the cubie order used by the solvers
+  pub corners: [Vec<u8>; 8],
+  pub edges: [Vec<u8>; 12],
+}
+
+pub(crate) fn tables() -> &'static Tables {
+  static TABLES: OnceLock<Tables> = OnceLock::new();
+  TABLES.get_or_init(|| {
+    let stickers: Vec<([i8; 3], [i8; 3])> = (0..FACELETS).map(sticker_coords).collect();
+    let index_of = |position: [i8; 3], normal: [i8; 3]| {
+      stickers.iter().position(|s| *s == (position, normal)).unwrap() as u8
+    };
+
+    let mut layers = [[[0u8; FACELETS]; 3]; 3];
+    for axis in Axis::ALL {
+      for (layer, perm) in layers[axis.index()].iter_mut().enumerate() {
+        for (i, from) in perm.iter_mut().enumerate() { *from = i as u8; }
+        for (i, (position, normal)) in stickers.iter().enumerate() {
+          if position[axis.index()] != layer as i8 - 1 { continue }
+          let to = index_of(rotate(*position, axis), rotate(*normal, axis));
+          perm[to as usize] = i as u8;
+        }
+      }
+    }
+
+    // kociemba order: URF UFL ULB UBR DFR DLF DBL DRB, stickers listed U/D first then clockwise
+    let corner = |faces: [Face; 3]| -> Vec<u8> {
+      let mut position = [0; 3];
+      for face in faces { position[face.axis().index()] = face.normal()[face.axis().index()]; }
+      faces.iter().map(|face| index_of(position, face.normal())).collect()
+    };
+    use Face::*;
+    let corners = [
+      corner([U, R, F]), corner([U, F, L]), corner([U, L, B]), corner([U, B, R]),
+      corner([D, F, R]), corner([D, L, F]), corner([D, B, L]), corner([D, R, B]),
+    ];
+    let edge = |faces: [Face; 2]| -> Vec<u8> {
+      let mut position = [0; 3];
+      for face in faces { position[face.axis().index()] = face.normal()[face.axis().index()]; }
+      faces.iter().map(|face| index_of(position, face.normal())).collect()
+    };
+    let edges = [
+      edge([U, R]), edge([U, F]), edge([U, L]), edge([U, B]),
+      edge([D, R]), edge([D, F]), edge([D, L]), edge([D, B]),
+      edge([F, R]), edge([F, L]), edge([B, L]), edge([B, R]),
+    ];
+
+    Tables { layers, corners, edges }
+  })
+}
+
+// right handed +90° rotation about an axis
+pub(crate) fn rotate(v: [i8; 3], axis: Axis) -> [i8; 3] {
+  let [x, y, z] = v;
+  match axis {
+    Axis::X => [x, -z, y],
+    Axis::Y => [z, y, -x],
+    Axis::Z => [-y, x, z],
+  }
+}
+
+// (cubie position, outward normal) of a facelet index
+pub fn sticker_coords(index: usize) -> ([i8; 3], [i8; 3]) {
+  let face = Face::from_index(index / 9);
+  let (row, col) = ((index % 9) / 3, index % 3);
+  // direction of increasing column and row when looking at the face
+  let (right, down): ([i8; 3], [i8; 3]) = match face {
+    Face::U => ([1, 0, 0], [0, 0, 1]),
+    Face::R => ([0, 0, -1], [0, -1, 0]),
+    Face::F => ([1, 0, 0], [0, -1, 0]),
+    Face::D => ([1, 0, 0], [0, 0, -1]),
+    Face::L => ([0, 0, 1], [0, -1, 0]),
+    Face::B => ([-1, 0, 0], [0, -1, 0]),
+  };
+  let normal = face.normal();
+  let mut position = [0; 3];
+  for k in 0..3 {
+    position[k] = normal[k] + (col as i8 - 1) * right[k] + (row as i8 - 1) * down[k];
+  }
+  (position, normal)
+}
+
+/* MARK: ROTATIONS
+  the 24 ways to hold the cube, as the shortest rotation from the home orientation
+*/
+pub fn rotations() -> &'static [Alg] {
+  static ROTATIONS: OnceLock<Vec<Alg>> = OnceLock::new();
+  ROTATIONS.get_or_init(|| {
+    let mut found: Vec<(Cube, Alg)> = vec![(Cube::solved(), Alg::new())];
+    let mut next = 0;
+    while next < found.len() {
+      let (cube, alg) = found[next].clone();
+      for axis in Axis::ALL {
+        for amount in 1..=3 {
+          let mv = Move::new(Turn::Rotate(axis), amount);
+          let mut turned = cube;
+          turned.apply_move(mv);
+          if found.iter().any(|(c, _)| c.center_positions() == turned.center_positions()) { continue }
+          let mut longer = alg.clone();
+          longer.push(mv);
+          found.push((turned, longer));
+        }
+      }
+      next += 1;
+    }
+    found.into_iter().map(|(_, alg)| alg).collect()
+  })
+}
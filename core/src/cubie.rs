@@ -0,0 +1,274 @@
+use std::sync::OnceLock;
+
+use crate::cube::{tables, Cube, FACELETS};
+use crate::face::Face;
+use crate::moves::Move;
+
+// corner and edge positions, in kociemba order
+pub const URF: usize = 0; pub const UFL: usize = 1; pub const ULB: usize = 2; pub const UBR: usize = 3;
+pub const DFR: usize = 4; pub const DLF: usize = 5; pub const DBL: usize = 6; pub const DRB: usize = 7;
+
+pub const UR: usize = 0; pub const UF: usize = 1; pub const UL: usize = 2; pub const UB: usize = 3;
+pub const DR: usize = 4; pub const DF: usize = 5; pub const DL: usize = 6; pub const DB: usize = 7;
+pub const FR: usize = 8; pub const FL: usize = 9; pub const BL: usize = 10; pub const BR: usize = 11;
+
+/* MARK: CUBIE CUBE
+  piece level state used by the solvers, centres are always home:
+  cp[i] is the corner sitting in position i, co[i] its twist (0..3), likewise for edges
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CubieCube {
+  pub cp: [u8; 8],
+  pub co: [u8; 8],
+  pub ep: [u8; 12],
+  pub eo: [u8; 12],
+}
+
+impl Default for CubieCube {
+  fn default() -> Self { CubieCube::SOLVED }
+}
+
+impl CubieCube {
+  pub const SOLVED: CubieCube = CubieCube {
+    cp: [0, 1, 2, 3, 4, 5, 6, 7],
+    co: [0; 8],
+    ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    eo: [0; 12],
+  };
+
+  // reads the cube as held with its centres home
+  pub fn from_cube(cube: &Cube) -> CubieCube {
+    let tables = tables();
+    let facelets = cube.normalized();
+    let facelets = facelets.facelets();
+    let pieces = piece_of_sticker();
+    let mut cubie = CubieCube::SOLVED;
+
+    for (i, position) in tables.corners.iter().enumerate() {
+      for (ori, sticker) in position.iter().enumerate() {
+        let (piece, index) = pieces[facelets[*sticker as usize] as usize];
+        if index == 0 {
+          cubie.cp[i] = piece;
+          cubie.co[i] = ori as u8;
+        }
+      }
+    }
+    for (i, position) in tables.edges.iter().enumerate() {
+      let (piece, index) = pieces[facelets[position[0] as usize] as usize];
+      cubie.ep[i] = piece;
+      cubie.eo[i] = index;
+    }
+    cubie
+  }
+
+  pub fn to_cube(&self) -> Cube {
+    let tables = tables();
+    let mut facelets = *Cube::solved().facelets();
+    for i in 0..8 {
+      let (piece, ori) = (self.cp[i] as usize, self.co[i] as usize);
+      for n in 0..3 {
+        facelets[tables.corners[i][(n + ori) % 3] as usize] = tables.corners[piece][n];
+      }
+    }
+    for i in 0..12 {
+      let (piece, ori) = (self.ep[i] as usize, self.eo[i] as usize);
+      for n in 0..2 {
+        facelets[tables.edges[i][(n + ori) % 2] as usize] = tables.edges[piece][n];
+      }
+    }
+    Cube::from_facelets(facelets)
+  }
+
+  // apply `self` then `other`
+  pub fn multiply(&self, other: &CubieCube) -> CubieCube {
+    let mut result = CubieCube::SOLVED;
+    for i in 0..8 {
+      let from = other.cp[i] as usize;
+      result.cp[i] = self.cp[from];
+      result.co[i] = (self.co[from] + other.co[i]) % 3;
+    }
+    for i in 0..12 {
+      let from = other.ep[i] as usize;
+      result.ep[i] = self.ep[from];
+      result.eo[i] = (self.eo[from] + other.eo[i]) % 2;
+    }
+    result
+  }
+
+  pub fn inverse(&self) -> CubieCube {
+    let mut result = CubieCube::SOLVED;
+    for i in 0..8 {
+      let piece = self.cp[i] as usize;
+      result.cp[piece] = i as u8;
+      result.co[piece] = (3 - self.co[i]) % 3;
+    }
+    for i in 0..12 {
+      let piece = self.ep[i] as usize;
+      result.ep[piece] = i as u8;
+      result.eo[piece] = self.eo[i];
+    }
+    result
+  }
+
+  pub fn apply_move(&mut self, mv: Move) {
+    let mut cube = self.to_cube();
+    cube.apply_move(mv);
+    *self = CubieCube::from_cube(&cube);
+  }
+
+  // quarter turn of a single face, the building block of every solver move table
+  pub fn face_turn(face: Face) -> &'static CubieCube {
+    static TURNS: OnceLock<[CubieCube; 6]> = OnceLock::new();
+    &TURNS.get_or_init(|| {
+      Face::ALL.map(|face| {
+        let mut cube = Cube::solved();
+        cube.apply_move(Move::face(face, 1));
+        CubieCube::from_cube(&cube)
+      })
+    })[face.index()]
+  }
+
+  pub fn corner_parity(&self) -> bool { permutation_parity(&self.cp) }
+
+  pub fn edge_parity(&self) -> bool { permutation_parity(&self.ep) }
+
+  pub fn is_solvable(&self) -> bool {
+    let twist: u32 = self.co.iter().map(|c| *c as u32).sum();
+    let flip: u32 = self.eo.iter().map(|e| *e as u32).sum();
+    twist.is_multiple_of(3) && flip.is_multiple_of(2) && self.corner_parity() == self.edge_parity()
+  }
+
+  /* MARK: COORDINATES
+  */
+  pub fn twist(&self) -> u16 {
+    self.co[..7].iter().fold(0, |acc, c| acc * 3 + *c as u16)
+  }
+
+  pub fn set_twist(&mut self, mut twist: u16) {
+    let mut total = 0;
+    for i in (0..7).rev() {
+      self.co[i] = (twist % 3) as u8;
+      total += self.co[i];
+      twist /= 3;
+    }
+    self.co[7] = (3 - total % 3) % 3;
+  }
+
+  pub fn flip(&self) -> u16 {
+    self.eo[..11].iter().fold(0, |acc, e| acc * 2 + *e as u16)
+  }
+
+  pub fn set_flip(&mut self, mut flip: u16) {
+    let mut total = 0;
+    for i in (0..11).rev() {
+      self.eo[i] = (flip % 2) as u8;
+      total += self.eo[i];
+      flip /= 2;
+    }
+    self.eo[11] = total % 2;
+  }
+
+  // positions of the four E slice edges (FR FL BL BR), ignoring their order: 0..495
+  pub fn slice(&self) -> u16 {
+    let (mut index, mut found) = (0, 0);
+    for j in (0..12).rev() {
+      if self.ep[j] as usize >= FR {
+        index += binomial(11 - j, found + 1);
+        found += 1;
+      }
+    }
+    index as u16
+  }
+
+  pub fn set_slice(&mut self, mut index: u16) {
+    let mut found = 4;
+    let (mut slice_edge, mut other) = (FR as u8, 0);
+    for j in 0..12 {
+      let c = if found > 0 { binomial(11 - j, found) as u16 } else { u16::MAX };
+      if found > 0 && index >= c {
+        index -= c;
+        found -= 1;
+        self.ep[j] = slice_edge;
+        slice_edge += 1;
+      } else {
+        self.ep[j] = other;
+        other += 1;
+      }
+    }
+  }
+
+  pub fn corner_perm(&self) -> u16 { permutation_index(&self.cp) as u16 }
+
+  pub fn set_corner_perm(&mut self, index: u16) { set_permutation(&mut self.cp, index as usize) }
+
+  // order of the eight U and D layer edges, only meaningful once the slice is home
+  pub fn ud_edge_perm(&self) -> u16 { permutation_index(&self.ep[..8]) as u16 }
+
+  pub fn set_ud_edge_perm(&mut self, index: u16) { set_permutation(&mut self.ep[..8], index as usize) }
+
+  // order of the four E slice edges while they stay in the slice: 0..24
+  pub fn slice_perm(&self) -> u8 {
+    let slice: Vec<u8> = self.ep[8..].iter().map(|e| e.saturating_sub(8)).collect();
+    permutation_index(&slice) as u8
+  }
+
+  pub fn set_slice_perm(&mut self, index: u8) {
+    let mut slice = [0u8; 4];
+    set_permutation(&mut slice, index as usize);
+    for (i, e) in slice.iter().enumerate() { self.ep[8 + i] = e + 8; }
+  }
+}
+
+// sticker id -> (piece index, sticker index within the piece), centres map to (u8::MAX, 0)
+fn piece_of_sticker() -> &'static [(u8, u8); FACELETS] {
+  static PIECES: OnceLock<[(u8, u8); FACELETS]> = OnceLock::new();
+  PIECES.get_or_init(|| {
+    let tables = tables();
+    let mut pieces = [(u8::MAX, 0); FACELETS];
+    for (piece, stickers) in tables.corners.iter().enumerate() {
+      for (k, id) in stickers.iter().enumerate() { pieces[*id as usize] = (piece as u8, k as u8); }
+    }
+    for (piece, stickers) in tables.edges.iter().enumerate() {
+      for (k, id) in stickers.iter().enumerate() { pieces[*id as usize] = (piece as u8, k as u8); }
+    }
+    pieces
+  })
+}
+
+/* MARK: UTIL
+*/
+pub(crate) fn binomial(n: usize, k: usize) -> usize {
+  if k > n { return 0 }
+  (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+pub(crate) fn permutation_parity(perm: &[u8]) -> bool {
+  let mut odd = false;
+  for i in 0..perm.len() {
+    for j in i + 1..perm.len() {
+      if perm[i] > perm[j] { odd = !odd; }
+    }
+  }
+  odd
+}
+
+// lehmer code of a permutation of 0..n
+pub(crate) fn permutation_index(perm: &[u8]) -> usize {
+  let mut index = 0;
+  for i in 0..perm.len() {
+    let smaller = perm[i + 1..].iter().filter(|p| **p < perm[i]).count();
+    index = index * (perm.len() - i) + smaller;
+  }
+  index
+}
+
+pub(crate) fn set_permutation(perm: &mut [u8], mut index: usize) {
+  let n = perm.len();
+  let mut digits = vec![0; n];
+  for i in (0..n).rev() {
+    digits[i] = index % (n - i);
+    index /= n - i;
+  }
+  let mut left: Vec<u8> = (0..n as u8).collect();
+  for i in 0..n { perm[i] = left.remove(digits[i]); }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+/* MARK: FACE
+  URFDLB order matches facelet strings: U1..U9, R1..R9, F1..F9, D1..D9, L1..L9, B1..B9
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Face { U, R, F, D, L, B }
+
+/* MARK: AXIS
+  right handed, same as the renderer: R is +X, U is +Y, F is +Z
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Axis { X, Y, Z }
+
+impl Face {
+  pub const ALL: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+  pub fn index(self) -> usize { self as usize }
+
+  pub fn from_index(index: usize) -> Face { Face::ALL[index % 6] }
+
+  pub fn axis(self) -> Axis {
+    match self {
+      Face::R | Face::L => Axis::X,
+      Face::U | Face::D => Axis::Y,
+      Face::F | Face::B => Axis::Z,
+    }
+  }
+
+  // face lies on the positive side of its axis
+  pub fn is_positive(self) -> bool { matches!(self, Face::U | Face::R | Face::F) }
+
+  pub fn opposite(self) -> Face { Face::from_index(self.index() + 3) }
+
+  pub fn from_axis(axis: Axis, positive: bool) -> Face {
+    match (axis, positive) {
+      (Axis::X, true) => Face::R, (Axis::X, false) => Face::L,
+      (Axis::Y, true) => Face::U, (Axis::Y, false) => Face::D,
+      (Axis::Z, true) => Face::F, (Axis::Z, false) => Face::B,
+    }
+  }
+
+  pub fn normal(self) -> [i8; 3] {
+    let mut normal = [0; 3];
+    normal[self.axis().index()] = if self.is_positive() { 1 } else { -1 };
+    normal
+  }
+
+  pub fn from_char(c: char) -> Option<Face> {
+    match c.to_ascii_uppercase() {
+      'U' => Some(Face::U), 'R' => Some(Face::R), 'F' => Some(Face::F),
+      'D' => Some(Face::D), 'L' => Some(Face::L), 'B' => Some(Face::B),
+      _ => None,
+    }
+  }
+
+  pub fn to_char(self) -> char { ['U', 'R', 'F', 'D', 'L', 'B'][self.index()] }
+}
+
+impl Axis {
+  pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+  pub fn index(self) -> usize { self as usize }
+
+  pub fn from_char(c: char) -> Option<Axis> {
+    match c { 'x' => Some(Axis::X), 'y' => Some(Axis::Y), 'z' => Some(Axis::Z), _ => None }
+  }
+
+  pub fn to_char(self) -> char { ['x', 'y', 'z'][self.index()] }
+}
+
+impl fmt::Display for Face {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.to_char()) }
+}
+
+impl fmt::Display for Axis {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.to_char()) }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use rand_core::RngCore;
+
+use crate::cube::Cube;
+use crate::moves::{Alg, Move, ParseAlgError, Turn};
+use crate::scramble;
+
+/* MARK: FEWEST MOVES
+  WCA rules: a random state scramble padded with R' U' F at both ends, one hour, and a
+  solution of at most 80 moves written with outer turns, wide turns and rotations.
+  rotations are free, every other move is 1 in HTM
+*/
+pub const TIME_LIMIT: f32 = 3600.0;
+pub const MAX_LENGTH: usize = 80;
+
+pub fn scramble(rng: &mut impl RngCore) -> Alg {
+  let padding: Alg = "R' U' F".parse().unwrap_or_default();
+  // only the random part is simplified, the padding has to stay as written
+  padding.concat(&scramble::random_state(rng).simplify()).concat(&padding)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FmcError {
+  Parse(ParseAlgError),
+  // slice moves are not allowed in fewest moves notation
+  Slice(Move),
+  TooLong(usize),
+  NotSolved,
+}
+
+impl fmt::Display for FmcError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FmcError::Parse(e) => write!(f, "{}", e),
+      FmcError::Slice(mv) => write!(f, "slice move {} is not allowed", mv),
+      FmcError::TooLong(length) => write!(f, "{} moves is over the limit of {}", length, MAX_LENGTH),
+      FmcError::NotSolved => write!(f, "the solution does not solve the scramble"),
+    }
+  }
+}
+
+impl std::error::Error for FmcError {}
+
+// HTM count of a solution, if it is legal and solves the scramble
+pub fn score(scramble: &Alg, solution: &str) -> Result<usize, FmcError> {
+  let solution: Alg = solution.parse().map_err(FmcError::Parse)?;
+  if let Some(mv) = solution.iter().find(|mv| matches!(mv.turn, Turn::Slice(_))) { return Err(FmcError::Slice(*mv)) }
+  let length = solution.iter().filter(|mv| !mv.is_rotation()).count();
+  if length > MAX_LENGTH { return Err(FmcError::TooLong(length)) }
+  if !Cube::from_alg(&scramble.concat(&solution)).is_solved_any_orientation() { return Err(FmcError::NotSolved) }
+  Ok(length)
+}
+
+/* MARK: NISS
+  moves found on the normal scramble and on its inverse. moves on the inverse are premoves
+  for the normal scramble, so the two together always make one solution
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Niss {
+  pub normal: Alg,
+  pub inverse: Alg,
+  pub on_inverse: bool,
+  // which side each move went to, for undo
+  sides: Vec<bool>,
+}
+
+impl Niss {
+  pub fn push(&mut self, mv: Move) { self.push_to(self.on_inverse, mv); }
+
+  // a move done before the scramble on this side is a move at the end of the other side
+  pub fn premove(&mut self, mv: Move) { self.push_to(!self.on_inverse, mv.inverse()); }
+
+  fn push_to(&mut self, inverse: bool, mv: Move) {
+    if inverse { self.inverse.push(mv) } else { self.normal.push(mv) }
+    self.sides.push(inverse);
+  }
+
+  pub fn undo(&mut self) -> Option<Move> {
+    let inverse = self.sides.pop()?;
+    if inverse { self.inverse.0.pop() } else { self.normal.0.pop() }
+  }
+
+  pub fn switch(&mut self) { self.on_inverse = !self.on_inverse; }
+
+  // moves from solved to the cube as seen on the current side
+  pub fn position(&self, scramble: &Alg) -> Alg {
+    let (before, scramble, after) = match self.on_inverse {
+      false => (&self.inverse, scramble.clone(), &self.normal),
+      true => (&self.normal, scramble.inverse(), &self.inverse),
+    };
+    before.inverse().concat(&scramble).concat(after)
+  }
+
+  pub fn solution(&self) -> Alg { self.normal.concat(&self.inverse.inverse()) }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+
+  fn alg(s: &str) -> Alg { s.parse().unwrap() }
+
+  #[test]
+  fn scrambles_keep_their_padding() {
+    let padding = alg("R' U' F");
+    // a random part starting or ending with R, U or F used to cancel into it
+    for seed in 0..20 {
+      let scramble = scramble(&mut ChaCha8Rng::seed_from_u64(seed));
+      assert_eq!(scramble.0[..3], padding.0[..], "{}", scramble);
+      assert_eq!(scramble.0[scramble.len() - 3..], padding.0[..], "{}", scramble);
+    }
+  }
+
+  #[test]
+  fn scores_legal_solutions_only() {
+    let scramble = alg("R U F");
+    assert_eq!(score(&scramble, "F' U' R'"), Ok(3));
+    // rotations are free and the cube may end up turned
+    assert_eq!(score(&scramble, "F' U' R' y2 x"), Ok(3));
+    assert_eq!(score(&scramble, "F' U' R"), Err(FmcError::NotSolved));
+    assert_eq!(score(&scramble, "F' M U' R'"), Err(FmcError::Slice(alg("M").0[0])));
+    assert_eq!(score(&scramble, "(R U R' U')20 F' U' R'"), Err(FmcError::TooLong(83)));
+    assert!(matches!(score(&scramble, "F' Q"), Err(FmcError::Parse(_))));
+  }
+
+  #[test]
+  fn niss_joins_both_sides_into_one_solution() {
+    let scramble = alg("R U F");
+    let mut niss = Niss::default();
+    niss.push(alg("F'").0[0]);
+    niss.switch();
+    // on the inverse the scramble is F' U' R', so R U after F' solves it
+    niss.push(alg("R").0[0]);
+    niss.push(alg("D").0[0]);
+    assert_eq!(niss.undo(), alg("D").0.first().copied());
+    niss.push(alg("U").0[0]);
+    assert!(Cube::from_alg(&niss.position(&scramble)).is_solved());
+    assert_eq!(niss.solution(), alg("F' U' R'"));
+    assert_eq!(score(&scramble, &niss.solution().to_string()), Ok(3));
+  }
+
+  #[test]
+  fn premoves_go_to_the_other_side() {
+    let mut niss = Niss::default();
+    niss.premove(alg("R").0[0]);
+    assert_eq!((niss.normal.clone(), niss.inverse.clone()), (Alg::new(), alg("R'")));
+    // undo takes back the premove, not the last move on this side
+    niss.push(alg("U").0[0]);
+    niss.undo();
+    assert_eq!(niss.undo(), alg("R'").0.first().copied());
+    assert_eq!(niss, Niss::default());
+  }
+}
@@ -0,0 +1,31 @@
+/* MARK: PUZZLE CUBE CORE
+  cube state, move notation, scrambling and solving without any renderer attached
+*/
+pub mod face;
+pub mod moves;
+pub mod simplify;
+pub mod metrics;
+pub mod cube;
+pub mod cubie;
+pub mod scramble;
+pub mod record;
+pub mod steps;
+pub mod cases;
+pub mod blind;
+pub mod fmc;
+pub mod library;
+pub mod analysis;
+pub mod commutator;
+pub mod alggen;
+pub mod supercube;
+pub mod puzzle;
+pub mod bandage;
+pub mod patterns;
+pub mod solver;
+
+pub use cube::Cube;
+pub use face::{Axis, Face};
+pub use metrics::{Metric, Metrics};
+pub use moves::{Alg, Move, Slice, Turn};
+pub use record::{SolveRecord, TimedMove};
+pub use steps::{Split, Step};
@@ -0,0 +1,304 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cases::{F2L, OLL, PLL};
+use crate::cube::Cube;
+use crate::cubie::CubieCube;
+use crate::face::Face;
+use crate::moves::{Alg, Move};
+use crate::solver::pieces;
+
+/* MARK: LIBRARY
+  named algorithms in sets, bundled with the game and added to by the player. every one is
+  checked on the state model against what its set is for, solved with D down and F in front
+*/
+pub const BUNDLED: &str = include_str!("../data/algs.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlgSet { Oll, Pll, Coll, Zbll, F2l, Comm }
+
+impl AlgSet {
+  pub const ALL: [AlgSet; 6] = [AlgSet::Oll, AlgSet::Pll, AlgSet::Coll, AlgSet::Zbll, AlgSet::F2l, AlgSet::Comm];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      AlgSet::Oll => "OLL", AlgSet::Pll => "PLL", AlgSet::Coll => "COLL",
+      AlgSet::Zbll => "ZBLL", AlgSet::F2l => "F2L", AlgSet::Comm => "Comm",
+    }
+  }
+
+  // what the algorithm does to a solved cube has to fit the set
+  fn check(self, effect: &CubieCube) -> Result<(), &'static str> {
+    let f2l = pieces::is_solved(effect, &pieces::f2l_goal(&[]));
+    let oriented = effect.co.iter().all(|o| *o == 0) && effect.eo.iter().all(|o| *o == 0);
+    let edges_oriented = effect.eo.iter().all(|o| *o == 0);
+    let last_layer = !matches!(self, AlgSet::F2l | AlgSet::Comm);
+    if last_layer && !f2l { return Err("breaks the first two layers") }
+    if last_layer && solved_after_auf(effect) { return Err("does nothing but turn the top layer") }
+
+    match self {
+      AlgSet::Oll if oriented => Err("leaves the last layer oriented"),
+      AlgSet::Pll if !oriented => Err("changes the orientation of the last layer"),
+      AlgSet::Coll | AlgSet::Zbll if !edges_oriented => Err("flips last layer edges"),
+      AlgSet::Coll if corners_solved_after_auf(effect) => Err("leaves the corners alone"),
+      AlgSet::F2l if !pieces::is_solved(effect, &pieces::f2l_goal(&[0])) => Err("breaks the cross or another pair"),
+      AlgSet::F2l if f2l => Err("leaves the front right pair alone"),
+      AlgSet::Comm => match moved_pieces(effect) {
+        0 => Err("does nothing"),
+        1..=3 => Ok(()),
+        _ => Err("moves more than three pieces"),
+      },
+      _ => Ok(()),
+    }
+  }
+
+  // whether the algorithm reaches the end of its step from `before`, `after` being the result
+  fn reached(self, before: &CubieCube, after: &CubieCube) -> bool {
+    let f2l = |cube: &CubieCube| pieces::is_solved(cube, &pieces::f2l_goal(&[]));
+    let oriented = after.co.iter().all(|o| *o == 0) && after.eo.iter().all(|o| *o == 0);
+    match self {
+      AlgSet::Oll => f2l(after) && oriented,
+      AlgSet::Coll => f2l(after) && oriented && corners_solved_after_auf(after),
+      AlgSet::Pll | AlgSet::Zbll => solved_after_auf(after),
+      AlgSet::F2l => !f2l(before) && f2l(after),
+      AlgSet::Comm => *after == CubieCube::SOLVED,
+    }
+  }
+}
+
+impl fmt::Display for AlgSet {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.name()) }
+}
+
+impl FromStr for AlgSet {
+  type Err = LibraryError;
+  fn from_str(s: &str) -> Result<AlgSet, LibraryError> {
+    AlgSet::ALL.iter().find(|set| set.name().eq_ignore_ascii_case(s)).copied().ok_or(LibraryError::Set(s.to_string()))
+  }
+}
+
+fn auf(cube: &CubieCube, turns: usize) -> CubieCube {
+  (0..turns).fold(*cube, |cube, _| cube.multiply(CubieCube::face_turn(Face::U)))
+}
+
+fn solved_after_auf(cube: &CubieCube) -> bool {
+  (0..4).any(|turns| auf(cube, turns) == CubieCube::SOLVED)
+}
+
+fn corners_solved_after_auf(cube: &CubieCube) -> bool {
+  (0..4).any(|turns| {
+    let cube = auf(cube, turns);
+    cube.cp == CubieCube::SOLVED.cp && cube.co == CubieCube::SOLVED.co
+  })
+}
+
+// corners and edges out of place or twisted
+fn moved_pieces(cube: &CubieCube) -> usize {
+  let corners = (0..8).filter(|i| cube.cp[*i] as usize != *i || cube.co[*i] != 0).count();
+  let edges = (0..12).filter(|i| cube.ep[*i] as usize != *i || cube.eo[*i] != 0).count();
+  corners + edges
+}
+
+/* MARK: ERRORS
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryError {
+  Fields,
+  Set(String),
+  Alg(String),
+  Invalid(String, &'static str),
+  Duplicate(String),
+}
+
+impl fmt::Display for LibraryError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      LibraryError::Fields => write!(f, "expected a set, a name and an algorithm"),
+      LibraryError::Set(set) => write!(f, "unknown set '{}'", set),
+      LibraryError::Alg(e) => write!(f, "invalid algorithm: {}", e),
+      LibraryError::Invalid(name, why) => write!(f, "{} {}", name, why),
+      LibraryError::Duplicate(name) => write!(f, "{} is already in the library", name),
+    }
+  }
+}
+
+impl std::error::Error for LibraryError {}
+
+/* MARK: ENTRY
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+  pub set: AlgSet,
+  pub name: String,
+  pub alg: Alg,
+  pub favourite: bool,
+  // came with the game rather than from the player
+  pub bundled: bool,
+  // the algorithm done on a solved cube
+  effect: CubieCube,
+}
+
+impl Entry {
+  pub fn new(set: AlgSet, name: &str, alg: Alg) -> Result<Entry, LibraryError> {
+    let effect = CubieCube::from_cube(&Cube::from_alg(&alg));
+    set.check(&effect).map_err(|why| LibraryError::Invalid(format!("{} {}", set, name), why))?;
+    Ok(Entry { set, name: name.to_string(), alg, favourite: false, bundled: false, effect })
+  }
+
+  // "OLL 27: R U R' U R U2 R'", the way one is typed in
+  pub fn from_input(input: &str) -> Result<Entry, LibraryError> {
+    let (label, alg) = input.split_once(':').ok_or(LibraryError::Fields)?;
+    let (set, name) = label.trim().split_once(' ').ok_or(LibraryError::Fields)?;
+    let alg = alg.parse().map_err(|e| LibraryError::Alg(format!("{}", e)))?;
+    Entry::new(set.parse()?, name.trim(), alg)
+  }
+
+  fn matches(&self, words: &[String]) -> bool {
+    let text = format!("{} {} {}", self.set, self.name, self.alg).to_lowercase();
+    words.iter().all(|word| text.contains(word.as_str()))
+  }
+}
+
+// set, name, algorithm and a star for favourites, tab separated
+impl fmt::Display for Entry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}\t{}\t{}", self.set, self.name, self.alg)?;
+    if self.favourite { write!(f, "\t*")?; }
+    Ok(())
+  }
+}
+
+impl FromStr for Entry {
+  type Err = LibraryError;
+  fn from_str(s: &str) -> Result<Entry, LibraryError> {
+    let fields: Vec<&str> = s.trim_end_matches(['\r', '\n']).split('\t').collect();
+    let [set, name, alg, rest @ ..] = fields.as_slice() else { return Err(LibraryError::Fields) };
+    let alg = alg.parse().map_err(|e| LibraryError::Alg(format!("{}", e)))?;
+    let mut entry = Entry::new(set.parse()?, name, alg)?;
+    entry.favourite = rest.first().is_some_and(|star| star.trim() == "*");
+    Ok(entry)
+  }
+}
+
+/* MARK: LIBRARY
+*/
+#[derive(Debug, Clone, Default)]
+pub struct Library {
+  pub entries: Vec<Entry>,
+}
+
+impl Library {
+  // the trainer's PLL, OLL and F2L cases, then the other sets from the data file
+  pub fn bundled() -> Library {
+    let mut library = Library::default();
+    for (set, cases) in [(AlgSet::Pll, &PLL[..]), (AlgSet::Oll, &OLL[..]), (AlgSet::F2l, &F2L[..])] {
+      library.entries.extend(cases.iter().filter_map(|case| Entry::new(set, case.name, case.alg()).ok()));
+    }
+    library.entries.extend(Library::parse(BUNDLED).0.entries);
+    for entry in &mut library.entries { entry.bundled = true; }
+    library
+  }
+
+  // every valid line, with the line number and error of the ones left out.
+  // blank lines and lines starting with # are skipped
+  pub fn parse(text: &str) -> (Library, Vec<(usize, LibraryError)>) {
+    let mut library = Library::default();
+    let mut errors = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+      if line.trim().is_empty() || line.starts_with('#') { continue }
+      match line.parse() {
+        Ok(entry) => library.entries.push(entry),
+        Err(e) => errors.push((number + 1, e)),
+      }
+    }
+    (library, errors)
+  }
+
+  pub fn position(&self, set: AlgSet, name: &str) -> Option<usize> {
+    self.entries.iter().position(|e| e.set == set && e.name == name)
+  }
+
+  pub fn add(&mut self, entry: Entry) -> Result<usize, LibraryError> {
+    if self.position(entry.set, &entry.name).is_some() {
+      return Err(LibraryError::Duplicate(format!("{} {}", entry.set, entry.name)))
+    }
+    self.entries.push(entry);
+    Ok(self.entries.len() - 1)
+  }
+
+  // the player's own lines replace bundled ones of the same set and name, keeping them bundled
+  // when only the favourite mark differs
+  pub fn merge(&mut self, other: Library) {
+    for entry in other.entries {
+      match self.position(entry.set, &entry.name) {
+        Some(at) => {
+          let bundled = self.entries[at].bundled && self.entries[at].alg == entry.alg;
+          self.entries[at] = Entry { bundled, ..entry };
+        }
+        None => self.entries.push(entry),
+      }
+    }
+  }
+
+  // what is worth saving: the player's algorithms and favourites
+  pub fn user_entries(&self) -> impl Iterator<Item = &Entry> {
+    self.entries.iter().filter(|e| !e.bundled || e.favourite)
+  }
+
+  // entries containing every word of the query in their set, name or moves, favourites first
+  pub fn search(&self, query: &str) -> Vec<usize> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut found: Vec<usize> = (0..self.entries.len()).filter(|i| self.entries[*i].matches(&words)).collect();
+    found.sort_by_key(|i| !self.entries[*i].favourite);
+    found
+  }
+
+  // entries that finish their step from this position, each with the U turn to do first
+  pub fn recognise(&self, cube: &Cube) -> Vec<(usize, Option<Move>)> {
+    let before = CubieCube::from_cube(cube);
+    let mut found = Vec::new();
+    for (i, entry) in self.entries.iter().enumerate() {
+      let Some(turns) = (0..4).find(|turns| entry.set.reached(&before, &auf(&before, *turns).multiply(&entry.effect))) else {
+        continue
+      };
+      found.push((i, (turns > 0).then(|| Move::face(Face::U, turns as u8))));
+    }
+    found.sort_by_key(|(i, _)| !self.entries[*i].favourite);
+    found
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bundled_algorithms_are_all_valid() {
+    assert!(Library::parse(BUNDLED).1.is_empty());
+    let library = Library::bundled();
+    for (set, cases) in [(AlgSet::Pll, &PLL[..]), (AlgSet::Oll, &OLL[..]), (AlgSet::F2l, &F2L[..])] {
+      for case in cases { assert!(library.position(set, case.name).is_some(), "{} {} left out", set, case.name); }
+    }
+    assert_eq!(library.entries.len(), PLL.len() + OLL.len() + F2L.len() + Library::parse(BUNDLED).0.entries.len());
+  }
+
+  #[test]
+  fn checks_what_is_typed_in() {
+    let entry = Entry::from_input("OLL 27: R U R' U R U2 R'").unwrap();
+    assert_eq!((entry.set, entry.name.as_str()), (AlgSet::Oll, "27"));
+    assert!(matches!(Entry::from_input("PLL T: R U R' U R U2 R'"), Err(LibraryError::Invalid(..))));
+    assert!(matches!(Entry::from_input("PLL T R U R'"), Err(LibraryError::Fields)));
+  }
+
+  #[test]
+  fn player_lines_replace_bundled_ones() {
+    let mut library = Library::bundled();
+    let (mine, errors) = Library::parse("PLL\tT\tR U R' U' R' F R2 U' R' U' R U R' F'\t*\nPLL\tT2\tx R2 D2 R U R' D2 R U' R x'\n");
+    assert!(errors.is_empty());
+    library.merge(mine);
+    let t = &library.entries[library.position(AlgSet::Pll, "T").unwrap()];
+    assert!(t.bundled && t.favourite);
+    let saved: Vec<String> = library.user_entries().map(|e| e.name.clone()).collect();
+    assert_eq!(saved, ["T", "T2"]);
+  }
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::moves::{Alg, Move, Turn};
+
+/* MARK: METRICS
+  HTM: any outer or wide turn is 1, slices count as their two outer turns
+  QTM: like HTM but half turns count twice
+  STM: any layer turn is 1, including slices
+  ETM: every move is 1, rotations included
+  rotations cost nothing except in ETM
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric { Htm, Qtm, Stm, Etm }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Metrics {
+  pub htm: usize,
+  pub qtm: usize,
+  pub stm: usize,
+  pub etm: usize,
+}
+
+impl Metric {
+  pub const ALL: [Metric; 4] = [Metric::Htm, Metric::Qtm, Metric::Stm, Metric::Etm];
+
+  pub fn name(self) -> &'static str {
+    match self { Metric::Htm => "HTM", Metric::Qtm => "QTM", Metric::Stm => "STM", Metric::Etm => "ETM" }
+  }
+}
+
+impl Move {
+  pub fn cost(&self, metric: Metric) -> usize {
+    let quarters = self.quarter_turns().unsigned_abs() as usize;
+    if quarters == 0 { return 0 }
+    match (self.turn, metric) {
+      (_, Metric::Etm) => 1,
+      (Turn::Rotate(_), _) => 0,
+      (_, Metric::Stm) => 1,
+      (Turn::Slice(_), Metric::Htm) => 2,
+      (Turn::Slice(_), Metric::Qtm) => 2 * quarters,
+      (_, Metric::Htm) => 1,
+      (_, Metric::Qtm) => quarters,
+    }
+  }
+}
+
+impl Alg {
+  pub fn count(&self, metric: Metric) -> usize { self.iter().map(|mv| mv.cost(metric)).sum() }
+
+  pub fn metrics(&self) -> Metrics {
+    Metrics {
+      htm: self.count(Metric::Htm),
+      qtm: self.count(Metric::Qtm),
+      stm: self.count(Metric::Stm),
+      etm: self.count(Metric::Etm),
+    }
+  }
+}
+
+impl Metrics {
+  pub fn get(&self, metric: Metric) -> usize {
+    match metric { Metric::Htm => self.htm, Metric::Qtm => self.qtm, Metric::Stm => self.stm, Metric::Etm => self.etm }
+  }
+
+  pub fn add(&mut self, mv: &Move) {
+    self.htm += mv.cost(Metric::Htm);
+    self.qtm += mv.cost(Metric::Qtm);
+    self.stm += mv.cost(Metric::Stm);
+    self.etm += mv.cost(Metric::Etm);
+  }
+}
+
+impl fmt::Display for Metric {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.name()) }
+}
+
+impl FromStr for Metric {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Metric, String> {
+    Metric::ALL.into_iter().find(|m| m.name().eq_ignore_ascii_case(s)).ok_or(format!("unknown metric '{}'", s))
+  }
+}
+
+impl fmt::Display for Metrics {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} HTM, {} QTM, {} STM, {} ETM", self.htm, self.qtm, self.stm, self.etm)
+  }
+}
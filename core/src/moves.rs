@@ -0,0 +1,408 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::face::{Axis, Face};
+
+/* MARK: TURN
+  every layer combination the renderer can turn: outer faces, wide (face + middle),
+  middle slices and whole cube rotations
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+  Face(Face),
+  Wide(Face),
+  Slice(Slice),
+  Rotate(Axis),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slice { M, E, S }
+
+impl Slice {
+  pub fn axis(self) -> Axis {
+    match self { Slice::M => Axis::X, Slice::E => Axis::Y, Slice::S => Axis::Z }
+  }
+
+  // slices turn in the same direction as L, D and F respectively
+  pub fn follows(self) -> Face {
+    match self { Slice::M => Face::L, Slice::E => Face::D, Slice::S => Face::F }
+  }
+
+  pub fn to_char(self) -> char { ['M', 'E', 'S'][self as usize] }
+}
+
+impl Turn {
+  pub fn axis(self) -> Axis {
+    match self {
+      Turn::Face(face) | Turn::Wide(face) => face.axis(),
+      Turn::Slice(slice) => slice.axis(),
+      Turn::Rotate(axis) => axis,
+    }
+  }
+
+  // which layers along the axis are turned: [negative, middle, positive]
+  pub fn layers(self) -> [bool; 3] {
+    match self {
+      Turn::Face(face) => if face.is_positive() { [false, false, true] } else { [true, false, false] },
+      Turn::Wide(face) => if face.is_positive() { [false, true, true] } else { [true, true, false] },
+      Turn::Slice(_) => [false, true, false],
+      Turn::Rotate(_) => [true, true, true],
+    }
+  }
+
+  // sign of a clockwise quarter turn, measured as a right handed rotation about the positive axis
+  pub fn clockwise_sign(self) -> i8 {
+    let face = match self {
+      Turn::Face(face) | Turn::Wide(face) => face,
+      Turn::Slice(slice) => slice.follows(),
+      Turn::Rotate(axis) => Face::from_axis(axis, true),
+    };
+    if face.is_positive() { -1 } else { 1 }
+  }
+}
+
+/* MARK: MOVE
+  amount is the number of clockwise quarter turns: 1, 2 or 3 (prime)
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+  pub turn: Turn,
+  pub amount: u8,
+}
+
+impl Move {
+  pub fn new(turn: Turn, amount: u8) -> Move { Move { turn, amount: amount % 4 } }
+
+  pub fn face(face: Face, amount: u8) -> Move { Move::new(Turn::Face(face), amount) }
+
+  pub fn axis(&self) -> Axis { self.turn.axis() }
+
+  pub fn layers(&self) -> [bool; 3] { self.turn.layers() }
+
+  // signed quarter turns about the positive axis, always within -2..=2
+  pub fn quarter_turns(&self) -> i8 {
+    let sign = self.turn.clockwise_sign();
+    match self.amount % 4 {
+      1 => sign,
+      2 => 2 * sign,
+      3 => -sign,
+      _ => 0,
+    }
+  }
+
+  pub fn inverse(&self) -> Move { Move::new(self.turn, 4 - self.amount % 4) }
+
+  pub fn is_rotation(&self) -> bool { matches!(self.turn, Turn::Rotate(_)) }
+
+  // move turning `layers` by signed quarter turns about the positive axis, if one exists
+  pub fn from_layers(axis: Axis, layers: [bool; 3], quarters: i8) -> Option<Move> {
+    let turn = match layers {
+      [false, false, true] => Turn::Face(Face::from_axis(axis, true)),
+      [true, false, false] => Turn::Face(Face::from_axis(axis, false)),
+      [false, true, true] => Turn::Wide(Face::from_axis(axis, true)),
+      [true, true, false] => Turn::Wide(Face::from_axis(axis, false)),
+      [false, true, false] => Turn::Slice(match axis { Axis::X => Slice::M, Axis::Y => Slice::E, Axis::Z => Slice::S }),
+      [true, true, true] => Turn::Rotate(axis),
+      _ => return None,
+    };
+    let amount = (quarters * turn.clockwise_sign()).rem_euclid(4) as u8;
+    if amount == 0 { None } else { Some(Move::new(turn, amount)) }
+  }
+}
+
+impl fmt::Display for Move {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.turn {
+      Turn::Face(face) => write!(f, "{}", face)?,
+      Turn::Wide(face) => write!(f, "{}w", face)?,
+      Turn::Slice(slice) => write!(f, "{}", slice.to_char())?,
+      Turn::Rotate(axis) => write!(f, "{}", axis)?,
+    }
+    match self.amount % 4 {
+      2 => write!(f, "2"),
+      3 => write!(f, "'"),
+      _ => Ok(()),
+    }
+  }
+}
+
+/* MARK: ALGORITHM
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Alg(pub Vec<Move>);
+
+impl Alg {
+  pub fn new() -> Alg { Alg(Vec::new()) }
+
+  pub fn len(&self) -> usize { self.0.len() }
+
+  pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+  pub fn push(&mut self, mv: Move) { self.0.push(mv) }
+
+  pub fn iter(&self) -> std::slice::Iter<'_, Move> { self.0.iter() }
+
+  pub fn inverse(&self) -> Alg { Alg(self.0.iter().rev().map(Move::inverse).collect()) }
+
+  pub fn concat(&self, other: &Alg) -> Alg {
+    Alg(self.0.iter().chain(other.0.iter()).copied().collect())
+  }
+}
+
+// A B A' B'
+pub fn commutator(a: &Alg, b: &Alg) -> Alg { a.concat(b).concat(&a.inverse()).concat(&b.inverse()) }
+
+// A B A'
+pub fn conjugate(setup: &Alg, body: &Alg) -> Alg { setup.concat(body).concat(&setup.inverse()) }
+
+impl From<Vec<Move>> for Alg {
+  fn from(moves: Vec<Move>) -> Alg { Alg(moves) }
+}
+
+impl IntoIterator for Alg {
+  type Item = Move;
+  type IntoIter = std::vec::IntoIter<Move>;
+  fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+
+impl<'a> IntoIterator for &'a Alg {
+  type Item = &'a Move;
+  type IntoIter = std::slice::Iter<'a, Move>;
+  fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+
+impl fmt::Display for Alg {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, mv) in self.0.iter().enumerate() {
+      if i > 0 { write!(f, " ")?; }
+      write!(f, "{}", mv)?;
+    }
+    Ok(())
+  }
+}
+
+/* MARK: PARSING
+  accepts WCA style notation: R U' F2 Rw r M E S x y z, with optional (groups)N,
+  commutators [A, B] = A B A' B' and conjugates [A: B] = A B A', nested as deep as wanted
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseAlgError {
+  UnexpectedChar { index: usize, found: char },
+  UnclosedGroup { index: usize },
+  // a bracket without the , or : between its two halves
+  MissingSeparator { index: usize },
+  // a repeat or bracket that would expand past MAX_MOVES
+  TooLong { index: usize },
+  // a move was wanted but the string held none or several
+  NotSingleMove { count: usize },
+}
+
+// longest alg a string may expand to, (R U)99999999 would otherwise never finish
+pub const MAX_MOVES: usize = 10_000;
+
+impl fmt::Display for ParseAlgError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseAlgError::UnexpectedChar { index, found } => write!(f, "unexpected '{}' at {}", found, index),
+      ParseAlgError::UnclosedGroup { index } => write!(f, "group opened at {} is never closed", index),
+      ParseAlgError::MissingSeparator { index } => write!(f, "bracket opened at {} needs a , or :", index),
+      ParseAlgError::TooLong { index } => write!(f, "expands to more than {} moves at {}", MAX_MOVES, index),
+      ParseAlgError::NotSingleMove { count } => write!(f, "expected a single move, found {}", count),
+    }
+  }
+}
+
+impl std::error::Error for ParseAlgError {}
+
+impl FromStr for Move {
+  type Err = ParseAlgError;
+
+  fn from_str(s: &str) -> Result<Move, ParseAlgError> {
+    let alg: Alg = s.parse()?;
+    match alg.0.as_slice() {
+      [mv] => Ok(*mv),
+      moves => Err(ParseAlgError::NotSingleMove { count: moves.len() }),
+    }
+  }
+}
+
+impl FromStr for Alg {
+  type Err = ParseAlgError;
+
+  fn from_str(s: &str) -> Result<Alg, ParseAlgError> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut parser = Parser { chars, index: 0 };
+    let alg = parser.sequence()?;
+    match parser.peek() {
+      Some(found) => Err(ParseAlgError::UnexpectedChar { index: parser.index, found }),
+      None => Ok(alg),
+    }
+  }
+}
+
+struct Parser {
+  chars: Vec<char>,
+  index: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<char> { self.chars.get(self.index).copied() }
+
+  fn skip_space(&mut self) {
+    while self.peek().is_some_and(|c| c.is_whitespace()) { self.index += 1; }
+  }
+
+  // parse until end of input, a closing bracket or a bracket separator
+  fn sequence(&mut self) -> Result<Alg, ParseAlgError> {
+    let mut alg = Alg::new();
+    loop {
+      self.skip_space();
+      match self.peek() {
+        None | Some(')' | ']' | ',' | ':') => return Ok(alg),
+        Some('(') => {
+          let open = self.index;
+          self.index += 1;
+          let group = self.sequence()?;
+          if self.peek() != Some(')') { return Err(ParseAlgError::UnclosedGroup { index: open }) }
+          self.index += 1;
+          self.repeat(&mut alg, &group, open)?;
+        }
+        Some('[') => {
+          let open = self.index;
+          let group = self.bracket()?;
+          self.repeat(&mut alg, &group, open)?;
+        }
+        Some(c) => {
+          let turn = self.turn(c)?;
+          let amount = self.amount().rem_euclid(4) as u8;
+          if amount != 0 { alg.push(Move::new(turn, amount)); }
+          if alg.len() > MAX_MOVES { return Err(ParseAlgError::TooLong { index: self.index }) }
+        }
+      }
+    }
+  }
+
+  // [A, B] or [A: B], expanded
+  fn bracket(&mut self) -> Result<Alg, ParseAlgError> {
+    let open = self.index;
+    self.index += 1;
+    let first = self.sequence()?;
+    let separator = self.peek();
+    if !matches!(separator, Some(',' | ':')) {
+      return Err(match separator {
+        Some(']') => ParseAlgError::MissingSeparator { index: open },
+        _ => ParseAlgError::UnclosedGroup { index: open },
+      })
+    }
+    self.index += 1;
+    let second = self.sequence()?;
+    if self.peek() != Some(']') { return Err(ParseAlgError::UnclosedGroup { index: open }) }
+    self.index += 1;
+    // both halves appear twice in a commutator, the setup twice in a conjugate
+    let length = match separator {
+      Some(',') => 2 * (first.len() + second.len()),
+      _ => 2 * first.len() + second.len(),
+    };
+    if length > MAX_MOVES { return Err(ParseAlgError::TooLong { index: open }) }
+    Ok(match separator {
+      Some(',') => commutator(&first, &second),
+      _ => conjugate(&first, &second),
+    })
+  }
+
+  // a group followed by its repetition suffix, a prime inverting it
+  fn repeat(&mut self, alg: &mut Alg, group: &Alg, open: usize) -> Result<(), ParseAlgError> {
+    let amount = self.amount();
+    let count = amount.unsigned_abs() as usize;
+    if group.is_empty() { return Ok(()) }
+    if count.saturating_mul(group.len()).saturating_add(alg.len()) > MAX_MOVES {
+      return Err(ParseAlgError::TooLong { index: open })
+    }
+    let part = if amount < 0 { group.inverse() } else { group.clone() };
+    for _ in 0..count { alg.0.extend_from_slice(&part.0); }
+    Ok(())
+  }
+
+  fn turn(&mut self, c: char) -> Result<Turn, ParseAlgError> {
+    let start = self.index;
+    self.index += 1;
+    let turn = if let Some(face) = Face::from_char(c) {
+      if c.is_ascii_lowercase() {
+        Turn::Wide(face)
+      } else if self.peek() == Some('w') {
+        self.index += 1;
+        Turn::Wide(face)
+      } else {
+        Turn::Face(face)
+      }
+    } else if let Some(axis) = Axis::from_char(c) {
+      Turn::Rotate(axis)
+    } else {
+      match c {
+        'M' => Turn::Slice(Slice::M),
+        'E' => Turn::Slice(Slice::E),
+        'S' => Turn::Slice(Slice::S),
+        _ => return Err(ParseAlgError::UnexpectedChar { index: start, found: c }),
+      }
+    };
+    Ok(turn)
+  }
+
+  // repetition suffix: digits followed by an optional prime, defaults to 1
+  fn amount(&mut self) -> i32 {
+    let mut count: Option<i32> = None;
+    while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+      count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit as i32));
+      self.index += 1;
+    }
+    let mut count = count.unwrap_or(1);
+    if matches!(self.peek(), Some('\'') | Some('’')) {
+      self.index += 1;
+      count = -count;
+    }
+    count
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn alg(s: &str) -> Alg { s.parse().unwrap() }
+
+  #[test]
+  fn prints_what_it_parses() {
+    for s in ["R U R' U'", "Rw2 Lw' M E2 S'", "x y' z2 F B2 L' D"] {
+      assert_eq!(alg(s).to_string(), s);
+    }
+    // lower case wide turns and spacing are read but not kept
+    assert_eq!(alg("r'  u2(F)").to_string(), "Rw' Uw2 F");
+  }
+
+  #[test]
+  fn expands_groups_and_brackets() {
+    assert_eq!(alg("(R U)2"), alg("R U R U"));
+    assert_eq!(alg("(R U)2'"), alg("U' R' U' R'"));
+    assert_eq!(alg("(R (U F)2)2"), alg("R U F U F R U F U F"));
+    assert_eq!(alg("[R, U]"), alg("R U R' U'"));
+    assert_eq!(alg("[F: [R, U]]"), alg("F R U R' U' F'"));
+    assert_eq!(alg("R4 U0"), Alg::new());
+  }
+
+  #[test]
+  fn reports_where_it_fails() {
+    assert_eq!("R Q".parse::<Alg>(), Err(ParseAlgError::UnexpectedChar { index: 2, found: 'Q' }));
+    assert_eq!("R (U F".parse::<Alg>(), Err(ParseAlgError::UnclosedGroup { index: 2 }));
+    assert_eq!("R U)".parse::<Alg>(), Err(ParseAlgError::UnexpectedChar { index: 3, found: ')' }));
+    assert_eq!("[R U]".parse::<Alg>(), Err(ParseAlgError::MissingSeparator { index: 0 }));
+    assert_eq!("R U".parse::<Move>(), Err(ParseAlgError::NotSingleMove { count: 2 }));
+    assert_eq!("R2'".parse::<Move>().map(|mv| mv.to_string()), Ok("R2".to_string()));
+  }
+
+  #[test]
+  fn refuses_huge_expansions() {
+    assert_eq!("R (R U)99999999".parse::<Alg>(), Err(ParseAlgError::TooLong { index: 2 }));
+    assert_eq!(alg(&format!("(R U){}", MAX_MOVES / 2)).len(), MAX_MOVES);
+    assert!(matches!("[(R U)3000, (F D)3000]".parse::<Alg>(), Err(ParseAlgError::TooLong { .. })));
+  }
+}
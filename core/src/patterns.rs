@@ -0,0 +1,21 @@
+/* MARK: PATTERNS
+  well known pretty patterns, each set up from a solved cube with the D face down and F in front
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern {
+  pub name: &'static str,
+  pub alg: &'static str,
+}
+
+pub const PATTERNS: [Pattern; 10] = [
+  Pattern { name: "Checkerboard", alg: "M2 E2 S2" },
+  Pattern { name: "Superflip", alg: "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2" },
+  Pattern { name: "Cube in a cube", alg: "F L F U' R U F2 L2 U' L' B D' B' L2 U" },
+  Pattern { name: "Cube in a cube in a cube", alg: "U' L' U' F' R2 B' R F U B2 U B' L U' F U R F'" },
+  Pattern { name: "Python", alg: "F2 R' B' U R' L F' L F' B D' R B L2" },
+  Pattern { name: "Anaconda", alg: "L U B' U' R L' B R' F B' D R D' F'" },
+  Pattern { name: "Six spots", alg: "U D' R L' F B' U D'" },
+  Pattern { name: "Four spots", alg: "F2 B2 U D' R2 L2 U D'" },
+  Pattern { name: "Plus", alg: "U F B' L2 U2 L2 F' B U2 L2 U" },
+  Pattern { name: "Tetris", alg: "L R F B U' D' L' R'" },
+];
@@ -0,0 +1,189 @@
+use rand_core::RngCore;
+
+use crate::cube::{rotations, sticker_coords, tables, Cube};
+use crate::cubie::permutation_parity;
+use crate::face::{Axis, Face};
+use crate::moves::{Alg, Move, Slice, Turn};
+
+/* MARK: PUZZLE
+  puzzles played on the 3x3 mechanism with some of its pieces missing. a side two layers deep
+  has no middle slice, and a layer only turns a quarter when the two sides it spans are the
+  same length, otherwise it has to go half way round to fit back. the state is still a 3x3,
+  the missing pieces move along unseen and are never looked at
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Puzzle {
+  #[default]
+  Cube,
+  // no centres, so the middle slices are the only thing holding a frame
+  Void,
+  // two layers tall, U and D turn a quarter and the sides only half
+  Cuboid332,
+  // two layers wide and deep, the sides only turn half
+  Cuboid223,
+}
+
+impl Puzzle {
+  pub const ALL: [Puzzle; 4] = [Puzzle::Cube, Puzzle::Void, Puzzle::Cuboid332, Puzzle::Cuboid223];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Puzzle::Cube => "3x3x3",
+      Puzzle::Void => "Void cube",
+      Puzzle::Cuboid332 => "3x3x2",
+      Puzzle::Cuboid223 => "2x2x3",
+    }
+  }
+
+  pub fn next(self) -> Puzzle { Puzzle::ALL[(self as usize + 1) % Puzzle::ALL.len()] }
+
+  // layers along x, y and z
+  pub fn dimensions(self) -> [u8; 3] {
+    match self {
+      Puzzle::Cube | Puzzle::Void => [3, 3, 3],
+      Puzzle::Cuboid332 => [3, 2, 3],
+      Puzzle::Cuboid223 => [2, 3, 2],
+    }
+  }
+
+  // smallest turn about an axis in quarter turns
+  pub fn step(self, axis: Axis) -> i8 {
+    let dimensions = self.dimensions();
+    let across: Vec<u8> = Axis::ALL.iter().filter(|a| **a != axis).map(|a| dimensions[a.index()]).collect();
+    if across[0] == across[1] { 1 } else { 2 }
+  }
+
+  // whether a cubie position of the 3x3, -1..=1 on each axis, holds a piece
+  pub fn has_piece(self, position: [i8; 3]) -> bool {
+    let dimensions = self.dimensions();
+    if (0..3).any(|k| dimensions[k] == 2 && position[k] == 0) { return false }
+    // the void cube is missing its centres and core, everything with fewer than two sides out
+    !(self == Puzzle::Void && position.iter().filter(|v| **v != 0).count() < 2)
+  }
+
+  pub fn allows(self, mv: Move) -> bool { self.fit(mv) == Some(mv) }
+
+  // the turn as the puzzle can make it: a quarter turn where only half turns fit becomes a half
+  // turn and a wide turn with no middle is just its face. slices that do not exist are None
+  pub fn fit(self, mv: Move) -> Option<Move> {
+    let axis = mv.axis();
+    let turn = match mv.turn {
+      Turn::Slice(_) if self.dimensions()[axis.index()] == 2 => return None,
+      Turn::Wide(face) if self.dimensions()[axis.index()] == 2 => Turn::Face(face),
+      turn => turn,
+    };
+    let amount = if self.step(axis) == 2 { 2 } else { mv.amount };
+    Some(Move::new(turn, amount))
+  }
+
+  pub fn fit_alg(self, alg: &Alg) -> Alg { Alg(alg.iter().filter_map(|mv| self.fit(*mv)).collect()) }
+
+  // every side a single colour across the stickers it has, however the puzzle is held
+  pub fn solved(self, cube: &Cube) -> bool {
+    if self == Puzzle::Cube { return cube.is_solved_any_orientation() }
+    let colors = cube.home_colors();
+    Face::ALL.iter().all(|face| {
+      let mut shown = (face.index() * 9..face.index() * 9 + 9).filter(|i| self.has_piece(sticker_coords(*i).0));
+      let Some(first) = shown.next() else { return true };
+      shown.all(|i| colors[i] == colors[first])
+    })
+  }
+
+  // faces the puzzle can turn, with slices as well on the void cube where they are what
+  // changes its parity
+  fn turns(self) -> Vec<Turn> {
+    let mut turns: Vec<Turn> = Face::ALL.iter().map(|face| Turn::Face(*face)).collect();
+    if self == Puzzle::Void { turns.extend([Slice::M, Slice::E, Slice::S].map(Turn::Slice)); }
+    turns
+  }
+
+  // random turns the puzzle allows, never the same layers twice in a row or three on one axis
+  pub fn random_moves(self, rng: &mut impl RngCore, count: usize) -> Alg {
+    let turns = self.turns();
+    let mut alg = Alg::new();
+    while alg.len() < count {
+      let turn = turns[rng.next_u32() as usize % turns.len()];
+      let amount = if self.step(turn.axis()) == 2 { 2 } else { (rng.next_u32() % 3) as u8 + 1 };
+
+      let previous: Vec<&Move> = alg.iter().rev().take(2).collect();
+      if previous.first().is_some_and(|mv| mv.turn == turn) { continue }
+      if previous.len() == 2 && previous.iter().all(|mv| mv.axis() == turn.axis()) { continue }
+
+      alg.push(Move::new(turn, amount));
+    }
+    alg
+  }
+}
+
+/* MARK: VOID PARITY
+  with no centres to go by, a void cube solved like a 3x3 can end with two edges swapped, which
+  only a slice quarter turn puts right. measured with the cube held so the DBL corner is home,
+  it is whether the corners and edges are permuted with different parity
+*/
+pub fn void_parity(cube: &Cube) -> bool {
+  let tables = tables();
+  let dbl = &tables.corners[6];
+  let held = rotations().iter().map(|rotation| {
+    let mut held = *cube;
+    held.apply_alg(rotation);
+    held
+  }).find(|held| dbl.iter().all(|sticker| held.facelets()[*sticker as usize] == *sticker));
+  let Some(held) = held else { return false };
+
+  // which piece sits in each position, by the home position of one of its stickers
+  let permutation = |pieces: &[Vec<u8>]| -> Vec<u8> {
+    pieces.iter().map(|position| {
+      let id = held.facelets()[position[0] as usize];
+      pieces.iter().position(|piece| piece.contains(&id)).unwrap_or(0) as u8
+    }).collect()
+  };
+  permutation_parity(&permutation(&tables.corners)) != permutation_parity(&permutation(&tables.edges))
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+
+  fn parity(alg: &Alg) -> bool { void_parity(&Cube::from_alg(alg)) }
+
+  #[test]
+  fn slice_quarter_turns_flip_void_parity() {
+    let alg = |s: &str| -> Alg { s.parse().unwrap() };
+    assert!(!parity(&alg("")));
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    for _ in 0..20 {
+      let scramble = Puzzle::Void.random_moves(&mut rng, 20);
+      let before = parity(&scramble);
+      assert_eq!(parity(&scramble.concat(&alg("M"))), !before);
+      assert_eq!(parity(&scramble.concat(&alg("E' S"))), before);
+      assert_eq!(parity(&scramble.concat(&alg("S2"))), before);
+      // how the cube is held makes no difference
+      assert_eq!(parity(&scramble.concat(&alg("x y' z2"))), before);
+    }
+  }
+
+  #[test]
+  fn turns_that_keep_dbl_home_keep_void_parity() {
+    let faces = [Face::U, Face::R, Face::F];
+    let mut rng = ChaCha8Rng::seed_from_u64(8);
+    for _ in 0..20 {
+      let alg = Alg((0..20).map(|_| Move::face(faces[rng.next_u32() as usize % 3], (rng.next_u32() % 3) as u8 + 1)).collect());
+      assert!(!parity(&alg));
+    }
+  }
+
+  #[test]
+  fn cuboids_only_turn_what_fits() {
+    let turn = |s: &str| s.parse::<Move>().unwrap();
+    assert!(Puzzle::Cuboid332.allows(turn("U")));
+    assert!(!Puzzle::Cuboid332.allows(turn("R")));
+    assert_eq!(Puzzle::Cuboid332.fit(turn("R")), Some(turn("R2")));
+    let mut rng = ChaCha8Rng::seed_from_u64(9);
+    for puzzle in Puzzle::ALL {
+      assert!(puzzle.random_moves(&mut rng, 30).iter().all(|mv| puzzle.allows(*mv)));
+    }
+  }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::metrics::{Metric, Metrics};
+use crate::moves::{Alg, Move};
+use crate::steps::{Split, Step};
+
+/* MARK: SOLVE RECORD
+  every move made while the clock was running, timestamped in seconds from the first turn
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedMove {
+  pub time: f32,
+  pub mv: Move,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveRecord {
+  pub scramble: Alg,
+  pub moves: Vec<TimedMove>,
+  pub time: f32,
+  pub metrics: Metrics,
+  pub splits: Vec<Split>,
+  // hints asked for during the solve, any at all marks it as assisted
+  pub hints: usize,
+}
+
+impl SolveRecord {
+  pub fn new(scramble: Alg, moves: Vec<TimedMove>, time: f32) -> SolveRecord {
+    let metrics = Alg(moves.iter().map(|m| m.mv).collect()).metrics();
+    SolveRecord { scramble, moves, time, metrics, splits: Vec::new(), hints: 0 }
+  }
+
+  pub fn alg(&self) -> Alg { Alg(self.moves.iter().map(|m| m.mv).collect()) }
+
+  pub fn tps(&self, metric: Metric) -> f32 { tps(self.metrics.get(metric), self.time) }
+}
+
+// turns per second, zero before any time has passed
+pub fn tps(turns: usize, seconds: f32) -> f32 {
+  if seconds > 0.0 { turns as f32 / seconds } else { 0.0 }
+}
+
+/* MARK: SAVED FORMAT
+  one solve per line, tab separated: time, scramble, "time move" pairs, "step end" splits, hints used
+  e.g. 9.120	R U F'	0.000 y,0.410 R,0.700 U'	cross 1,pair1 2	0
+  lines saved before hints were counted end after the splits
+*/
+impl fmt::Display for SolveRecord {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let moves: Vec<String> = self.moves.iter().map(|m| format!("{:.3} {}", m.time, m.mv)).collect();
+    let splits: Vec<String> = self.splits.iter().map(|s| format!("{} {}", s.step.id(), s.end)).collect();
+    write!(f, "{:.3}\t{}\t{}\t{}\t{}", self.time, self.scramble, moves.join(","), splits.join(","), self.hints)
+  }
+}
+
+impl FromStr for SolveRecord {
+  type Err = String;
+  fn from_str(s: &str) -> Result<SolveRecord, String> {
+    let fields: Vec<&str> = s.trim_end_matches(['\r', '\n']).split('\t').collect();
+    let [time, scramble, moves, rest @ ..] = fields.as_slice() else { return Err("missing fields".to_string()) };
+
+    let time: f32 = time.parse().map_err(|_| format!("invalid time '{}'", time))?;
+    let scramble: Alg = scramble.parse().map_err(|e| format!("invalid scramble: {}", e))?;
+    let moves = moves.split(',').filter(|m| !m.is_empty()).map(|m| {
+      let (at, mv) = m.split_once(' ').ok_or(format!("invalid move '{}'", m))?;
+      Ok(TimedMove {
+        time: at.parse().map_err(|_| format!("invalid move time '{}'", at))?,
+        mv: mv.parse().map_err(|e| format!("invalid move '{}': {}", mv, e))?,
+      })
+    }).collect::<Result<Vec<_>, String>>()?;
+
+    let mut record = SolveRecord::new(scramble, moves, time);
+    let mut start = 0;
+    for split in rest.first().unwrap_or(&"").split(',').filter(|s| !s.is_empty()) {
+      let (step, end) = split.split_once(' ').ok_or(format!("invalid split '{}'", split))?;
+      let step: Step = step.parse()?;
+      let end: usize = end.parse().map_err(|_| format!("invalid split '{}'", split))?;
+      record.splits.push(Split::new(&record, step, start, end));
+      start = end;
+    }
+    if let Some(hints) = rest.get(1) {
+      record.hints = hints.parse().map_err(|_| format!("invalid hint count '{}'", hints))?;
+    }
+    Ok(record)
+  }
+}
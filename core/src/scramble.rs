@@ -0,0 +1,55 @@
+use rand_core::RngCore;
+
+use crate::cube::Cube;
+use crate::cubie::{permutation_parity, set_permutation, CubieCube};
+use crate::face::Face;
+use crate::moves::{Alg, Move, Turn};
+use crate::solver;
+
+/* MARK: RANDOM MOVES
+  face turns that never repeat a face, or turn the same axis three times in a row
+*/
+pub fn random_moves(rng: &mut impl RngCore, count: usize) -> Alg {
+  let mut alg = Alg::new();
+  while alg.len() < count {
+    let face = Face::from_index((rng.next_u32() % 6) as usize);
+    let amount = (rng.next_u32() % 3) as u8 + 1;
+
+    let previous: Vec<Face> = alg.iter().rev().take(2).filter_map(|mv| match mv.turn {
+      Turn::Face(face) => Some(face),
+      _ => None,
+    }).collect();
+    if previous.first() == Some(&face) { continue }
+    if previous.len() == 2 && previous.iter().all(|p| p.axis() == face.axis()) { continue }
+
+    alg.push(Move::face(face, amount));
+  }
+  alg
+}
+
+/* MARK: RANDOM STATE
+  uniformly random solvable cube, and the inverse of its solution as the scramble
+*/
+pub fn random_cube(rng: &mut impl RngCore) -> CubieCube {
+  let mut cube = CubieCube::SOLVED;
+  set_permutation(&mut cube.cp, (rng.next_u32() % 40320) as usize);
+  set_permutation(&mut cube.ep, (rng.next_u64() % 479_001_600) as usize);
+  if permutation_parity(&cube.cp) != permutation_parity(&cube.ep) { cube.ep.swap(10, 11); }
+  cube.set_twist((rng.next_u32() % 2187) as u16);
+  cube.set_flip((rng.next_u32() % 2048) as u16);
+  cube
+}
+
+pub fn random_state(rng: &mut impl RngCore) -> Alg {
+  let cube = random_cube(rng).to_cube();
+  match solver::solve(&cube) {
+    Ok(solution) => solution.inverse(),
+    // the two phase search practically never fails this short, fall back to moves
+    Err(_) => random_moves(rng, 25),
+  }
+}
+
+// scramble that takes a solved cube to `cube`
+pub fn scramble_to(cube: &Cube) -> Result<Alg, solver::SolveError> {
+  solver::solve(cube).map(|solution| solution.inverse())
+}
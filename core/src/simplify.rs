@@ -0,0 +1,127 @@
+use crate::cube::Cube;
+use crate::face::{Axis, Face};
+use crate::moves::{Alg, Move};
+
+/* MARK: SIMPLIFY
+  rotations are taken out by relabelling the turns after them, then each run of turns on one axis
+  (which all commute) is summed per layer and rewritten with as few moves as possible.
+  the result reaches the same state as the original up to a whole cube rotation
+*/
+impl Alg {
+  pub fn simplify(&self) -> Alg {
+    let mut moves = self.0.clone();
+    loop {
+      let merged = merge_axis_runs(&without_rotations(&moves));
+      if merged == moves { return Alg(merged) }
+      moves = merged;
+    }
+  }
+
+  pub fn without_rotations(&self) -> Alg { Alg(without_rotations(&self.0)) }
+}
+
+fn without_rotations(moves: &[Move]) -> Vec<Move> {
+  // only rotations are applied here, so the centres say how the cube is held
+  let mut held = Cube::solved();
+  let mut result = Vec::with_capacity(moves.len());
+  for mv in moves {
+    if mv.is_rotation() { held.apply_move(*mv); } else { result.push(relabel(&held, *mv)); }
+  }
+  result
+}
+
+// the same physical turn, named for the orientation the algorithm started in
+fn relabel(held: &Cube, mv: Move) -> Move {
+  let centers = held.center_positions();
+  let physical = Face::from_axis(mv.axis(), true);
+  let original = Face::from_index(centers.iter().position(|f| *f == physical).unwrap());
+
+  let (mut layers, mut quarters) = (mv.layers(), mv.quarter_turns());
+  if !original.is_positive() {
+    layers.reverse();
+    quarters = -quarters;
+  }
+  Move::from_layers(original.axis(), layers, quarters).unwrap_or(mv)
+}
+
+fn merge_axis_runs(moves: &[Move]) -> Vec<Move> {
+  let mut result = Vec::with_capacity(moves.len());
+  let mut start = 0;
+  while start < moves.len() {
+    let axis = moves[start].axis();
+    let end = moves[start..].iter().position(|mv| mv.axis() != axis).map_or(moves.len(), |p| start + p);
+    let run = &moves[start..end];
+
+    let mut turns = [0i32; 3];
+    for mv in run {
+      for (layer, turned) in mv.layers().iter().enumerate() {
+        if *turned { turns[layer] += mv.quarter_turns() as i32; }
+      }
+    }
+    let shortest = shortest_run(axis, turns);
+    // a run that is already as short keeps the order and names it was written with
+    if shortest.len() < run.len() || run.iter().any(|mv| mv.amount == 0) {
+      result.extend(shortest);
+    } else {
+      result.extend_from_slice(run);
+    }
+    start = end;
+  }
+  result
+}
+
+// fewest moves turning the [negative, middle, positive] layers by the given quarter turns
+fn shortest_run(axis: Axis, turns: [i32; 3]) -> Vec<Move> {
+  let [neg, mid, pos] = turns.map(|t| t.rem_euclid(4) as i8);
+  let layer = |layers: [bool; 3], quarters: i8| Move::from_layers(axis, layers, quarters);
+
+  if mid != 0 && neg == mid && pos == mid {
+    return layer([true, true, true], mid).into_iter().collect();
+  }
+
+  let mut candidates = vec![vec![
+    layer([false, false, true], pos), layer([true, false, false], neg), layer([false, true, false], mid),
+  ]];
+  if mid != 0 {
+    candidates.push(vec![layer([false, true, true], mid), layer([false, false, true], pos - mid), layer([true, false, false], neg)]);
+    candidates.push(vec![layer([true, true, false], mid), layer([true, false, false], neg - mid), layer([false, false, true], pos)]);
+  }
+  candidates.into_iter()
+    .map(|moves| moves.into_iter().flatten().collect::<Vec<Move>>())
+    .min_by_key(|moves| moves.len())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::{RngCore, SeedableRng};
+
+  use super::*;
+  use crate::moves::{Slice, Turn};
+
+  #[test]
+  fn merges_turns_on_one_axis() {
+    let alg: Alg = "R L R' U U2 D D'".parse().unwrap();
+    assert_eq!(alg.simplify(), "L U'".parse().unwrap());
+    let alg: Alg = "R R'".parse().unwrap();
+    assert!(alg.simplify().is_empty());
+  }
+
+  #[test]
+  fn keeps_the_state() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let turns: Vec<Turn> = Face::ALL.iter().flat_map(|face| [Turn::Face(*face), Turn::Wide(*face)])
+      .chain([Slice::M, Slice::E, Slice::S].map(Turn::Slice))
+      .chain([Axis::X, Axis::Y, Axis::Z].map(Turn::Rotate))
+      .collect();
+    for _ in 0..200 {
+      let alg = Alg((0..20).map(|_| Move::new(turns[rng.next_u32() as usize % turns.len()], (rng.next_u32() % 3) as u8 + 1)).collect());
+      let simplified = alg.simplify();
+      assert!(simplified.iter().all(|mv| !mv.is_rotation()));
+      assert!(simplified.len() <= alg.len());
+      // the same up to how the cube is held
+      assert_eq!(Cube::from_alg(&alg).normalized(), Cube::from_alg(&simplified).normalized(), "{} simplified to {}", alg, simplified);
+    }
+  }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::cube::Cube;
+use crate::cubie::CubieCube;
+use crate::face::Face;
+use crate::moves::{Alg, Move, Turn};
+
+pub mod optimal;
+pub mod pieces;
+pub mod thistlethwaite;
+pub mod two_phase;
+
+// two phase solutions are almost always found within this many moves
+pub const DEFAULT_MAX_LENGTH: usize = 22;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+  Unsolvable,
+  NotFound(usize),
+  Cancelled,
+}
+
+impl fmt::Display for SolveError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SolveError::Unsolvable => write!(f, "cube cannot be solved, a piece is twisted, flipped or swapped"),
+      SolveError::NotFound(max) => write!(f, "no solution within {} moves", max),
+      SolveError::Cancelled => write!(f, "search cancelled"),
+    }
+  }
+}
+
+impl std::error::Error for SolveError {}
+
+/* MARK: SOLVE
+  face turns that solve the cube as it is currently held; the cube ends with every face
+  a single colour, so a cube that was rotated stays rotated
+*/
+pub fn solve(cube: &Cube) -> Result<Alg, SolveError> { solve_within(cube, DEFAULT_MAX_LENGTH) }
+
+pub fn solve_within(cube: &Cube, max_length: usize) -> Result<Alg, SolveError> {
+  let cubie = CubieCube::from_cube(cube);
+  if !cubie.is_solvable() { return Err(SolveError::Unsolvable) }
+  let solution = two_phase::solve(&cubie, max_length).ok_or(SolveError::NotFound(max_length))?;
+  Ok(in_frame(cube, &solution))
+}
+
+// relabel face turns found on the normalised cube to the faces the centres currently sit on
+pub fn in_frame(cube: &Cube, alg: &Alg) -> Alg {
+  let centers = cube.center_positions();
+  let relabel = |face: Face| centers[face.index()];
+  Alg(alg.iter().map(|mv| match mv.turn {
+    Turn::Face(face) => Move::new(Turn::Face(relabel(face)), mv.amount),
+    Turn::Wide(face) => Move::new(Turn::Wide(relabel(face)), mv.amount),
+    _ => *mv,
+  }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+  use crate::scramble;
+
+  #[test]
+  fn solves_random_states() {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    for _ in 0..10 {
+      let cube = scramble::random_cube(&mut rng).to_cube();
+      let solution = solve(&cube).unwrap();
+      assert!(solution.len() <= DEFAULT_MAX_LENGTH);
+      let mut solved = cube;
+      solved.apply_alg(&solution);
+      assert!(solved.is_solved());
+    }
+  }
+
+  #[test]
+  fn solves_a_cube_held_rotated() {
+    let scramble: Alg = "x R U2 F' y D".parse().unwrap();
+    let cube = Cube::from_alg(&scramble);
+    let mut solved = cube;
+    solved.apply_alg(&solve(&cube).unwrap());
+    assert!(solved.is_solved_any_orientation());
+    assert_eq!(solved.center_positions(), cube.center_positions());
+  }
+
+  #[test]
+  fn refuses_a_twisted_corner() {
+    let mut cubie = CubieCube::SOLVED;
+    cubie.co[0] = 1;
+    assert_eq!(solve(&cubie.to_cube()), Err(SolveError::Unsolvable));
+  }
+}
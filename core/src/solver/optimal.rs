@@ -0,0 +1,323 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::cube::Cube;
+use crate::cubie::{CubieCube, BL, BR, DB, DF, DL, DR, FL, FR, UB, UF, UL, UR};
+use crate::metrics::Metric;
+use crate::moves::Alg;
+
+use super::pieces::{codes, Codes};
+use super::two_phase::{all_moves, move_of, move_table, redundant};
+use super::{in_frame, SolveError};
+
+/* MARK: OPTIMAL
+  korf's algorithm: IDA* over every face turn, bounded below by three pattern databases,
+  one for all the corners and one for each half of the edges with their flips. slow, but
+  the first solution found is as short as any can be
+*/
+const N_MOVES: usize = 18;
+const N_TWIST: usize = 2187;
+const N_PERM: usize = 40320;
+// places of 6 edges out of 12 in order, times their flips
+const N_EDGE_PLACES: usize = 665_280;
+const N_EDGES: usize = N_EDGE_PLACES * 64;
+const N_CORNERS: usize = N_PERM * N_TWIST;
+pub const DATABASE_SIZE: usize = N_CORNERS + 2 * N_EDGES;
+
+const EDGE_GROUPS: [[usize; 6]; 2] = [[UR, UF, UL, UB, DR, DF], [DL, DB, FR, FL, BL, BR]];
+
+// god's number in each metric, no position needs more
+const MAX_HTM: usize = 20;
+const MAX_QTM: usize = 26;
+
+// entries or nodes between looks at the cancel flag
+const CHECK_EVERY: usize = 1 << 16;
+
+/* MARK: PROGRESS
+  shared with whoever started the search, which can cancel it from another thread
+*/
+#[derive(Debug, Default)]
+pub struct Progress {
+  cancelled: AtomicBool,
+  // database entries filled in, out of DATABASE_SIZE
+  filled: AtomicUsize,
+  // length being searched and positions looked at so far
+  depth: AtomicUsize,
+  nodes: AtomicU64,
+}
+
+impl Progress {
+  pub fn cancel(&self) { self.cancelled.store(true, Ordering::Relaxed); }
+
+  pub fn cancelled(&self) -> bool { self.cancelled.load(Ordering::Relaxed) }
+
+  // share of the databases built, 1 once the search itself is running
+  pub fn databases(&self) -> f32 { self.filled.load(Ordering::Relaxed) as f32 / DATABASE_SIZE as f32 }
+
+  pub fn depth(&self) -> usize { self.depth.load(Ordering::Relaxed) }
+
+  pub fn nodes(&self) -> u64 { self.nodes.load(Ordering::Relaxed) }
+}
+
+/* MARK: SOLVE
+  quarter turn metric counts half turns twice, any other metric is searched in HTM
+*/
+pub fn solve(cube: &Cube, metric: Metric, progress: &Progress) -> Result<Alg, SolveError> {
+  let cubie = CubieCube::from_cube(cube);
+  if !cubie.is_solvable() { return Err(SolveError::Unsolvable) }
+  let databases = databases(progress).ok_or(SolveError::Cancelled)?;
+
+  let quarter = metric == Metric::Qtm;
+  let max_length = if quarter { MAX_QTM } else { MAX_HTM };
+  let mut search = Search { databases: &databases, codes: codes(), quarter, progress, path: Vec::new(), nodes: 0 };
+  let start = State::of(&cubie);
+  for limit in search.bound(&start, usize::MAX)..=max_length {
+    progress.depth.store(limit, Ordering::Relaxed);
+    if search.dfs(&start, 0, limit).ok_or(SolveError::Cancelled)? {
+      let solution = Alg(search.path.iter().map(|m| move_of(*m)).collect());
+      return Ok(in_frame(cube, &solution));
+    }
+  }
+  Err(SolveError::NotFound(max_length))
+}
+
+/* MARK: DATABASES
+  moves to solve each pattern, four bits an entry, built breadth first on first use and
+  kept for every later search. a cancelled build is thrown away
+*/
+const UNKNOWN: u8 = 0xF;
+
+struct Nibbles(Vec<u8>);
+
+impl Nibbles {
+  fn new(size: usize) -> Nibbles { Nibbles(vec![0xFF; size.div_ceil(2)]) }
+
+  fn get(&self, index: usize) -> u8 { (self.0[index / 2] >> (index % 2 * 4)) & 0xF }
+
+  fn set(&mut self, index: usize, value: u8) {
+    let shift = index % 2 * 4;
+    let byte = &mut self.0[index / 2];
+    *byte = (*byte & !(0xF << shift)) | (value << shift);
+  }
+}
+
+struct Databases {
+  corner_move: Vec<u16>,
+  twist_move: Vec<u16>,
+  corners: Nibbles,
+  edges: [Nibbles; 2],
+}
+
+fn databases(progress: &Progress) -> Option<Arc<Databases>> {
+  static DATABASES: Mutex<Option<Arc<Databases>>> = Mutex::new(None);
+  let mut databases = DATABASES.lock().unwrap_or_else(|e| e.into_inner());
+  if let Some(built) = &*databases {
+    progress.filled.store(DATABASE_SIZE, Ordering::Relaxed);
+    return Some(built.clone())
+  }
+
+  let corner_move = move_table(N_PERM, &all_moves(), |c| c.corner_perm() as usize, |c, i| c.set_corner_perm(i as u16));
+  let twist_move = move_table(N_TWIST, &all_moves(), |c| c.twist() as usize, |c, i| c.set_twist(i as u16));
+  let corners = breadth_first(N_CORNERS, 0, progress, |index, next| {
+    let (perm, twist) = (index / N_TWIST, index % N_TWIST);
+    for (m, next) in next.iter_mut().enumerate() {
+      *next = corner_move[perm * N_MOVES + m] as usize * N_TWIST + twist_move[twist * N_MOVES + m] as usize;
+    }
+  })?;
+
+  let codes = codes();
+  let edge_database = |group: usize| {
+    let solved = edge_index(&EDGE_GROUPS[group].map(|edge| edge as u8 * 2));
+    breadth_first(N_EDGES, solved, progress, |index, next| {
+      let edges = edge_codes(index);
+      for (m, next) in next.iter_mut().enumerate() {
+        *next = edge_index(&edges.map(|code| codes.edges[m][code as usize]));
+      }
+    })
+  };
+  let edges = [edge_database(0)?, edge_database(1)?];
+
+  let built = Arc::new(Databases { corner_move, twist_move, corners, edges });
+  *databases = Some(built.clone());
+  Some(built)
+}
+
+// distances from `solved` out to every reachable index, None if cancelled along the way.
+// once most of the table is known it is quicker to look back from the unknown entries
+fn breadth_first(
+  size: usize,
+  solved: usize,
+  progress: &Progress,
+  neighbours: impl Fn(usize, &mut [usize; N_MOVES]),
+) -> Option<Nibbles> {
+  let mut table = Nibbles::new(size);
+  table.set(solved, 0);
+  progress.filled.fetch_add(1, Ordering::Relaxed);
+
+  let mut reached = 1;
+  let mut next = [0; N_MOVES];
+  for depth in 0..UNKNOWN - 1 {
+    if reached == size { break }
+    let backward = reached > size / 2;
+    let mut found = 0;
+    for index in 0..size {
+      if index.is_multiple_of(CHECK_EVERY) {
+        if progress.cancelled() { return None }
+        progress.filled.fetch_add(found, Ordering::Relaxed);
+        reached += std::mem::take(&mut found);
+      }
+
+      let value = table.get(index);
+      if backward && value == UNKNOWN {
+        neighbours(index, &mut next);
+        if !next.iter().any(|neighbour| table.get(*neighbour) == depth) { continue }
+        table.set(index, depth + 1);
+        found += 1;
+      } else if !backward && value == depth {
+        neighbours(index, &mut next);
+        for neighbour in next {
+          if table.get(neighbour) != UNKNOWN { continue }
+          table.set(neighbour, depth + 1);
+          found += 1;
+        }
+      }
+    }
+    progress.filled.fetch_add(found, Ordering::Relaxed);
+    reached += found;
+  }
+  Some(table)
+}
+
+// edge piece codes (position * 2 + flip) of a group in its order, numbered by the positions
+// as a partial permutation, then the flips
+fn edge_index(codes: &[u8; 6]) -> usize {
+  let (mut places, mut flips, mut used) = (0, 0, 0u16);
+  for (i, code) in codes.iter().enumerate() {
+    let position = code / 2;
+    let rank = position as u32 - (used & ((1 << position) - 1)).count_ones();
+    places = places * (12 - i) + rank as usize;
+    flips = flips * 2 + (code % 2) as usize;
+    used |= 1 << position;
+  }
+  places * 64 + flips
+}
+
+fn edge_codes(index: usize) -> [u8; 6] {
+  let (mut places, mut flips) = (index / 64, index % 64);
+  let mut ranks = [0; 6];
+  let mut flip = [0; 6];
+  for i in (0..6).rev() {
+    ranks[i] = places % (12 - i);
+    places /= 12 - i;
+    flip[i] = (flips % 2) as u8;
+    flips /= 2;
+  }
+
+  let mut used = 0u16;
+  std::array::from_fn(|i| {
+    let position = (0..12u8).filter(|p| used & (1 << p) == 0).nth(ranks[i]).unwrap_or(0);
+    used |= 1 << position;
+    position * 2 + flip[i]
+  })
+}
+
+/* MARK: SEARCH
+*/
+#[derive(Clone, Copy)]
+struct State {
+  perm: usize,
+  twist: usize,
+  edges: [[u8; 6]; 2],
+}
+
+impl State {
+  fn of(cube: &CubieCube) -> State {
+    let edge = |edge: usize| {
+      let at = cube.ep.iter().position(|e| *e as usize == edge).unwrap_or(0);
+      (at * 2) as u8 + cube.eo[at]
+    };
+    State {
+      perm: cube.corner_perm() as usize,
+      twist: cube.twist() as usize,
+      edges: EDGE_GROUPS.map(|group| group.map(edge)),
+    }
+  }
+}
+
+struct Search<'a> {
+  databases: &'a Databases,
+  codes: &'static Codes,
+  quarter: bool,
+  progress: &'a Progress,
+  path: Vec<usize>,
+  nodes: u64,
+}
+
+impl Search<'_> {
+  // no more than this many moves from solved, and 0 only when solved. the edge databases
+  // are only looked at while the bound is still within `budget`
+  fn bound(&self, state: &State, budget: usize) -> usize {
+    let db = self.databases;
+    let mut bound = db.corners.get(state.perm * N_TWIST + state.twist) as usize;
+    for group in 0..2 {
+      if bound > budget { break }
+      bound = bound.max(db.edges[group].get(edge_index(&state.edges[group])) as usize);
+    }
+    bound
+  }
+
+  fn turn(&self, state: &State, m: usize) -> State {
+    let db = self.databases;
+    State {
+      perm: db.corner_move[state.perm * N_MOVES + m] as usize,
+      twist: db.twist_move[state.twist * N_MOVES + m] as usize,
+      edges: state.edges.map(|group| group.map(|code| self.codes.edges[m][code as usize])),
+    }
+  }
+
+  fn cost(&self, m: usize) -> usize { if self.quarter && m % 3 == 1 { 2 } else { 1 } }
+
+  // whether a solution within `limit` follows the path so far, None once cancelled
+  fn dfs(&mut self, state: &State, cost: usize, limit: usize) -> Option<bool> {
+    let bound = self.bound(state, limit.saturating_sub(cost));
+    if cost + bound > limit { return Some(false) }
+    if bound == 0 { return Some(true) }
+
+    self.nodes += 1;
+    if self.nodes.is_multiple_of(CHECK_EVERY as u64) {
+      self.progress.nodes.store(self.nodes, Ordering::Relaxed);
+      if self.progress.cancelled() { return None }
+    }
+
+    for m in 0..N_MOVES {
+      if redundant(self.path.last().copied(), m) { continue }
+      self.path.push(m);
+      if self.dfs(&self.turn(state, m), cost + self.cost(m), limit)? { return Some(true) }
+      self.path.pop();
+    }
+    Some(false)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // builds every database first, which takes a while outside of a release build
+  #[test]
+  fn finds_the_shortest_solution() {
+    let scramble: Alg = "R U F' D2 L".parse().unwrap();
+    let cube = Cube::from_alg(&scramble);
+    let progress = Progress::default();
+    let solution = solve(&cube, Metric::Htm, &progress).unwrap();
+    assert_eq!(solution.len(), scramble.len());
+    let mut solved = cube;
+    solved.apply_alg(&solution);
+    assert!(solved.is_solved());
+    assert_eq!(progress.databases(), 1.0);
+
+    // a half turn counts twice in QTM
+    let cube = Cube::from_alg(&"R2 U".parse().unwrap());
+    assert_eq!(solve(&cube, Metric::Qtm, &progress).unwrap().count(Metric::Qtm), 3);
+  }
+}
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::cube::{tables, Cube};
+use crate::cubie::{CubieCube, DF, DFR, DB, DL, DR, FR};
+use crate::face::Face;
+use crate::moves::{Alg, Move};
+
+use super::in_frame;
+
+/* MARK: PIECE GOALS
+  optimal solutions that only have to bring some pieces home, the rest of the cube may end
+  anywhere. IDA* over a chosen set of faces, pruned by one table per group of goal pieces.
+  a piece is coded as position * orientations + orientation, 24 codes for corners and edges alike
+*/
+pub(crate) const CODES: usize = 24;
+const N_MOVES: usize = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Group {
+  pub corners: Vec<u8>,
+  pub edges: Vec<u8>,
+}
+
+impl Group {
+  // the four D layer edges
+  pub fn cross() -> Group { Group { corners: Vec::new(), edges: vec![DR as u8, DF as u8, DL as u8, DB as u8] } }
+
+  // the four edges around a centre, cross() is the one for D
+  pub fn cross_of(face: Face) -> Group {
+    let edges = tables().edges.iter().enumerate()
+      .filter(|(_, stickers)| stickers.iter().any(|sticker| *sticker as usize / 9 == face.index()))
+      .map(|(edge, _)| edge as u8)
+      .collect();
+    Group { corners: Vec::new(), edges }
+  }
+
+  // first layer corner and middle layer edge of a slot, 0 FR, 1 FL, 2 BL, 3 BR
+  pub fn pair(slot: usize) -> Group { Group { corners: vec![(DFR + slot) as u8], edges: vec![(FR + slot) as u8] } }
+
+  // a pair with the two cross edges beside it, a tighter bound than the pair alone
+  pub fn slot(slot: usize) -> Group {
+    let mut group = Group::pair(slot);
+    group.edges.extend([(DR + slot) as u8, (DR + (slot + 1) % 4) as u8]);
+    group
+  }
+
+  fn len(&self) -> usize { self.corners.len() + self.edges.len() }
+
+  pub(crate) fn solved(&self) -> usize {
+    encode(self.corners.iter().map(|c| *c as usize * 3).chain(self.edges.iter().map(|e| *e as usize * 2)))
+  }
+
+  pub(crate) fn index(&self, cube: &CubieCube) -> usize {
+    let corner = |piece: &u8| {
+      let at = cube.cp.iter().position(|p| p == piece).unwrap_or(0);
+      at * 3 + cube.co[at] as usize
+    };
+    let edge = |piece: &u8| {
+      let at = cube.ep.iter().position(|p| p == piece).unwrap_or(0);
+      at * 2 + cube.eo[at] as usize
+    };
+    encode(self.corners.iter().map(corner).chain(self.edges.iter().map(edge)))
+  }
+
+  fn apply(&self, index: usize, mv: usize) -> usize {
+    let codes = codes();
+    self.follow(index, &codes.corners[mv], &codes.edges[mv])
+  }
+
+  // the index once every piece code has gone where the tables send it
+  pub(crate) fn follow(&self, mut index: usize, corners: &[u8; CODES], edges: &[u8; CODES]) -> usize {
+    let mut result = 0;
+    let mut scale = 1;
+    for i in 0..self.len() {
+      let code = index % CODES;
+      index /= CODES;
+      let table = if i < self.corners.len() { corners } else { edges };
+      result += table[code] as usize * scale;
+      scale *= CODES;
+    }
+    result
+  }
+}
+
+// cross and every pair but the unsolved ones
+pub fn f2l_goal(unsolved: &[usize]) -> Vec<Group> {
+  std::iter::once(Group::cross()).chain((0..4).filter(|slot| !unsolved.contains(slot)).map(Group::slot)).collect()
+}
+
+pub fn is_solved(cube: &CubieCube, goal: &[Group]) -> bool {
+  goal.iter().all(|group| group.index(cube) == group.solved())
+}
+
+/* MARK: CROSS
+  crosses are found on the cube with its centres home and named for the faces it is held by
+*/
+// every cross can be solved in this many turns
+const CROSS_MAX_LENGTH: usize = 8;
+
+pub fn crosses(cube: &Cube, color: Face, limit: usize) -> Vec<Alg> {
+  let goal = [Group::cross_of(color)];
+  let solutions = solve_pieces(&CubieCube::from_cube(cube), &goal, &Face::ALL, CROSS_MAX_LENGTH, limit);
+  solutions.iter().map(|alg| in_frame(cube, alg)).collect()
+}
+
+pub fn cross_solved(cube: &Cube, color: Face) -> bool {
+  is_solved(&CubieCube::from_cube(cube), &[Group::cross_of(color)])
+}
+
+fn encode(codes: impl Iterator<Item = usize>) -> usize {
+  codes.fold((0, 1), |(index, scale), code| (index + code * scale, scale * CODES)).0
+}
+
+fn move_of(index: usize) -> Move { Move::face(Face::from_index(index / 3), (index % 3) as u8 + 1) }
+
+/* MARK: TABLES
+*/
+pub(super) struct Codes {
+  pub(super) corners: [[u8; CODES]; N_MOVES],
+  pub(super) edges: [[u8; CODES]; N_MOVES],
+}
+
+// where each piece code goes under each face turn
+pub(super) fn codes() -> &'static Codes {
+  static CODES_TABLE: OnceLock<Codes> = OnceLock::new();
+  CODES_TABLE.get_or_init(|| {
+    let mut codes = Codes { corners: [[0; CODES]; N_MOVES], edges: [[0; CODES]; N_MOVES] };
+    for mv in 0..N_MOVES {
+      let quarter = CubieCube::face_turn(Face::from_index(mv / 3));
+      let mut turn = *quarter;
+      for _ in 0..mv % 3 { turn = turn.multiply(quarter); }
+      (codes.corners[mv], codes.edges[mv]) = code_moves(&turn);
+    }
+    codes
+  })
+}
+
+// where each corner and edge code goes under a turn, or under a whole sequence of them
+pub(crate) fn code_moves(turn: &CubieCube) -> ([u8; CODES], [u8; CODES]) {
+  let (mut corners, mut edges) = ([0; CODES], [0; CODES]);
+  for to in 0..8 {
+    let from = turn.cp[to] as usize;
+    for ori in 0..3 {
+      corners[from * 3 + ori] = (to * 3 + (ori + turn.co[to] as usize) % 3) as u8;
+    }
+  }
+  for to in 0..12 {
+    let from = turn.ep[to] as usize;
+    for ori in 0..2 {
+      edges[from * 2 + ori] = (to * 2 + (ori + turn.eo[to] as usize) % 2) as u8;
+    }
+  }
+  (corners, edges)
+}
+
+// moves to bring the group home for every arrangement of it, u8::MAX where the faces cannot
+pub(crate) fn prune_table(group: &Group, faces: &[Face]) -> Arc<Vec<u8>> {
+  type Key = (Group, Vec<Face>);
+  static TABLES: OnceLock<Mutex<HashMap<Key, Arc<Vec<u8>>>>> = OnceLock::new();
+  let key = (group.clone(), faces.to_vec());
+  let tables = TABLES.get_or_init(|| Mutex::new(HashMap::new()));
+  if let Some(table) = tables.lock().unwrap().get(&key) { return table.clone() }
+
+  let moves = face_moves(faces);
+  let mut table = vec![u8::MAX; CODES.pow(group.len() as u32)];
+  let solved = group.solved();
+  table[solved] = 0;
+  let mut queue = VecDeque::from([solved]);
+  while let Some(index) = queue.pop_front() {
+    let depth = table[index];
+    for mv in &moves {
+      let next = group.apply(index, *mv);
+      if table[next] == u8::MAX {
+        table[next] = depth + 1;
+        queue.push_back(next);
+      }
+    }
+  }
+
+  let table = Arc::new(table);
+  tables.lock().unwrap().insert(key, table.clone());
+  table
+}
+
+fn face_moves(faces: &[Face]) -> Vec<usize> {
+  faces.iter().flat_map(|face| (0..3).map(move |amount| face.index() * 3 + amount)).collect()
+}
+
+/* MARK: SEARCH
+  every solution of the shortest length, up to `limit` of them, none when the goal
+  cannot be reached within `max_length` turns of the faces
+*/
+pub fn solve_pieces(cube: &CubieCube, goal: &[Group], faces: &[Face], max_length: usize, limit: usize) -> Vec<Alg> {
+  search_pieces(cube, goal, faces, max_length, limit, true)
+}
+
+// every solution up to `max_length` turns rather than only the shortest, shortest first
+pub fn all_solutions(cube: &CubieCube, goal: &[Group], faces: &[Face], max_length: usize, limit: usize) -> Vec<Alg> {
+  search_pieces(cube, goal, faces, max_length, limit, false)
+}
+
+fn search_pieces(cube: &CubieCube, goal: &[Group], faces: &[Face], max_length: usize, limit: usize, shortest: bool) -> Vec<Alg> {
+  let search = Search {
+    goal,
+    tables: goal.iter().map(|group| prune_table(group, faces)).collect(),
+    moves: face_moves(faces),
+    limit,
+  };
+  let start: Vec<usize> = goal.iter().map(|group| group.index(cube)).collect();
+
+  let mut solutions = Vec::new();
+  let mut path = Vec::new();
+  let Some(bound) = search.distance(&start) else { return solutions };
+  for depth in bound..=max_length {
+    search.dfs(&start, depth, &mut path, &mut solutions);
+    if shortest && !solutions.is_empty() { break }
+  }
+  solutions
+}
+
+struct Search<'a> {
+  goal: &'a [Group],
+  tables: Vec<Arc<Vec<u8>>>,
+  moves: Vec<usize>,
+  limit: usize,
+}
+
+impl Search<'_> {
+  // lower bound on the moves left, None if a group cannot be solved at all
+  fn distance(&self, state: &[usize]) -> Option<usize> {
+    let mut bound = 0;
+    for (table, index) in self.tables.iter().zip(state) {
+      let depth = table[*index];
+      if depth == u8::MAX { return None }
+      bound = bound.max(depth as usize);
+    }
+    Some(bound)
+  }
+
+  fn dfs(&self, state: &[usize], depth: usize, path: &mut Vec<usize>, solutions: &mut Vec<Alg>) {
+    if solutions.len() >= self.limit { return }
+    let Some(bound) = self.distance(state) else { return };
+    if bound > depth { return }
+    if depth == 0 {
+      solutions.push(Alg(path.iter().map(|mv| move_of(*mv)).collect()));
+      return
+    }
+
+    for mv in &self.moves {
+      let face = Face::from_index(mv / 3);
+      if let Some(last) = path.last().map(|last| Face::from_index(last / 3)) {
+        // same face twice, or opposite faces in both orders, only one of each kept
+        if face == last || (face == last.opposite() && face.index() < last.index()) { continue }
+      }
+      let next: Vec<usize> = self.goal.iter().zip(state).map(|(group, index)| group.apply(*index, *mv)).collect();
+      path.push(*mv);
+      self.dfs(&next, depth - 1, path, solutions);
+      path.pop();
+    }
+  }
+}
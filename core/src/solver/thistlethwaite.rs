@@ -0,0 +1,274 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::OnceLock;
+
+use crate::cube::Cube;
+use crate::cubie::{permutation_index, set_permutation, CubieCube, BL, BR, DB, DF, DL, DR, FL, FR, UB, UF, UL, UR};
+use crate::face::Face;
+use crate::moves::{Alg, Move};
+
+use super::two_phase::{move_of, move_table};
+use super::{in_frame, SolveError};
+
+/* MARK: THISTLETHWAITE
+  four stages through nested groups, each solved with fewer kinds of turn than the last:
+    G0 <U, R, F, D, L, B>    any cube
+    G1 <U, R, F2, D, L, B2>  edges oriented
+    G2 <U, R2, F2, D, L2, B2> corners oriented, E slice edges in the E slice
+    G3 <U2, R2, F2, D2, L2, B2> corners in the half turn group, M slice edges in the M slice
+    G4 solved
+  every stage has an exact distance table, so its moves are found by walking downhill
+*/
+pub const STAGES: usize = 4;
+
+const STAGE_MOVES: [&[usize]; STAGES] = [
+  &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17],
+  &[0, 1, 2, 3, 4, 5, 7, 9, 10, 11, 12, 13, 14, 16],
+  &[0, 1, 2, 4, 7, 9, 10, 11, 13, 16],
+  &[1, 4, 7, 10, 13, 16],
+];
+
+const N_FLIP: usize = 2048;
+const N_TWIST: usize = 2187;
+const N_SLICE: usize = 495;
+const N_PERM: usize = 40320;
+// places of the four M slice edges among the eight U and D layer positions
+const N_M_PLACES: usize = 70;
+// corner permutations reachable by half turns
+const N_HALF_CORNERS: usize = 96;
+// order of the edges within each of the three slices
+const N_SLICE_ORDERS: usize = 24 * 24 * 24;
+
+// edge positions of the S, M and E slices
+const SLICES: [[usize; 4]; 3] = [[UR, UL, DR, DL], [UF, UB, DF, DB], [FR, FL, BL, BR]];
+const M_EDGES: [usize; 4] = SLICES[1];
+
+// turns that keep the cube in `group`, the way they are written
+pub fn generators(group: usize) -> Alg {
+  let Some(moves) = STAGE_MOVES.get(group) else { return Alg::new() };
+  Alg(Face::ALL.iter().filter_map(|face| moves.iter().find(|m| *m / 3 == face.index()).map(|m| move_of(*m))).collect())
+}
+
+// "<U, R, F2, D, L, B2>" for a group, relabelled to the faces the cube is held by
+pub fn allowed_moves(cube: &Cube, group: usize) -> String {
+  if group >= STAGES { return "solved".to_string() }
+  let moves: Vec<String> = in_frame(cube, &generators(group)).iter().map(Move::to_string).collect();
+  format!("<{}>", moves.join(", "))
+}
+
+// deepest group the cube is in, STAGES once solved
+pub fn group_of(cube: &Cube) -> usize {
+  let tables = tables();
+  let cubie = CubieCube::from_cube(cube);
+  (0..STAGES).find(|stage| tables.distance(*stage, &cubie) != 0).unwrap_or(STAGES)
+}
+
+// moves of each stage in turn, a stage already done is empty
+pub fn solve(cube: &Cube) -> Result<Vec<Alg>, SolveError> {
+  let tables = tables();
+  let mut cubie = CubieCube::from_cube(cube);
+  if !cubie.is_solvable() { return Err(SolveError::Unsolvable) }
+
+  let mut stages = Vec::new();
+  for (stage, stage_moves) in STAGE_MOVES.iter().enumerate() {
+    let mut index = tables.index(stage, &cubie);
+    let mut moves = Alg::new();
+    while tables.distances[stage][index] > 0 {
+      let distance = tables.distances[stage][index];
+      let (m, next) = stage_moves.iter().enumerate()
+        .map(|(k, m)| (*m, tables.step(stage, index, k)))
+        .find(|(_, next)| tables.distances[stage][*next] < distance)
+        .ok_or(SolveError::Unsolvable)?;
+      let mv = move_of(m);
+      cubie.apply_move(mv);
+      moves.push(mv);
+      index = next;
+    }
+    stages.push(moves);
+  }
+  // moves found on the normalised cube are relabelled for the way it is held
+  Ok(stages.iter().map(|moves| in_frame(cube, moves)).collect())
+}
+
+/* MARK: TABLES
+*/
+struct Tables {
+  flip_move: Vec<u16>,
+  twist_move: Vec<u16>,
+  slice_move: Vec<u16>,
+  corner_move: Vec<u16>,
+  m_move: Vec<u16>,
+  half_corner: Vec<u8>,
+  half_corner_move: Vec<u16>,
+  slice_order_move: Vec<u16>,
+  distances: [Vec<u8>; STAGES],
+}
+
+fn tables() -> &'static Tables {
+  static TABLES: OnceLock<Tables> = OnceLock::new();
+  TABLES.get_or_init(|| {
+    let [g0, g1, g2, g3] = STAGE_MOVES;
+    let flip_move = move_table(N_FLIP, g0, |c| c.flip() as usize, |c, i| c.set_flip(i as u16));
+    let twist_move = move_table(N_TWIST, g1, |c| c.twist() as usize, |c, i| c.set_twist(i as u16));
+    let slice_move = move_table(N_SLICE, g1, |c| c.slice() as usize, |c, i| c.set_slice(i as u16));
+    let corner_move = move_table(N_PERM, g2, |c| c.corner_perm() as usize, |c, i| c.set_corner_perm(i as u16));
+    let m_move = move_table(N_M_PLACES, g2, m_places, set_m_places);
+
+    // corner permutations of the half turn group, numbered in the order they are reached
+    let half_corners = half_turn_corners();
+    let mut half_corner = vec![u8::MAX; N_PERM];
+    for (i, perm) in half_corners.iter().enumerate() { half_corner[*perm] = i as u8; }
+    let half_corner_move = move_table(N_HALF_CORNERS, g3, |c| half_corner[c.corner_perm() as usize] as usize, |c, i| {
+      c.set_corner_perm(half_corners[i] as u16)
+    });
+    let slice_order_move = move_table(N_SLICE_ORDERS, g3, slice_orders, set_slice_orders);
+
+    let mut tables = Tables {
+      flip_move, twist_move, slice_move, corner_move, m_move, half_corner, half_corner_move, slice_order_move,
+      distances: Default::default(),
+    };
+    let home = m_places(&CubieCube::SOLVED);
+    let goals = [vec![0], vec![0], half_corners.iter().map(|perm| perm * N_M_PLACES + home).collect(), vec![0]];
+    let sizes = [N_FLIP, N_TWIST * N_SLICE, N_PERM * N_M_PLACES, N_HALF_CORNERS * N_SLICE_ORDERS];
+    for stage in 0..STAGES {
+      let table = distances(sizes[stage], &goals[stage], STAGE_MOVES[stage].len(), |index, k| tables.step(stage, index, k));
+      tables.distances[stage] = table;
+    }
+    tables
+  })
+}
+
+impl Tables {
+  // a stage's coordinate of a cube already through the stages before it
+  fn index(&self, stage: usize, cube: &CubieCube) -> usize {
+    match stage {
+      0 => cube.flip() as usize,
+      1 => cube.twist() as usize * N_SLICE + cube.slice() as usize,
+      2 => cube.corner_perm() as usize * N_M_PLACES + m_places(cube),
+      _ => self.half_corner[cube.corner_perm() as usize] as usize * N_SLICE_ORDERS + slice_orders(cube),
+    }
+  }
+
+  fn distance(&self, stage: usize, cube: &CubieCube) -> u8 { self.distances[stage][self.index(stage, cube)] }
+
+  // coordinate after the k-th move of the stage
+  fn step(&self, stage: usize, index: usize, k: usize) -> usize {
+    let n = STAGE_MOVES[stage].len();
+    match stage {
+      0 => self.flip_move[index * n + k] as usize,
+      1 => {
+        let (twist, slice) = (index / N_SLICE, index % N_SLICE);
+        self.twist_move[twist * n + k] as usize * N_SLICE + self.slice_move[slice * n + k] as usize
+      }
+      2 => {
+        let (corner, m) = (index / N_M_PLACES, index % N_M_PLACES);
+        self.corner_move[corner * n + k] as usize * N_M_PLACES + self.m_move[m * n + k] as usize
+      }
+      _ => {
+        let (corner, order) = (index / N_SLICE_ORDERS, index % N_SLICE_ORDERS);
+        self.half_corner_move[corner * n + k] as usize * N_SLICE_ORDERS + self.slice_order_move[order * n + k] as usize
+      }
+    }
+  }
+}
+
+// breadth first distance to the nearest goal
+fn distances(size: usize, goals: &[usize], n_moves: usize, step: impl Fn(usize, usize) -> usize) -> Vec<u8> {
+  let mut table = vec![u8::MAX; size];
+  for goal in goals { table[*goal] = 0; }
+  let mut queue: VecDeque<usize> = goals.iter().copied().collect();
+  while let Some(index) = queue.pop_front() {
+    for k in 0..n_moves {
+      let next = step(index, k);
+      if table[next] == u8::MAX {
+        table[next] = table[index] + 1;
+        queue.push_back(next);
+      }
+    }
+  }
+  table
+}
+
+fn half_turn_corners() -> Vec<usize> {
+  let mut seen = HashSet::from([0]);
+  let mut order = vec![0];
+  let mut queue = VecDeque::from([CubieCube::SOLVED]);
+  while let Some(cube) = queue.pop_front() {
+    for m in STAGE_MOVES[3] {
+      let mut next = cube;
+      next.apply_move(move_of(*m));
+      if seen.insert(next.corner_perm() as usize) {
+        order.push(next.corner_perm() as usize);
+        queue.push_back(next);
+      }
+    }
+  }
+  order
+}
+
+/* MARK: COORDINATES
+*/
+// which of the U and D layer positions hold M slice edges, 0..70
+fn m_places(cube: &CubieCube) -> usize {
+  let mask = (0..8).filter(|at| M_EDGES.contains(&(cube.ep[*at] as usize))).fold(0, |mask, at| mask | 1 << at);
+  (0..256usize).filter(|m| m.count_ones() == 4).position(|m| m == mask).unwrap_or(0)
+}
+
+fn set_m_places(cube: &mut CubieCube, index: usize) {
+  let mask = (0..256usize).filter(|m| m.count_ones() == 4).nth(index).unwrap_or(0);
+  let mut m = M_EDGES.iter().copied();
+  let mut other = (0..8).filter(|e| !M_EDGES.contains(e));
+  for at in 0..8 {
+    let edge = if mask & 1 << at != 0 { m.next() } else { other.next() };
+    cube.ep[at] = edge.unwrap_or(at) as u8;
+  }
+}
+
+// order of the edges within each slice, S then M then E
+fn slice_orders(cube: &CubieCube) -> usize {
+  let order = |positions: [usize; 4]| {
+    let ranks = positions.map(|at| positions.iter().position(|p| *p == cube.ep[at] as usize).unwrap_or(0) as u8);
+    permutation_index(&ranks)
+  };
+  SLICES.iter().fold(0, |index, positions| index * 24 + order(*positions))
+}
+
+fn set_slice_orders(cube: &mut CubieCube, mut index: usize) {
+  for positions in SLICES.iter().rev() {
+    let mut ranks = [0u8; 4];
+    set_permutation(&mut ranks, index % 24);
+    index /= 24;
+    for (at, rank) in positions.iter().zip(ranks) { cube.ep[*at] = positions[rank as usize] as u8; }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+  use crate::scramble;
+
+  #[test]
+  fn each_stage_reaches_the_next_group() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    for _ in 0..10 {
+      let mut cube = scramble::random_cube(&mut rng).to_cube();
+      let stages = solve(&cube).unwrap();
+      assert_eq!(stages.len(), STAGES);
+      for (stage, moves) in stages.iter().enumerate() {
+        cube.apply_alg(moves);
+        assert!(group_of(&cube) > stage);
+      }
+      assert!(cube.is_solved());
+    }
+  }
+
+  #[test]
+  fn stages_already_done_are_empty() {
+    let cube = Cube::from_alg(&"U R2 F2 L2".parse().unwrap());
+    assert_eq!(group_of(&cube), 2);
+    let stages = solve(&cube).unwrap();
+    assert!(stages[0].is_empty() && stages[1].is_empty());
+  }
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use crate::cubie::CubieCube;
+use crate::face::Face;
+use crate::moves::{Alg, Move};
+
+/* MARK: TWO PHASE
+  kociemba's algorithm: phase 1 reaches <U, D, R2, L2, F2, B2> (no twist, no flip, slice edges
+  in the slice), phase 2 solves within that group. moves are indexed face * 3 + amount - 1
+*/
+const N_MOVES: usize = 18;
+const N_TWIST: usize = 2187;
+const N_FLIP: usize = 2048;
+const N_SLICE: usize = 495;
+const N_PERM: usize = 40320;
+const N_SLICE_PERM: usize = 24;
+
+// U1 U2 U3 R2 F2 D1 D2 D3 L2 B2
+const PHASE2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+struct Tables {
+  twist_move: Vec<u16>,
+  flip_move: Vec<u16>,
+  slice_move: Vec<u16>,
+  corner_move: Vec<u16>,
+  // phase 2 tables are indexed by position in PHASE2_MOVES
+  ud_edge_move: Vec<u16>,
+  slice_perm_move: Vec<u8>,
+
+  twist_slice_prune: Vec<u8>,
+  flip_slice_prune: Vec<u8>,
+  corner_slice_prune: Vec<u8>,
+  edge_slice_prune: Vec<u8>,
+}
+
+fn tables() -> &'static Tables {
+  static TABLES: OnceLock<Tables> = OnceLock::new();
+  TABLES.get_or_init(|| {
+    let twist_move = move_table(N_TWIST, &all_moves(), |c| c.twist() as usize, |c, i| c.set_twist(i as u16));
+    let flip_move = move_table(N_FLIP, &all_moves(), |c| c.flip() as usize, |c, i| c.set_flip(i as u16));
+    let slice_move = move_table(N_SLICE, &all_moves(), |c| c.slice() as usize, |c, i| c.set_slice(i as u16));
+    let corner_move = move_table(N_PERM, &all_moves(), |c| c.corner_perm() as usize, |c, i| c.set_corner_perm(i as u16));
+    let ud_edge_move = move_table(N_PERM, &PHASE2_MOVES, |c| c.ud_edge_perm() as usize, |c, i| c.set_ud_edge_perm(i as u16));
+    let slice_perm_move: Vec<u8> = move_table(N_SLICE_PERM, &PHASE2_MOVES, |c| c.slice_perm() as usize, |c, i| c.set_slice_perm(i as u8))
+      .into_iter().map(|v| v as u8).collect();
+
+    let twist_slice_prune = prune_table(N_TWIST, N_SLICE, N_MOVES, |a, b, m| (twist_move[a * N_MOVES + m] as usize, slice_move[b * N_MOVES + m] as usize));
+    let flip_slice_prune = prune_table(N_FLIP, N_SLICE, N_MOVES, |a, b, m| (flip_move[a * N_MOVES + m] as usize, slice_move[b * N_MOVES + m] as usize));
+    let corner_slice_prune = prune_table(N_PERM, N_SLICE_PERM, PHASE2_MOVES.len(), |a, b, m| {
+      (corner_move[a * N_MOVES + PHASE2_MOVES[m]] as usize, slice_perm_move[b * PHASE2_MOVES.len() + m] as usize)
+    });
+    let edge_slice_prune = prune_table(N_PERM, N_SLICE_PERM, PHASE2_MOVES.len(), |a, b, m| {
+      (ud_edge_move[a * PHASE2_MOVES.len() + m] as usize, slice_perm_move[b * PHASE2_MOVES.len() + m] as usize)
+    });
+
+    Tables {
+      twist_move, flip_move, slice_move, corner_move, ud_edge_move, slice_perm_move,
+      twist_slice_prune, flip_slice_prune, corner_slice_prune, edge_slice_prune,
+    }
+  })
+}
+
+pub(super) fn all_moves() -> [usize; N_MOVES] {
+  let mut moves = [0; N_MOVES];
+  for (i, m) in moves.iter_mut().enumerate() { *m = i; }
+  moves
+}
+
+pub(crate) fn move_of(index: usize) -> Move { Move::face(Face::from_index(index / 3), (index % 3) as u8 + 1) }
+
+// table[coord * moves.len() + k] = coordinate after moves[k]
+pub(super) fn move_table(
+  size: usize,
+  moves: &[usize],
+  get: impl Fn(&CubieCube) -> usize,
+  set: impl Fn(&mut CubieCube, usize),
+) -> Vec<u16> {
+  let mut table = vec![0u16; size * moves.len()];
+  let mut cube = CubieCube::SOLVED;
+  for coord in 0..size {
+    set(&mut cube, coord);
+    for (k, m) in moves.iter().enumerate() {
+      let turn = CubieCube::face_turn(Face::from_index(m / 3));
+      let mut turned = cube;
+      for _ in 0..(m % 3) + 1 { turned = turned.multiply(turn); }
+      table[coord * moves.len() + k] = get(&turned) as u16;
+    }
+  }
+  table
+}
+
+// breadth first distance to solved over a pair of coordinates
+fn prune_table(
+  size_a: usize,
+  size_b: usize,
+  n_moves: usize,
+  step: impl Fn(usize, usize, usize) -> (usize, usize),
+) -> Vec<u8> {
+  let mut table = vec![u8::MAX; size_a * size_b];
+  let mut queue = VecDeque::from([0usize]);
+  table[0] = 0;
+  while let Some(index) = queue.pop_front() {
+    let (a, b) = (index / size_b, index % size_b);
+    for m in 0..n_moves {
+      let (na, nb) = step(a, b, m);
+      let next = na * size_b + nb;
+      if table[next] == u8::MAX {
+        table[next] = table[index] + 1;
+        queue.push_back(next);
+      }
+    }
+  }
+  table
+}
+
+// skip a second turn of the same face, and always turn opposite faces in URF before DLB order
+pub(super) fn redundant(previous: Option<usize>, m: usize) -> bool {
+  match previous {
+    Some(p) => m / 3 == p / 3 || m / 3 + 3 == p / 3,
+    None => false,
+  }
+}
+
+/* MARK: SEARCH
+*/
+// phase 2 attempts spent looking for something shorter once a first solution is known
+const IMPROVE_PROBES: usize = 2000;
+
+struct Search<'a> {
+  tables: &'a Tables,
+  start: CubieCube,
+  max_length: usize,
+  phase1: Vec<usize>,
+  phase2: Vec<usize>,
+  probes: usize,
+  max_probes: Option<usize>,
+}
+
+pub fn solve(cube: &CubieCube, max_length: usize) -> Option<Alg> {
+  let tables = tables();
+  let mut search = Search {
+    tables, start: *cube, max_length, phase1: Vec::new(), phase2: Vec::new(), probes: 0, max_probes: None,
+  };
+  let mut best = search.run()?;
+
+  search.max_probes = Some(IMPROVE_PROBES);
+  while !best.is_empty() {
+    search.max_length = best.len() - 1;
+    match search.run() {
+      Some(shorter) => best = shorter,
+      None => break,
+    }
+  }
+  Some(best)
+}
+
+impl Search<'_> {
+  fn run(&mut self) -> Option<Alg> {
+    let cube = self.start;
+    let (twist, flip, slice) = (cube.twist() as usize, cube.flip() as usize, cube.slice() as usize);
+    for depth in 0..=self.max_length {
+      self.phase1.clear();
+      if self.phase1_search(twist, flip, slice, depth) {
+        let moves = self.phase1.iter().chain(self.phase2.iter()).map(|m| move_of(*m)).collect();
+        return Some(Alg(moves));
+      }
+      if self.exhausted() { return None }
+    }
+    None
+  }
+
+  fn exhausted(&self) -> bool { self.max_probes.is_some_and(|max| self.probes >= max) }
+
+  fn phase1_search(&mut self, twist: usize, flip: usize, slice: usize, depth: usize) -> bool {
+    let t = self.tables;
+    if self.exhausted() { return false }
+    if depth == 0 {
+      if twist != 0 || flip != 0 || slice != 0 { return false }
+      // ending on a phase 2 move would have been found at a shorter depth
+      if let Some(last) = self.phase1.last() {
+        if PHASE2_MOVES.contains(last) { return false }
+      }
+      return self.start_phase2();
+    }
+
+    let estimate = t.twist_slice_prune[twist * N_SLICE + slice].max(t.flip_slice_prune[flip * N_SLICE + slice]);
+    if estimate as usize > depth { return false }
+
+    for m in 0..N_MOVES {
+      if redundant(self.phase1.last().copied(), m) { continue }
+      self.phase1.push(m);
+      let found = self.phase1_search(
+        t.twist_move[twist * N_MOVES + m] as usize,
+        t.flip_move[flip * N_MOVES + m] as usize,
+        t.slice_move[slice * N_MOVES + m] as usize,
+        depth - 1,
+      );
+      if found { return true }
+      self.phase1.pop();
+    }
+    false
+  }
+
+  fn start_phase2(&mut self) -> bool {
+    if self.phase1.len() > self.max_length { return false }
+    self.probes += 1;
+    let mut cube = self.start;
+    for m in &self.phase1 {
+      let turn = CubieCube::face_turn(Face::from_index(m / 3));
+      for _ in 0..(m % 3) + 1 { cube = cube.multiply(turn); }
+    }
+    let (corner, edge, slice) = (cube.corner_perm() as usize, cube.ud_edge_perm() as usize, cube.slice_perm() as usize);
+
+    for depth in 0..=self.max_length - self.phase1.len() {
+      self.phase2.clear();
+      if self.phase2_search(corner, edge, slice, depth) { return true }
+    }
+    false
+  }
+
+  fn phase2_search(&mut self, corner: usize, edge: usize, slice: usize, depth: usize) -> bool {
+    let t = self.tables;
+    if depth == 0 { return corner == 0 && edge == 0 && slice == 0 }
+
+    let estimate = t.corner_slice_prune[corner * N_SLICE_PERM + slice].max(t.edge_slice_prune[edge * N_SLICE_PERM + slice]);
+    if estimate as usize > depth { return false }
+
+    for (k, m) in PHASE2_MOVES.iter().enumerate() {
+      let previous = self.phase2.last().or(self.phase1.last()).copied();
+      if redundant(previous, *m) { continue }
+      self.phase2.push(*m);
+      let found = self.phase2_search(
+        t.corner_move[corner * N_MOVES + m] as usize,
+        t.ud_edge_move[edge * PHASE2_MOVES.len() + k] as usize,
+        t.slice_perm_move[slice * PHASE2_MOVES.len() + k] as usize,
+        depth - 1,
+      );
+      if found { return true }
+      self.phase2.pop();
+    }
+    false
+  }
+}
@@ -0,0 +1,453 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cube::{tables, Cube, FACELETS};
+use crate::face::Face;
+use crate::metrics::Metrics;
+use crate::moves::{Alg, Move};
+use crate::record::SolveRecord;
+
+/* MARK: METHOD
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Method {
+  #[default]
+  Cfop,
+  Roux,
+  Zz,
+  // layer by layer, the way most people learn
+  Beginner,
+}
+
+impl Method {
+  pub const ALL: [Method; 4] = [Method::Cfop, Method::Roux, Method::Zz, Method::Beginner];
+
+  pub fn name(self) -> &'static str {
+    match self { Method::Cfop => "CFOP", Method::Roux => "Roux", Method::Zz => "ZZ", Method::Beginner => "Beginner" }
+  }
+
+  pub fn steps(self) -> &'static [Step] {
+    match self {
+      Method::Cfop => &[Step::Cross, Step::Pair(1), Step::Pair(2), Step::Pair(3), Step::Pair(4), Step::Oll, Step::Pll],
+      Method::Roux => &[Step::FirstBlock, Step::SecondBlock, Step::Cmll, Step::Lse],
+      Method::Zz => &[Step::Eoline, Step::F2l, Step::Oll, Step::Pll],
+      Method::Beginner => &[
+        Step::Cross, Step::FirstLayer, Step::F2l, Step::LastCross, Step::Oll, Step::LastCorners, Step::Pll,
+      ],
+    }
+  }
+
+  pub fn next(self) -> Method { Method::ALL[(self as usize + 1) % Method::ALL.len()] }
+}
+
+impl fmt::Display for Method {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.name()) }
+}
+
+impl FromStr for Method {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Method, String> {
+    Method::ALL.iter().find(|m| m.name().eq_ignore_ascii_case(s)).copied().ok_or(format!("unknown method '{}'", s))
+  }
+}
+
+/* MARK: STEP
+  stages of a solving method, detected from the cube state alone
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+  Cross,
+  // number of F2L pairs solved, 1..=4
+  Pair(u8),
+  Oll,
+  Pll,
+  FirstBlock,
+  SecondBlock,
+  Cmll,
+  Lse,
+  // every edge oriented for <R, L, U, D> with the front and back bottom edges solved
+  Eoline,
+  F2l,
+  // last layer in one step, when OLL and PLL end on the same move
+  Zbll,
+  // beginner milestones: bottom corners on the cross, top edges facing up,
+  // then the oriented top corners in place
+  FirstLayer,
+  LastCross,
+  LastCorners,
+}
+
+// colours the steps are measured against: the first layer, and the first block (Roux)
+// or the front face that edge orientation is measured on (ZZ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame {
+  pub bottom: Face,
+  pub side: Face,
+}
+
+impl Frame {
+  // the 24 ways to pick a bottom colour and a side colour next to it
+  pub fn all() -> impl Iterator<Item = Frame> {
+    Face::ALL.into_iter().flat_map(|bottom| {
+      Face::ALL.into_iter().filter(move |side| side.axis() != bottom.axis()).map(move |side| Frame { bottom, side })
+    })
+  }
+}
+
+impl Step {
+  // short name used when saving splits
+  pub fn id(self) -> String {
+    match self {
+      Step::Pair(n) => format!("pair{}", n),
+      step => STEP_IDS.iter().find(|(s, _)| *s == step).map_or("", |(_, id)| id).to_string(),
+    }
+  }
+
+  // whether the step is done in the given frame, every earlier step of its method included
+  pub fn is_done(self, cube: &Cube, frame: Frame) -> bool {
+    let colors = cube.colors();
+    let centers = cube.center_positions();
+    let (bottom, side) = (centers[frame.bottom.index()], centers[frame.side.index()]);
+    let top = bottom.opposite();
+    let f2l = |colors: &[Face; FACELETS]| cross_solved(colors, bottom) && pairs_solved(colors, bottom) == 4;
+    match self {
+      Step::Cross => cross_solved(&colors, bottom),
+      Step::Pair(n) => cross_solved(&colors, bottom) && pairs_solved(&colors, bottom) >= n as usize,
+      Step::FirstLayer => cross_solved(&colors, bottom) && corners_solved(&colors, bottom),
+      Step::F2l => f2l(&colors),
+      Step::LastCross => f2l(&colors) && [1, 3, 5, 7].iter().all(|p| colors[top.index() * 9 + p] == top),
+      Step::Oll => f2l(&colors) && face_oriented(&colors, top),
+      Step::LastCorners => f2l(&colors) && face_oriented(&colors, top) && corners_solved_after_auf(*cube, top),
+      Step::Pll | Step::Lse | Step::Zbll => cube.is_solved_any_orientation(),
+      Step::FirstBlock => slice_offsets(*cube, frame).any(|(cube, bottom)| block_solved(&cube.colors(), bottom, side)),
+      Step::SecondBlock => slice_offsets(*cube, frame).any(|(cube, bottom)| blocks_solved(&cube.colors(), bottom, side)),
+      // corners may still need an AUF, that is left for the last six edges
+      Step::Cmll => slice_offsets(*cube, frame).any(|(cube, bottom)| {
+        blocks_solved(&cube.colors(), bottom, side) && corners_solved_after_auf(cube, bottom.opposite())
+      }),
+      Step::Eoline => {
+        edges_oriented(&colors, bottom, side)
+          && [side, side.opposite()].iter().all(|s| edge_between(bottom, *s).is_some_and(|e| piece_solved(&colors, e)))
+      }
+    }
+  }
+
+  // what is left to do to reach the step, as a beginner would be told
+  pub fn goal(self) -> &'static str {
+    match self {
+      Step::Cross => "solve the cross on the bottom",
+      Step::Pair(_) => "pair up a corner and edge and insert them",
+      Step::FirstLayer => "insert the bottom corners under the cross",
+      Step::F2l => "insert the middle layer edges",
+      Step::LastCross => "flip the top edges into a cross",
+      Step::Oll => "twist the top corners to finish the top face",
+      Step::LastCorners => "swap the top corners into place",
+      Step::Pll => "cycle the top edges to solve the cube",
+      Step::FirstBlock => "build a 1x2x3 block on the left",
+      Step::SecondBlock => "build a 1x2x3 block on the right",
+      Step::Cmll => "solve the top corners",
+      Step::Lse => "solve the last six edges",
+      Step::Eoline => "orient every edge and place the two bottom line edges",
+      Step::Zbll => "solve the last layer",
+    }
+  }
+}
+
+const STEP_IDS: [(Step, &str); 13] = [
+  (Step::Cross, "cross"), (Step::Oll, "oll"), (Step::Pll, "pll"),
+  (Step::FirstBlock, "fb"), (Step::SecondBlock, "sb"), (Step::Cmll, "cmll"), (Step::Lse, "lse"),
+  (Step::Eoline, "eoline"), (Step::F2l, "f2l"), (Step::Zbll, "zbll"),
+  (Step::FirstLayer, "layer1"), (Step::LastCross, "llcross"), (Step::LastCorners, "llcorners"),
+];
+
+impl fmt::Display for Step {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Step::Cross => write!(f, "Cross"),
+      Step::Pair(n) => write!(f, "F2L {}", n),
+      Step::Oll => write!(f, "OLL"),
+      Step::Pll => write!(f, "PLL"),
+      Step::FirstBlock => write!(f, "FB"),
+      Step::SecondBlock => write!(f, "SB"),
+      Step::Cmll => write!(f, "CMLL"),
+      Step::Lse => write!(f, "LSE"),
+      Step::Eoline => write!(f, "EOLine"),
+      Step::F2l => write!(f, "F2L"),
+      Step::Zbll => write!(f, "ZBLL"),
+      Step::FirstLayer => write!(f, "Layer 1"),
+      Step::LastCross => write!(f, "LL cross"),
+      Step::LastCorners => write!(f, "LL corners"),
+    }
+  }
+}
+
+impl FromStr for Step {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Step, String> {
+    if let Some((step, _)) = STEP_IDS.iter().find(|(_, id)| *id == s) { return Ok(*step) }
+    s.strip_prefix("pair")
+      .and_then(|n| n.parse().ok())
+      .filter(|n| (1..=4).contains(n))
+      .map(Step::Pair)
+      .ok_or(format!("unknown step '{}'", s))
+  }
+}
+
+/* MARK: PIECES
+  a piece is solved when every sticker matches the centre of the face it is on
+*/
+fn piece_solved(colors: &[Face; FACELETS], stickers: &[u8]) -> bool {
+  stickers.iter().all(|p| colors[*p as usize] == Face::from_index(*p as usize / 9))
+}
+
+fn touches(stickers: &[u8], face: Face) -> bool {
+  stickers.iter().any(|p| *p as usize / 9 == face.index())
+}
+
+fn edge_between(a: Face, b: Face) -> Option<&'static Vec<u8>> {
+  tables().edges.iter().find(|e| touches(e, a) && touches(e, b))
+}
+
+fn cross_solved(colors: &[Face; FACELETS], bottom: Face) -> bool {
+  tables().edges.iter().filter(|e| touches(e, bottom)).all(|e| piece_solved(colors, e))
+}
+
+// corners on the bottom face solved together with the middle layer edge beside them
+fn pairs_solved(colors: &[Face; FACELETS], bottom: Face) -> usize {
+  tables().corners.iter().filter(|c| touches(c, bottom)).filter(|corner| {
+    let sides: Vec<Face> = corner.iter().map(|p| Face::from_index(*p as usize / 9)).filter(|f| *f != bottom).collect();
+    piece_solved(colors, corner) && edge_between(sides[0], sides[1]).is_some_and(|e| piece_solved(colors, e))
+  }).count()
+}
+
+fn corners_solved(colors: &[Face; FACELETS], face: Face) -> bool {
+  tables().corners.iter().filter(|c| touches(c, face)).all(|c| piece_solved(colors, c))
+}
+
+// corners of the face solved once it is turned the right way
+fn corners_solved_after_auf(cube: Cube, face: Face) -> bool {
+  (0..4).any(|auf| {
+    let mut cube = cube;
+    cube.apply_move(Move::face(face, auf));
+    corners_solved(&cube.colors(), face)
+  })
+}
+
+fn face_oriented(colors: &[Face; FACELETS], face: Face) -> bool {
+  colors[face.index() * 9..face.index() * 9 + 9].iter().all(|c| *c == face)
+}
+
+// 1x2x3 block on the side face along the bottom: two corners and three edges
+fn block_solved(colors: &[Face; FACELETS], bottom: Face, side: Face) -> bool {
+  let tables = tables();
+  tables.corners.iter().filter(|c| touches(c, bottom) && touches(c, side)).all(|c| piece_solved(colors, c))
+    && tables.edges.iter().filter(|e| touches(e, side) && !touches(e, bottom.opposite())).all(|e| piece_solved(colors, e))
+}
+
+fn blocks_solved(colors: &[Face; FACELETS], bottom: Face, side: Face) -> bool {
+  block_solved(colors, bottom, side) && block_solved(colors, bottom, side.opposite())
+}
+
+// the cube and its bottom face with the slice between the blocks turned each way,
+// so blocks count whatever the M slice is doing
+fn slice_offsets(cube: Cube, frame: Frame) -> impl Iterator<Item = (Cube, Face)> {
+  let axis = cube.center_positions()[frame.side.index()].axis();
+  (0..4).map(move |quarters| {
+    let mut cube = cube;
+    if let Some(mv) = Move::from_layers(axis, [false, true, false], quarters) { cube.apply_move(mv); }
+    (cube, cube.center_positions()[frame.bottom.index()])
+  })
+}
+
+// an edge is oriented when its highest priority colour (bottom/top, then front/back)
+// sits on its highest priority sticker, so it can be solved without quarter turns of front or back
+fn edges_oriented(colors: &[Face; FACELETS], bottom: Face, front: Face) -> bool {
+  let priority = |face: Face| if face.axis() == bottom.axis() { 2 } else if face.axis() == front.axis() { 1 } else { 0 };
+  tables().edges.iter().all(|edge| {
+    let position = |p: &&u8| priority(Face::from_index(**p as usize / 9));
+    let color = |p: &&u8| priority(colors[**p as usize]);
+    edge.iter().max_by_key(position) == edge.iter().max_by_key(color)
+  })
+}
+
+/* MARK: SPLITS
+  each step ends at the first move after the previous step where its state is reached,
+  so a skipped step ends on the same move as the one before it
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+  pub step: Step,
+  // moves made when the step was done
+  pub end: usize,
+  // seconds into the solve when the step was done
+  pub time: f32,
+  // moves spent on this step alone
+  pub metrics: Metrics,
+}
+
+impl Split {
+  pub fn new(record: &SolveRecord, step: Step, start: usize, end: usize) -> Split {
+    let end = end.min(record.moves.len());
+    let time = if end == 0 { 0.0 } else { record.moves[end - 1].time };
+    let moves: Alg = record.moves[start.min(end)..end].iter().map(|m| m.mv).collect::<Vec<_>>().into();
+    Split { step, end, time, metrics: moves.metrics() }
+  }
+}
+
+// cube state before the solve and after every move of it
+fn states(record: &SolveRecord) -> Vec<Cube> {
+  let mut cube = Cube::from_alg(&record.scramble);
+  let mut states = vec![cube];
+  for m in &record.moves {
+    cube.apply_move(m.mv);
+    states.push(cube);
+  }
+  states
+}
+
+// moves made when each step was first done, stopping at the first step never reached
+fn step_ends(states: &[Cube], steps: &[Step], frame: Frame) -> Vec<(Step, usize)> {
+  let mut ends = Vec::new();
+  let mut from = 0;
+  for step in steps {
+    let Some(end) = (from..states.len()).find(|i| step.is_done(&states[*i], frame)) else { break };
+    ends.push((*step, end));
+    from = end;
+  }
+  ends
+}
+
+// colours are whichever get furthest soonest
+pub fn splits(record: &SolveRecord, method: Method) -> Vec<Split> {
+  let states = states(record);
+  let mut ends = Frame::all()
+    .map(|frame| step_ends(&states, method.steps(), frame))
+    .min_by_key(|ends| (std::cmp::Reverse(ends.len()), ends.last().map(|e| e.1), ends.first().map(|e| e.1)))
+    .unwrap_or_default();
+
+  // ZZ last layers that never pass through an oriented state were solved as one step
+  if method == Method::Zz {
+    if let [.., (Step::Oll, oll), (Step::Pll, pll)] = ends.as_slice() {
+      if oll == pll && ends.len() >= 3 && ends[ends.len() - 3].1 != *oll {
+        let end = *pll;
+        ends.truncate(ends.len() - 2);
+        ends.push((Step::Zbll, end));
+      }
+    }
+  }
+
+  let mut start = 0;
+  ends.into_iter().map(|(step, end)| {
+    let split = Split::new(record, step, start, end);
+    start = end;
+    split
+  }).collect()
+}
+
+/* MARK: NEXT STEP
+  the first step of the method not yet done, in the colours that have got furthest
+*/
+pub fn next_step(cube: &Cube, method: Method) -> Option<(Step, Frame)> {
+  let steps = method.steps();
+  let (done, frame) = Frame::all()
+    .map(|frame| (steps.iter().take_while(|step| step.is_done(cube, frame)).count(), frame))
+    .min_by_key(|(done, _)| std::cmp::Reverse(*done))?;
+  steps.get(done).map(|step| (*step, frame))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::record::TimedMove;
+
+  fn alg(s: &str) -> Alg { s.parse().unwrap() }
+
+  // a solve of `solution` from the scramble it undoes, one move a second
+  fn record(solution: &str) -> SolveRecord {
+    let solution = alg(solution);
+    let moves = solution.iter().enumerate().map(|(i, mv)| TimedMove { time: i as f32 + 1.0, mv: *mv }).collect();
+    SolveRecord::new(solution.inverse(), moves, solution.len() as f32)
+  }
+
+  fn ends(record: &SolveRecord, method: Method) -> Vec<(Step, usize)> {
+    splits(record, method).iter().map(|split| (split.step, split.end)).collect()
+  }
+
+  // cross, the last pair, a Sune for OLL and a T perm
+  const CFOP_SOLVE: &str = "F R U R' R U R' U R U2 R' R U R' U' R' F R2 U' R' U' R U R' F'";
+
+  #[test]
+  fn splits_a_cfop_solve() {
+    let record = record(CFOP_SOLVE);
+    // the first three pairs were never broken, they end with the cross
+    assert_eq!(ends(&record, Method::Cfop), [
+      (Step::Cross, 1), (Step::Pair(1), 1), (Step::Pair(2), 1), (Step::Pair(3), 1), (Step::Pair(4), 4),
+      (Step::Oll, 11), (Step::Pll, 25),
+    ]);
+    let splits = splits(&record, Method::Cfop);
+    assert_eq!((splits[4].time, splits[4].metrics.htm), (4.0, 3));
+    assert_eq!(splits[1].metrics.htm, 0);
+  }
+
+  #[test]
+  fn skipped_steps_end_with_the_one_before() {
+    // PLL skip: the Sune solves the cube
+    let record = record("F R U R' R U R' U R U2 R'");
+    assert_eq!(ends(&record, Method::Cfop)[5..], [(Step::Oll, 11), (Step::Pll, 11)]);
+  }
+
+  #[test]
+  fn stops_at_the_first_step_never_reached() {
+    // the solve was given up after the cross
+    let mut record = record("F R U R'");
+    record.moves.truncate(1);
+    assert_eq!(ends(&record, Method::Cfop).last(), Some(&(Step::Pair(3), 1)));
+  }
+
+  #[test]
+  fn splits_a_roux_solve() {
+    // the first block, then the second with the L turn lining the two up, a Sune for CMLL and M' U2 M
+    let record = record("D2 F' U' F2 L R U R' R U R' U R U2 R' M' U2 M");
+    assert_eq!(ends(&record, Method::Roux), [(Step::FirstBlock, 4), (Step::SecondBlock, 8), (Step::Cmll, 15), (Step::Lse, 18)]);
+  }
+
+  #[test]
+  fn splits_a_zz_solve() {
+    assert_eq!(ends(&record(CFOP_SOLVE), Method::Zz), [(Step::Eoline, 1), (Step::F2l, 4), (Step::Oll, 11), (Step::Pll, 25)]);
+  }
+
+  #[test]
+  fn merges_a_zz_last_layer_into_zbll() {
+    // nothing oriented until the Sune solves the last layer outright
+    let sune = record("F R U R' R U R' U R U2 R'");
+    assert_eq!(ends(&sune, Method::Zz), [(Step::Eoline, 1), (Step::F2l, 4), (Step::Zbll, 11)]);
+    // only ZZ merges, CFOP keeps its skipped PLL
+    assert_eq!(ends(&sune, Method::Cfop).last(), Some(&(Step::Pll, 11)));
+    // a last layer skipped along with F2L was not solved as a ZBLL
+    assert_eq!(ends(&record("F R U R'"), Method::Zz)[1..], [(Step::F2l, 4), (Step::Oll, 4), (Step::Pll, 4)]);
+  }
+
+  #[test]
+  fn next_step_is_the_first_not_done() {
+    let next = |moves: &str, method| next_step(&Cube::from_alg(&alg(moves)), method).map(|(step, _)| step);
+    for method in Method::ALL { assert_eq!(next("", method), None); }
+    assert_eq!(next("R U R'", Method::Cfop), Some(Step::Pair(4)));
+    assert_eq!(next("R U R'", Method::Beginner), Some(Step::FirstLayer));
+    assert_eq!(next("M' U2 M", Method::Roux), Some(Step::Lse));
+    let scrambled = Cube::from_alg(&alg(CFOP_SOLVE).inverse());
+    assert_eq!(next_step(&scrambled, Method::Zz).map(|(step, _)| step), Some(Step::Eoline));
+    // with B as the bottom a single F only turns the last layer
+    assert_eq!(next("F", Method::Zz), Some(Step::Pll));
+    // the frame is the one the cube is furthest along in, here the last pair sits on the right
+    let (_, frame) = next_step(&Cube::from_alg(&alg("R U R'")), Method::Cfop).unwrap();
+    assert_eq!(frame.bottom, Face::D);
+  }
+
+  #[test]
+  fn step_ids_read_back() {
+    for method in Method::ALL {
+      for step in method.steps() { assert_eq!(step.id().parse::<Step>(), Ok(*step)); }
+    }
+    assert!("pair5".parse::<Step>().is_err());
+  }
+}
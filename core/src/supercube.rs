@@ -0,0 +1,204 @@
+use std::sync::OnceLock;
+
+use crate::cube::{rotate, rotations, Cube};
+use crate::cubie::CubieCube;
+use crate::face::Face;
+use crate::moves::{Alg, Move};
+
+/* MARK: SUPERCUBE
+  centres with a marking, so a centre turned in place is not solved. each centre is followed
+  by the direction it faces and the direction its marking points, both turning with its layer.
+  the marking starts towards the top of the face as it is seen: U towards B, D towards F and
+  every side face up
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Centers {
+  // indexed by the centre's home face
+  normals: [[i8; 3]; 6],
+  marks: [[i8; 3]; 6],
+}
+
+impl Default for Centers {
+  fn default() -> Self {
+    Centers { normals: Face::ALL.map(Face::normal), marks: Face::ALL.map(home_mark) }
+  }
+}
+
+pub fn home_mark(face: Face) -> [i8; 3] {
+  match face {
+    Face::U => [0, 0, -1],
+    Face::D => [0, 0, 1],
+    _ => [0, 1, 0],
+  }
+}
+
+impl Centers {
+  pub fn from_alg(alg: &Alg) -> Centers {
+    let mut centers = Centers::default();
+    centers.apply_alg(alg);
+    centers
+  }
+
+  pub fn apply_move(&mut self, mv: Move) {
+    let axis = mv.axis();
+    let quarters = mv.quarter_turns().rem_euclid(4);
+    let layers = mv.layers();
+    for (normal, mark) in self.normals.iter_mut().zip(self.marks.iter_mut()) {
+      if !layers[(normal[axis.index()] + 1) as usize] { continue }
+      for _ in 0..quarters {
+        *normal = rotate(*normal, axis);
+        *mark = rotate(*mark, axis);
+      }
+    }
+  }
+
+  pub fn apply_alg(&mut self, alg: &Alg) {
+    for mv in alg { self.apply_move(*mv); }
+  }
+
+  // where the marking of a centre points, as the cube sits
+  pub fn mark(&self, face: Face) -> [i8; 3] { self.marks[face.index()] }
+
+  // clockwise quarter turns of each centre away from solved, seen from outside and measured
+  // against how the cube as a whole is held (the U and F centres say which way that is)
+  pub fn twists(&self) -> [u8; 6] {
+    let (up, front) = (self.normals[Face::U.index()], self.normals[Face::F.index()]);
+    let right = cross(up, front);
+    // home direction carried along with the cube
+    let held = |v: [i8; 3]| -> [i8; 3] { std::array::from_fn(|k| right[k] * v[0] + up[k] * v[1] + front[k] * v[2]) };
+    Face::ALL.map(|face| {
+      let (normal, expected) = (self.normals[face.index()], held(home_mark(face)));
+      // a clockwise quarter turn seen from outside is a left handed turn about the normal
+      let mut mark = expected;
+      for twist in 0..4 {
+        if mark == self.marks[face.index()] { return twist }
+        mark = cross(mark, normal);
+      }
+      0
+    })
+  }
+
+  // every marking where it should be for the way the cube is held
+  pub fn solved(&self) -> bool { self.twists() == [0; 6] }
+
+  // quarter turn twists always add up to an odd number with odd corner permutation
+  pub fn parity(&self) -> bool { self.twists().iter().map(|t| *t as usize).sum::<usize>() % 2 == 1 }
+}
+
+fn cross(a: [i8; 3], b: [i8; 3]) -> [i8; 3] {
+  [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+// whether the centre twists fit the rest of the cube, the only thing a supercube adds
+pub fn consistent(cube: &Cube, centers: &Centers) -> bool {
+  CubieCube::from_cube(cube).corner_parity() == centers.parity()
+}
+
+/* MARK: TWIST ALGS
+  turns that leave every corner and edge alone and only twist centres: one centre half way
+  round, or two neighbouring centres a quarter clockwise each. the same alg is used for every
+  face, held so its centres are the ones wanted
+*/
+const HALF_TWIST: &str = "(U R L U2 R' L')2";
+const QUARTER_PAIR: &str = "R U2 R2 U R2 U2 R' U2 R2 U R2 U2 R U2 R2 U' R2 U2";
+
+struct TwistAlgs {
+  half: [Alg; 6],
+  // both faces given, either way round
+  pairs: [[Option<Alg>; 6]; 6],
+}
+
+fn twist_algs() -> &'static TwistAlgs {
+  static ALGS: OnceLock<TwistAlgs> = OnceLock::new();
+  ALGS.get_or_init(|| {
+    // the alg held every way, relabelled to face turns, with what it does to the centres
+    let held = |alg: &str| -> Vec<(Alg, [u8; 6])> {
+      let alg: Alg = alg.parse().unwrap_or_default();
+      rotations().iter().map(|rotation| {
+        let alg = rotation.concat(&alg).concat(&rotation.inverse()).without_rotations();
+        let twists = Centers::from_alg(&alg).twists();
+        (alg, twists)
+      }).collect()
+    };
+    let (half, pairs) = (held(HALF_TWIST), held(QUARTER_PAIR));
+    let find = |all: &[(Alg, [u8; 6])], wanted: [u8; 6]| all.iter().find(|(_, t)| *t == wanted).map(|(alg, _)| alg.clone());
+    TwistAlgs {
+      half: Face::ALL.map(|face| {
+        let mut wanted = [0; 6];
+        wanted[face.index()] = 2;
+        find(&half, wanted).unwrap_or_default()
+      }),
+      pairs: Face::ALL.map(|first| Face::ALL.map(|second| {
+        let mut wanted = [0; 6];
+        wanted[first.index()] = 1;
+        wanted[second.index()] = 1;
+        find(&pairs, wanted)
+      })),
+    }
+  })
+}
+
+// each face next to the one before it, so a quarter twist can be passed along to the last
+const CHAIN: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+// turns taking the centres from `from` to the twists in `to`, which must have the same parity
+pub fn twist_to(from: &Centers, to: [u8; 6]) -> Alg {
+  let algs = twist_algs();
+  let current = from.twists();
+  let mut needed: [u8; 6] = std::array::from_fn(|i| (to[i] + 4 - current[i]) % 4);
+  let mut alg = Alg::new();
+  for pair in CHAIN.windows(2) {
+    let (face, next) = (pair[0], pair[1]);
+    let twist = needed[face.index()];
+    // half twists stay on the face, quarters take the next face along with them
+    let part = match twist {
+      2 => algs.half[face.index()].clone(),
+      1 => algs.pairs[face.index()][next.index()].clone().unwrap_or_default(),
+      3 => algs.pairs[face.index()][next.index()].as_ref().map(Alg::inverse).unwrap_or_default(),
+      _ => continue,
+    };
+    alg = alg.concat(&part);
+    needed[face.index()] = 0;
+    if twist != 2 { needed[next.index()] = (needed[next.index()] + 4 - twist) % 4; }
+  }
+  let last = CHAIN[CHAIN.len() - 1];
+  if needed[last.index()] == 2 { alg = alg.concat(&algs.half[last.index()]); }
+  alg.simplify()
+}
+
+#[cfg(test)]
+mod tests {
+  use rand_chacha::ChaCha8Rng;
+  use rand_core::{RngCore, SeedableRng};
+
+  use super::*;
+  use crate::scramble;
+
+  #[test]
+  fn twists_reach_any_target_of_the_same_parity() {
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    for _ in 0..50 {
+      let centers = Centers::from_alg(&scramble::random_moves(&mut rng, 25));
+      let mut to: [u8; 6] = std::array::from_fn(|_| (rng.next_u32() % 4) as u8);
+      if to.iter().map(|t| *t as usize).sum::<usize>() % 2 != centers.parity() as usize { to[0] = (to[0] + 1) % 4; }
+
+      let alg = twist_to(&centers, to);
+      // corners and edges stay where they are
+      assert!(Cube::from_alg(&alg).is_solved());
+      let mut twisted = centers;
+      twisted.apply_alg(&alg);
+      assert_eq!(twisted.twists(), to);
+    }
+  }
+
+  #[test]
+  fn centre_parity_follows_the_corners() {
+    let mut rng = ChaCha8Rng::seed_from_u64(6);
+    for _ in 0..50 {
+      let count = 1 + rng.next_u32() as usize % 30;
+      let alg = scramble::random_moves(&mut rng, count);
+      assert!(consistent(&Cube::from_alg(&alg), &Centers::from_alg(&alg)));
+    }
+    assert!(!consistent(&Cube::solved(), &Centers::from_alg(&"U".parse().unwrap())));
+  }
+}
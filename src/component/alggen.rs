@@ -0,0 +1,184 @@
+use std::fs;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+
+use puzzle_cube_core::alggen::{self, Generated, MaskPreset, Request};
+use puzzle_cube_core::Cube;
+
+use crate::component::cube::CubeState;
+use crate::component::focus::{Focus, TextFocus};
+
+pub struct AlgGenComponent;
+
+impl Plugin for AlgGenComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(AlgGen::default());
+    app.insert_resource(AlgGenSettings::default());
+    app.add_systems(Startup, setup_alggen);
+    // typing happens before anything else reads the keyboard
+    app.add_systems(PreUpdate, alggen_entry.after(InputSystem));
+    app.add_systems(Update, (
+      poll_alggen,
+      update_alggen_text.run_if(any_with_component::<AlgGenText>),
+    ).chain());
+  }
+}
+
+// the last list exported, one algorithm per line
+const EXPORT_FILE: &str = "generated.txt";
+// algorithms listed in the panel, the export has them all
+const SHOWN: usize = 12;
+
+/* MARK: ALG GEN <RES>
+  the cube as it stands is the case. a move set with an optional length and pieces to ignore is
+  typed in, <R,U,F> 10 UF UB, and the algorithms found are listed best first
+*/
+#[derive(Resource, Default)]
+pub struct AlgGen {
+  pub open: bool,
+  pub entry: String,
+  pub preset: MaskPreset,
+  task: Option<Task<Vec<Generated>>>,
+  // case and request of the last search, and what it found
+  case: Cube,
+  request: Option<Request>,
+  pub results: Vec<Generated>,
+  pub selected: usize,
+  pub message: Option<String>,
+}
+
+impl AlgGen {
+  pub fn searching(&self) -> bool { self.task.is_some() }
+
+  fn start(&mut self, cube: Cube) {
+    self.results.clear();
+    self.selected = 0;
+    match Request::parse(&self.entry, self.preset) {
+      Ok(request) => {
+        let shared = request.clone();
+        self.task = Some(AsyncComputeTaskPool::get().spawn(async move { alggen::generate(&cube, &shared) }));
+        self.case = cube;
+        self.request = Some(request);
+        self.message = None;
+      }
+      Err(e) => {
+        self.request = None;
+        self.message = Some(e.to_string());
+      }
+    }
+  }
+
+  fn export(&mut self) {
+    let Some(request) = &self.request else { return };
+    if self.results.is_empty() { return }
+    self.message = Some(match fs::write(EXPORT_FILE, alggen::export(&self.case, request, &self.results)) {
+      Ok(()) => format!("exported {} algorithms to {}", self.results.len(), EXPORT_FILE),
+      Err(e) => format!("could not export to {}: {}", EXPORT_FILE, e),
+    });
+  }
+}
+
+#[derive(Resource)]
+struct AlgGenSettings {
+  // opens the generator, and closes it again
+  button_open: Option<KeyCode>,
+}
+
+impl Default for AlgGenSettings {
+  fn default() -> Self {
+    AlgGenSettings { button_open: Some(KeyCode::F4) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn alggen_entry(
+  mut events: EventReader<KeyboardInput>,
+  mut kbd: ResMut<ButtonInput<KeyCode>>,
+  settings: Res<AlgGenSettings>,
+  state: Res<CubeState>,
+  mut focus: ResMut<TextFocus>,
+  mut alggen: ResMut<AlgGen>,
+) {
+  let open = settings.button_open.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  if focus.enter(TextFocus::AlgGen, &mut alggen.open, open, &mut events, &mut kbd) != Focus::Typing { return }
+
+  for event in events.read() {
+    if event.state != ButtonState::Pressed { continue }
+    match &event.logical_key {
+      Key::Character(text) => alggen.entry.push_str(text),
+      Key::Space => alggen.entry.push(' '),
+      Key::Backspace => { alggen.entry.pop(); }
+      Key::ArrowUp => alggen.selected = alggen.selected.saturating_sub(1),
+      Key::ArrowDown => alggen.selected = (alggen.selected + 1).min(alggen.results.len().saturating_sub(1)),
+      Key::Tab => alggen.preset = alggen.preset.next(),
+      Key::Enter if !alggen.searching() => alggen.start(state.cube),
+      Key::F2 => alggen.export(),
+      Key::Escape => alggen.open = false,
+      _ => {}
+    }
+  }
+  kbd.reset_all();
+}
+
+fn poll_alggen(mut alggen: ResMut<AlgGen>) {
+  let Some(task) = &mut alggen.task else { return };
+  let Some(results) = block_on(poll_once(task)) else { return };
+  alggen.task = None;
+  if results.is_empty() { alggen.message = Some("nothing found, or nothing to solve".to_string()); }
+  alggen.results = results;
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct AlgGenText;
+
+fn setup_alggen(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Percent(55.0),
+        left: Val::Percent(2.0),
+        ..default()
+      }),
+    AlgGenText,
+  ));
+}
+
+fn update_alggen_text(
+  alggen: Res<AlgGen>,
+  mut text: Query<&mut Text, With<AlgGenText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if alggen.open {
+    lines.push("Alg generator (Esc to close)".to_string());
+    lines.push(format!("moves: {}_", alggen.entry));
+    lines.push(format!("solving: {} (Tab to change)", alggen.preset.name()));
+    if let Some(request) = &alggen.request {
+      let ignored = request.mask.ignored();
+      if !ignored.is_empty() { lines.push(format!("ignoring {}", ignored.join(" "))); }
+    }
+    if alggen.searching() { lines.push("searching...".to_string()); }
+    if let Some(message) = &alggen.message { lines.push(message.clone()); }
+    let first = alggen.selected.saturating_sub(SHOWN - 1);
+    for (i, generated) in alggen.results.iter().enumerate().skip(first).take(SHOWN) {
+      let marker = if i == alggen.selected { ">" } else { " " };
+      lines.push(format!("{} {} ({}, score {:.1})", marker, generated.alg, generated.alg.len(), generated.score));
+    }
+    if alggen.results.is_empty() && !alggen.searching() {
+      lines.push("e.g. <R,U,F> 10 UF UB, Enter to search the cube as it is".to_string());
+    } else if !alggen.results.is_empty() {
+      lines.push(format!("{} found, F2 to export", alggen.results.len()));
+    }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -0,0 +1,153 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+
+use puzzle_cube_core::analysis::{corner_position, edge_position, Analysis};
+use puzzle_cube_core::Alg;
+
+use crate::component::cube::Block;
+use crate::component::focus::{Focus, TextFocus};
+
+pub struct AnalysisComponent;
+
+impl Plugin for AnalysisComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(AlgAnalysis::default());
+    app.insert_resource(AnalysisSettings::default());
+    app.add_systems(Startup, setup_analysis);
+    // typing happens before anything else reads the keyboard
+    app.add_systems(PreUpdate, analysis_entry.after(InputSystem));
+    app.add_systems(Update, (
+      highlight_pieces.run_if(any_with_component::<Block>),
+      update_analysis_text.run_if(any_with_component::<AnalysisText>),
+    ));
+  }
+}
+
+const CORNER_HIGHLIGHT: Color = Color::srgb(1.0, 0.3, 0.8);
+const EDGE_HIGHLIGHT: Color = Color::srgb(0.2, 0.9, 1.0);
+
+/* MARK: ANALYSIS <RES>
+  an algorithm typed in and taken apart: its order, cycles and parity, with the blocks it
+  moves outlined where they sit on a solved cube
+*/
+#[derive(Resource, Default)]
+pub struct AlgAnalysis {
+  pub open: bool,
+  pub entry: String,
+  pub result: Option<Result<(Alg, Analysis), String>>,
+}
+
+impl AlgAnalysis {
+  pub fn analyse(&mut self) {
+    self.result = Some(match self.entry.parse::<Alg>() {
+      Ok(alg) if alg.is_empty() => Err("type an algorithm first".to_string()),
+      Ok(alg) => {
+        let analysis = Analysis::of(&alg);
+        Ok((alg, analysis))
+      }
+      Err(e) => Err(format!("invalid algorithm: {}", e)),
+    });
+  }
+}
+
+#[derive(Resource)]
+struct AnalysisSettings {
+  // opens the panel, and closes it again
+  button_open: Option<KeyCode>,
+}
+
+impl Default for AnalysisSettings {
+  fn default() -> Self {
+    AnalysisSettings { button_open: Some(KeyCode::F11) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn analysis_entry(
+  mut events: EventReader<KeyboardInput>,
+  mut kbd: ResMut<ButtonInput<KeyCode>>,
+  settings: Res<AnalysisSettings>,
+  mut focus: ResMut<TextFocus>,
+  mut analysis: ResMut<AlgAnalysis>,
+) {
+  let open = settings.button_open.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  if focus.enter(TextFocus::Analysis, &mut analysis.open, open, &mut events, &mut kbd) != Focus::Typing { return }
+
+  for event in events.read() {
+    if event.state != ButtonState::Pressed { continue }
+    match &event.logical_key {
+      Key::Character(text) => analysis.entry.push_str(text),
+      Key::Space => analysis.entry.push(' '),
+      Key::Backspace => { analysis.entry.pop(); }
+      Key::Enter => analysis.analyse(),
+      Key::Escape => analysis.open = false,
+      _ => {}
+    }
+  }
+  if open { analysis.open = false; }
+  kbd.reset_all();
+}
+
+// outlines the blocks at the positions the algorithm changes, corners and edges apart
+fn highlight_pieces(
+  mut gizmos: Gizmos,
+  analysis: Res<AlgAnalysis>,
+  blocks: Query<&Transform, With<Block>>,
+) {
+  let Some(Ok((_, result))) = analysis.result.as_ref().filter(|_| analysis.open) else { return };
+
+  let positions: Vec<([i8; 3], Color)> = result.affected_corners().map(|c| (corner_position(c), CORNER_HIGHLIGHT))
+    .chain(result.affected_edges().map(|e| (edge_position(e), EDGE_HIGHLIGHT)))
+    .collect();
+  for transform in &blocks {
+    // blocks sit 2.2 apart
+    let at = (transform.translation / 2.2).round();
+    let Some((_, color)) = positions.iter().find(|(p, _)| Vec3::from_array(p.map(|c| c as f32)) == at) else { continue };
+    gizmos.cuboid(Transform::from_translation(transform.translation).with_scale(Vec3::splat(2.3)), *color);
+  }
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct AnalysisText;
+
+fn setup_analysis(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Percent(20.0),
+        left: Val::Percent(35.0),
+        ..default()
+      }),
+    AnalysisText,
+  ));
+}
+
+fn update_analysis_text(
+  analysis: Res<AlgAnalysis>,
+  mut text: Query<&mut Text, With<AnalysisText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if analysis.open {
+    lines.push("Algorithm analysis (F11 to close)".to_string());
+    lines.push(format!("alg: {}_", analysis.entry));
+    match &analysis.result {
+      Some(Ok((alg, result))) => {
+        lines.push(format!("{} ({} moves)", alg, alg.len()));
+        lines.push(result.to_string());
+        lines.push("+ twisted clockwise or flipped, - twisted anticlockwise".to_string());
+      }
+      Some(Err(e)) => lines.push(e.clone()),
+      None => lines.push("Enter to analyse, commutators as [A: [B, C]]".to_string()),
+    }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
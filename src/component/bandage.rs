@@ -0,0 +1,133 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use puzzle_cube_core::bandage::{Bandaging, BANDAGES};
+use puzzle_cube_core::puzzle::Puzzle;
+use puzzle_cube_core::{Cube, Move};
+
+use crate::component::cube::CubeState;
+use crate::component::puzzles::PuzzleType;
+use crate::component::replay::replay_closed;
+
+pub struct BandageComponent;
+
+impl Plugin for BandageComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Bandage::load(BANDAGES_FILE));
+    app.insert_resource(BandageSettings::default());
+    app.add_systems(Startup, setup_bandage_text);
+    app.add_systems(Update, (
+      bandage_control.run_if(replay_closed),
+      draw_bandages,
+      update_bandage_text.run_if(any_with_component::<BandageText>),
+    ).chain());
+  }
+}
+
+// the player's own bandagings, name and blocks tab separated: Bar	UFL+UF+UFR
+const BANDAGES_FILE: &str = "bandages.txt";
+
+/* MARK: BANDAGE <RES>
+  fused blocks on the 3x3. picking one resets the cube, after that a turn that would split a
+  block shakes instead of turning, and scrambles and hints only use turns the blocks allow
+*/
+#[derive(Resource, Default)]
+pub struct Bandage {
+  // the bundled ones first, then any from the file
+  pub list: Vec<(String, Bandaging)>,
+  pub selected: Option<usize>,
+}
+
+impl Bandage {
+  // a missing file is just the bundled bandagings, unreadable lines are skipped
+  fn load(path: &str) -> Bandage {
+    let mut list: Vec<(String, Bandaging)> = BANDAGES.iter()
+      .filter_map(|preset| preset.blocks.parse().ok().map(|bandaging| (preset.name.to_string(), bandaging)))
+      .collect();
+    if let Ok(text) = fs::read_to_string(path) {
+      list.extend(text.lines().filter(|line| !line.trim().is_empty()).filter_map(|line| {
+        let (name, blocks) = line.split_once('\t')?;
+        blocks.parse().map_err(|e| warn!("skipping bandaging {} in {}: {}", name, path, e)).ok().map(|bandaging| (name.to_string(), bandaging))
+      }));
+    }
+    Bandage { list, selected: None }
+  }
+
+  pub fn current(&self) -> Option<&Bandaging> { self.selected.and_then(|i| self.list.get(i)).map(|(_, bandaging)| bandaging) }
+
+  pub fn allows(&self, cube: &Cube, mv: Move) -> bool { self.current().is_none_or(|bandaging| bandaging.allows(cube, mv)) }
+}
+
+#[derive(Resource)]
+struct BandageSettings {
+  // no bandaging, then each one in turn
+  button_bandage: Option<KeyCode>,
+}
+
+impl Default for BandageSettings {
+  fn default() -> Self {
+    BandageSettings { button_bandage: Some(KeyCode::Backslash) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn bandage_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<BandageSettings>,
+  puzzle: Res<PuzzleType>,
+  mut bandage: ResMut<Bandage>,
+) {
+  // blocks are fused on the 3x3, the other puzzles go without
+  if puzzle.is_changed() && puzzle.puzzle != Puzzle::Cube && bandage.selected.is_some() { bandage.selected = None; }
+  if !settings.button_bandage.map(|key| kbd.just_pressed(key)).unwrap_or(false) { return }
+  if puzzle.puzzle != Puzzle::Cube { warn!("bandaging is only for the {}", Puzzle::Cube.name()); return }
+
+  bandage.selected = match bandage.selected {
+    None if !bandage.list.is_empty() => Some(0),
+    Some(i) if i + 1 < bandage.list.len() => Some(i + 1),
+    _ => None,
+  };
+}
+
+// an outline around each pair of fused pieces, wherever the turns have taken them
+fn draw_bandages(mut gizmos: Gizmos, bandage: Res<Bandage>, state: Res<CubeState>) {
+  let Some(bandaging) = bandage.current() else { return };
+  let color = Color::srgb(1.0, 0.55, 0.1);
+  for (a, b) in bandaging.bond_positions(&state.cube) {
+    let (a, b) = (Vec3::from_array(a.map(|v| v as f32)), Vec3::from_array(b.map(|v| v as f32)));
+    // blocks sit 2.2 apart, the outline spans both along the bond
+    let size = Vec3::splat(2.3) + (b - a).abs() * 2.2;
+    gizmos.cuboid(Transform::from_translation((a + b) * 1.1).with_scale(size), color);
+  }
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct BandageText;
+
+fn setup_bandage_text(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Percent(11.0),
+        left: Val::Percent(35.0),
+        ..default()
+      }),
+    BandageText,
+  ));
+}
+
+fn update_bandage_text(bandage: Res<Bandage>, mut text: Query<&mut Text, With<BandageText>>) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let content = match bandage.selected.and_then(|i| bandage.list.get(i)) {
+    Some((name, bandaging)) => format!("Bandaged, {}: {}", name, bandaging),
+    None => String::new(),
+  };
+
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
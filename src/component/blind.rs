@@ -0,0 +1,260 @@
+use std::fs;
+
+use bevy::{prelude::*, time::Stopwatch};
+
+use puzzle_cube_core::blind::{self, BlindMethod, Lettering, Memo};
+use puzzle_cube_core::Cube;
+
+use crate::component::cube::{Block, GameSettings, SolveLog};
+use crate::component::replay::replay_closed;
+
+pub struct BlindComponent;
+
+impl Plugin for BlindComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Blind::new(load_lettering(LETTERING_FILE)));
+    app.insert_resource(BlindSettings::default());
+    app.add_systems(Startup, setup_blind);
+    app.add_systems(Update, (
+      (blind_control, memo_clock).run_if(replay_closed),
+      hide_stickers.run_if(any_with_component::<Block>),
+      update_blind_text.run_if(any_with_component::<BlindText>),
+    ).chain());
+  }
+}
+
+// optional custom lettering: 24 corner letters then 24 edge letters, in Speffz order
+const LETTERING_FILE: &str = "lettering.txt";
+
+const HIDDEN_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+// materials darker than this are the plastic, everything else is a sticker
+const PLASTIC_BRIGHTNESS: f32 = 0.05;
+
+/* MARK: BLIND <RES>
+  scrambles go to a random state from solved. memo runs from the scramble finishing to the
+  first turn, then the stickers go grey until the cube is solved or the attempt is stopped
+*/
+#[derive(Resource)]
+pub struct Blind {
+  pub active: bool,
+  pub method: BlindMethod,
+  pub lettering: Lettering,
+  pub memo: Stopwatch,
+  // stickers are grey while executing
+  pub hidden: bool,
+  // letters for the cube as it is, only worked out when asked for
+  pub helper: Option<Memo>,
+  // memo, execution and whether the cube ended solved, for each attempt this session
+  pub attempts: Vec<(f32, f32, bool)>,
+}
+
+impl Blind {
+  fn new(lettering: Lettering) -> Blind {
+    Blind {
+      active: false,
+      method: BlindMethod::default(),
+      lettering,
+      memo: Stopwatch::new(),
+      hidden: false,
+      helper: None,
+      attempts: Vec::new(),
+    }
+  }
+
+  // an attempt is over, false outside blind mode
+  pub fn finish(&mut self, execution: f32, solved: bool) -> bool {
+    if !self.active { return false }
+    self.attempts.push((self.memo.elapsed_secs(), execution, solved));
+    self.helper = None;
+    true
+  }
+}
+
+fn load_lettering(path: &str) -> Lettering {
+  let Ok(text) = fs::read_to_string(path) else { return Lettering::default() };
+  text.parse().unwrap_or_else(|e| {
+    warn!("ignoring lettering in {}: {}", path, e);
+    Lettering::default()
+  })
+}
+
+#[derive(Resource)]
+struct BlindSettings {
+  button_blind: Option<KeyCode>,
+  button_method: Option<KeyCode>,
+  button_helper: Option<KeyCode>,
+  // ends the attempt, a DNF unless the cube is solved
+  button_stop: Option<KeyCode>,
+}
+
+impl Default for BlindSettings {
+  fn default() -> Self {
+    BlindSettings {
+      button_blind: Some(KeyCode::KeyB),
+      button_method: Some(KeyCode::KeyK),
+      button_helper: Some(KeyCode::KeyN),
+      button_stop: Some(KeyCode::Enter),
+    }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn blind_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<BlindSettings>,
+  mut blind: ResMut<Blind>,
+  mut log: ResMut<SolveLog>,
+  mut game: ResMut<GameSettings>,
+) {
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+
+  if pressed(settings.button_blind) {
+    blind.active = !blind.active;
+    blind.helper = None;
+  }
+  if !blind.active { return }
+  if pressed(settings.button_method) {
+    blind.method = blind.method.next();
+    blind.helper = None;
+  }
+
+  // the helper is for memorising, it is not there once execution starts
+  if pressed(settings.button_helper) && !game.timing() {
+    blind.helper = match blind.helper {
+      Some(_) => None,
+      // the cube as it is now: the scramble and whatever has been turned since
+      None => {
+        let mut cube = Cube::from_alg(&log.scramble);
+        for timed in &log.moves { cube.apply_move(timed.mv); }
+        Some(blind::memo(&cube, blind.method, &blind.lettering))
+      }
+    };
+  }
+
+  if pressed(settings.button_stop) && log.solving && game.timing() {
+    game.clock.pause();
+    log.solving = false;
+    log.moves.clear();
+    let execution = game.clock.elapsed_secs();
+    blind.finish(execution, false);
+  }
+}
+
+fn memo_clock(
+  time: Res<Time>,
+  game: Res<GameSettings>,
+  log: Res<SolveLog>,
+  mut blind: ResMut<Blind>,
+  mut solving: Local<bool>,
+) {
+  // a new scramble has just finished
+  if log.solving && !*solving {
+    blind.memo.reset();
+    blind.helper = None;
+  }
+  *solving = log.solving;
+
+  if blind.active && log.solving && !game.timing() { blind.memo.tick(time.delta()); }
+  let hidden = blind.active && log.solving && game.timing();
+  if blind.hidden != hidden {
+    blind.hidden = hidden;
+    if hidden { blind.helper = None; }
+  }
+}
+
+/* MARK: STICKERS
+  every sticker material on the blocks is swapped for one grey material, the original kept
+  on the entity to put back afterwards
+*/
+#[derive(Component)]
+struct StickerMaterial(Handle<StandardMaterial>);
+
+fn hide_stickers(
+  mut commands: Commands,
+  blind: Res<Blind>,
+  blocks: Query<Entity, With<Block>>,
+  children: Query<&Children>,
+  mut meshes: Query<(&mut Handle<StandardMaterial>, Option<&StickerMaterial>)>,
+  mut materials: ResMut<Assets<StandardMaterial>>,
+  mut shown: Local<(bool, Option<Handle<StandardMaterial>>)>,
+) {
+  let (hidden, grey) = &mut *shown;
+  if *hidden == blind.hidden { return }
+  *hidden = blind.hidden;
+
+  let grey = grey.get_or_insert_with(|| materials.add(StandardMaterial { base_color: HIDDEN_COLOR, ..default() })).clone();
+  for block in &blocks {
+    for entity in children.iter_descendants(block) {
+      let Ok((mut material, original)) = meshes.get_mut(entity) else { continue };
+      if !*hidden {
+        let Some(original) = original else { continue };
+        *material = original.0.clone();
+        commands.entity(entity).remove::<StickerMaterial>();
+        continue
+      }
+
+      let plastic = materials.get(&*material).is_none_or(|m| {
+        let color = m.base_color.to_linear();
+        color.red + color.green + color.blue < PLASTIC_BRIGHTNESS
+      });
+      if plastic || original.is_some() { continue }
+      commands.entity(entity).insert(StickerMaterial(material.clone()));
+      *material = grey.clone();
+    }
+  }
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct BlindText;
+
+fn setup_blind(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        right: Val::Px(12.0),
+        bottom: Val::Px(12.0),
+        ..default()
+      }),
+    BlindText,
+  ));
+}
+
+fn update_blind_text(
+  blind: Res<Blind>,
+  game: Res<GameSettings>,
+  log: Res<SolveLog>,
+  mut text: Query<&mut Text, With<BlindText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if blind.active {
+    lines.push(format!("Blind, {} (K to change)", blind.method));
+    lines.push(if log.solving && !game.timing() {
+      format!("memo {:.2}, the first turn hides the stickers", blind.memo.elapsed_secs())
+    } else if log.solving {
+      format!("executing {:.2}, Enter to stop", game.clock.elapsed_secs())
+    } else {
+      "T to scramble".to_string()
+    });
+
+    match &blind.helper {
+      Some(memo) => lines.extend(memo.to_string().lines().map(String::from)),
+      None => lines.push("N for the memo letters".to_string()),
+    }
+
+    if let Some((memo, execution, solved)) = blind.attempts.last() {
+      let result = if *solved { format!("{:.2}", memo + execution) } else { "DNF".to_string() };
+      lines.push(format!("last {} (memo {:.2}, execution {:.2})", result, memo, execution));
+      let successes = blind.attempts.iter().filter(|(_, _, solved)| *solved).count();
+      lines.push(format!("{} of {} solved", successes, blind.attempts.len()));
+    }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -95,7 +95,8 @@ impl Default for OrbitSettings {
 }
 
 pub fn setup_camera(mut commands: Commands) {
-  commands.spawn(OrbitCameraBundle::default());
+  // other cameras draw over parts of the screen, the UI stays with this one
+  commands.spawn((OrbitCameraBundle::default(), IsDefaultUiCamera));
 }
 
 
@@ -195,8 +196,7 @@ pub fn orbit_camera_control(
       // YXZ Euler Rotation performs yaw/pitch/roll.
       transform.rotation = Quat::from_euler(EulerRot::YXZ, state.yaw, state.pitch, 0.0);
       // To position the camera, get the backward direction vector and place the camera at the desired radius from the center.
-      if state.radius > MIN_ZOOM { state.radius = MIN_ZOOM }
-      else if state.radius < MAX_ZOOM { state.radius = MAX_ZOOM };
+      state.radius = state.radius.clamp(MAX_ZOOM, MIN_ZOOM);
       transform.translation = state.center + transform.back() * state.radius;
     }
 
@@ -0,0 +1,167 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+
+use puzzle_cube_core::commutator::{self, Commutator, Sticker};
+
+use crate::component::analysis::AlgAnalysis;
+use crate::component::focus::{Focus, TextFocus};
+
+pub struct CommutatorComponent;
+
+impl Plugin for CommutatorComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(CommutatorFinder::default());
+    app.insert_resource(CommutatorSettings::default());
+    app.add_systems(Startup, setup_finder);
+    // typing happens before anything else reads the keyboard
+    app.add_systems(PreUpdate, finder_entry.after(InputSystem));
+    app.add_systems(Update, (
+      poll_finder,
+      update_finder_text.run_if(any_with_component::<FinderText>),
+    ).chain());
+  }
+}
+
+/* MARK: FINDER <RES>
+  three stickers typed in, UFR UBR UBL, and the short commutators cycling them. the search runs
+  in the background, the chosen one is taken apart in the analysis panel
+*/
+#[derive(Resource, Default)]
+pub struct CommutatorFinder {
+  pub open: bool,
+  pub entry: String,
+  task: Option<Task<Vec<Commutator>>>,
+  // stickers of the last search and what it found
+  pub stickers: Option<[Sticker; 3]>,
+  pub results: Vec<Commutator>,
+  pub selected: usize,
+  pub error: Option<String>,
+}
+
+impl CommutatorFinder {
+  pub fn searching(&self) -> bool { self.task.is_some() }
+
+  fn start(&mut self) {
+    self.results.clear();
+    self.selected = 0;
+    match commutator::three_cycle(&self.entry) {
+      Ok(stickers) => {
+        self.task = Some(AsyncComputeTaskPool::get().spawn(async move { commutator::find(&stickers) }));
+        self.stickers = Some(stickers);
+        self.error = None;
+      }
+      Err(e) => {
+        self.stickers = None;
+        self.error = Some(e.to_string());
+      }
+    }
+  }
+}
+
+#[derive(Resource)]
+struct CommutatorSettings {
+  // opens the finder, and closes it again
+  button_open: Option<KeyCode>,
+}
+
+impl Default for CommutatorSettings {
+  fn default() -> Self {
+    CommutatorSettings { button_open: Some(KeyCode::F12) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn finder_entry(
+  mut events: EventReader<KeyboardInput>,
+  mut kbd: ResMut<ButtonInput<KeyCode>>,
+  settings: Res<CommutatorSettings>,
+  mut focus: ResMut<TextFocus>,
+  mut finder: ResMut<CommutatorFinder>,
+  mut analysis: ResMut<AlgAnalysis>,
+) {
+  let open = settings.button_open.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  if focus.enter(TextFocus::Commutator, &mut finder.open, open, &mut events, &mut kbd) != Focus::Typing { return }
+
+  for event in events.read() {
+    if event.state != ButtonState::Pressed { continue }
+    match &event.logical_key {
+      Key::Character(text) => finder.entry.push_str(text),
+      Key::Space => finder.entry.push(' '),
+      Key::Backspace => { finder.entry.pop(); }
+      Key::ArrowUp => finder.selected = finder.selected.saturating_sub(1),
+      Key::ArrowDown => finder.selected = (finder.selected + 1).min(finder.results.len().saturating_sub(1)),
+      Key::Enter if !finder.searching() => finder.start(),
+      // the selected commutator goes over to the analysis panel
+      Key::Tab => if let Some(comm) = finder.results.get(finder.selected) {
+        analysis.entry = comm.to_string();
+        analysis.analyse();
+        analysis.open = true;
+        finder.open = false;
+        *focus = TextFocus::Analysis;
+      },
+      Key::Escape => finder.open = false,
+      _ => {}
+    }
+  }
+  if open { finder.open = false; }
+  kbd.reset_all();
+}
+
+fn poll_finder(mut finder: ResMut<CommutatorFinder>) {
+  let Some(task) = &mut finder.task else { return };
+  let Some(results) = block_on(poll_once(task)) else { return };
+  finder.task = None;
+  if results.is_empty() { finder.error = Some("nothing short enough found".to_string()); }
+  finder.results = results;
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct FinderText;
+
+fn setup_finder(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Percent(45.0),
+        right: Val::Percent(2.0),
+        ..default()
+      }),
+    FinderText,
+  ));
+}
+
+fn update_finder_text(
+  finder: Res<CommutatorFinder>,
+  mut text: Query<&mut Text, With<FinderText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if finder.open {
+    lines.push("3-cycle finder (F12 to close)".to_string());
+    lines.push(format!("stickers: {}_", finder.entry));
+    if let Some(stickers) = &finder.stickers {
+      lines.push(format!("{} -> {} -> {}", stickers[0], stickers[1], stickers[2]));
+    }
+    if finder.searching() { lines.push("searching...".to_string()); }
+    if let Some(e) = &finder.error { lines.push(e.clone()); }
+    for (i, comm) in finder.results.iter().enumerate() {
+      let marker = if i == finder.selected { ">" } else { " " };
+      lines.push(format!("{} {} ({} moves)", marker, comm, comm.expand().len()));
+    }
+    if finder.results.is_empty() && !finder.searching() {
+      lines.push("first sticker face first, e.g. UFR UBR UBL, Enter to search".to_string());
+    } else if !finder.results.is_empty() {
+      lines.push("Tab to analyse the selected one".to_string());
+    }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -1,7 +1,28 @@
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
 use bevy::{prelude::*, time::Stopwatch};
 use bevy_prng::ChaCha8Rng;
 use bevy_rand::prelude::GlobalEntropy;
-use rand_core::{RngCore, SeedableRng};
+use puzzle_cube_core::puzzle::Puzzle;
+use puzzle_cube_core::solver::SolveError;
+use puzzle_cube_core::supercube::Centers;
+use puzzle_cube_core::{scramble, solver, steps, steps::Method, Alg, Axis, Cube, Face, Metric, Move, Slice, SolveRecord, TimedMove, Turn};
+use rand_core::SeedableRng;
+
+use crate::component::bandage::Bandage;
+use crate::component::blind::Blind;
+use crate::component::fmc::Fmc;
+use crate::component::focus::TextFocus;
+use crate::component::patterns::Patterns;
+use crate::component::puzzles::PuzzleType;
+use crate::component::replay::{replay_closed, Replay};
+use crate::component::shapes::{PuzzleShape, Shape};
+use crate::component::supercube::Supercube;
+use crate::component::thistlethwaite::Thistlethwaite;
+use crate::component::trainer::{color_name, Trainer};
 
 // °s per second
 const TURN_SPEED: f32 = 560.0;
@@ -13,7 +34,19 @@ const TURN_DELAY: f32 = 0.14;
 const DOUBLE_DELAY: f32 = 0.18;
 const SCRAMBLE_DELAY: f32 = 0.075;
 
-const SCRAMBLE_NUM: i32 = 3;
+const SCRAMBLE_NUM: usize = 3;
+const SUPERCUBE_SCRAMBLE_NUM: usize = 25;
+// bandaged cubes scramble with legal turns only, and need more of them to get anywhere
+const BANDAGE_SCRAMBLE_NUM: usize = 40;
+
+// how far a layer blocked by a bandaged block rocks before settling back
+const JAM_ANGLE: f32 = 8.0;
+
+// seconds a hint stays up
+const HINT_TIME: f32 = 2.5;
+
+// finished solves, one per line
+const HISTORY_FILE: &str = "solves.txt";
 
 /* MARK: CUBE PLUGIN
 */
@@ -27,12 +60,18 @@ impl Plugin for CubeModels {
       reset_cube.run_if(any_with_component::<Block>),
       cube_control.run_if(any_with_component::<Block>),
       scramble_cube.run_if(any_with_component::<Block>),
+      reposition_cube.run_if(any_with_component::<Block>),
       rotate_scramble.run_if(any_with_component::<Block>),
       pregame.run_if(any_with_component::<Block>),
+      change_method,
+      hint_control,
+      draw_hint,
       (toggle_double_turn.run_if(any_with_component::<Block>),
+      demo_turn.run_if(any_with_component::<Block>),
       rotate_cube.run_if(any_with_component::<Block>), 
       check_cube.run_if(any_with_component::<Block>)).chain(),
-    ));
+    ).run_if(replay_closed));
+    app.add_systems(Update, replay_cube.run_if(any_with_component::<Block>));
     app.insert_resource(AggregateMovement { 
       active: false, 
       speed: TURN_SPEED,
@@ -40,11 +79,19 @@ impl Plugin for CubeModels {
       axis: Vec3::ZERO,
       positive: true,
       direction: 1.0,
+      turn: None,
       turn_timer: Timer::from_seconds(TURN_DELAY, TimerMode::Repeating),
       double_timer: Timer::from_seconds(DOUBLE_DELAY, TimerMode::Repeating),
-      scramble: 0,
+      scramble: VecDeque::new(),
+      pattern: false,
+      jammed: false,
       scramble_turn_timer: Timer::from_seconds(SCRAMBLE_DELAY, TimerMode::Repeating),
     });
+    app.insert_resource(CubeState::default());
+    app.insert_resource(SolveLog::default());
+    app.insert_resource(TextFocus::default());
+    app.insert_resource(SolveHistory::load(HISTORY_FILE));
+    app.insert_resource(Hint::default());
     app.insert_resource(ControlSettings {
       settings: ControlBinds::default(),
     });
@@ -52,6 +99,7 @@ impl Plugin for CubeModels {
       clock: Stopwatch::default(),
       pregame: Timer::from_seconds(15.0, TimerMode::Once),
       shuffle: SCRAMBLE_NUM,
+      method: Method::default(),
     });
 
     app.insert_resource(GlobalEntropy::new(ChaCha8Rng::seed_from_u64(0)));
@@ -70,12 +118,27 @@ struct AggregateMovement {
   axis: Vec3,
   positive: bool, // positive quadrant or not
   direction: f32,
+  turn: Option<Move>,
   turn_timer: Timer,
   double_timer: Timer,
 
-  // used with scramble turns
+  // used with scramble turns, front move is the one animating
   scramble_turn_timer: Timer,
-  scramble: i32,
+  scramble: VecDeque<Move>,
+  // the scramble turns are a pattern being shown, not a solve to start
+  pattern: bool,
+  // the turn in progress was blocked by the bandaging and only shakes the layer
+  jammed: bool,
+}
+
+// state model mirrored by the blocks, updated as each turn starts
+#[derive(Resource, Default)]
+pub struct CubeState {
+  pub cube: Cube,
+  // which way each centre is turned, only seen on a supercube
+  pub centers: Centers,
+  // turns since the cube was last solved, enough to rebuild the blocks after a replay
+  moves: Alg,
 }
 
 #[derive(Resource)]
@@ -84,10 +147,74 @@ struct ControlSettings {
 }
 
 #[derive(Resource)]
-struct GameSettings {
-  clock: Stopwatch,
-  pregame: Timer,
-  shuffle: i32,
+pub struct GameSettings {
+  pub clock: Stopwatch,
+  pub pregame: Timer,
+  pub shuffle: usize,
+  // splits are detected for this method
+  pub method: Method,
+}
+
+impl GameSettings {
+  // clock runs once inspection is over, or cut short by the first turn
+  pub fn timing(&self) -> bool { self.pregame.finished() || self.pregame.paused() }
+}
+
+// turns of the solve in progress, solving from the end of a scramble until the cube is solved
+#[derive(Resource, Default)]
+pub struct SolveLog {
+  pub solving: bool,
+  pub scramble: Alg,
+  pub moves: Vec<TimedMove>,
+  // hints asked for since the scramble
+  pub hints: usize,
+  // puzzle and time of the last solve kept out of the history, until the next 3x3 solve
+  pub variant: Option<(&'static str, f32)>,
+}
+
+/* MARK: HINT <RES>
+  the next move of a solution, shown as an arrow around its layer until it is made,
+  or the next milestone of the beginner method in words. the solution is searched for in the
+  background, the hint is dropped if the cube moves before it is found
+*/
+#[derive(Resource)]
+pub struct Hint {
+  pub mv: Option<Move>,
+  pub step: Option<String>,
+  // position the hint was for
+  at: Cube,
+  shown: Timer,
+  task: Option<Task<Result<Alg, SolveError>>>,
+}
+
+impl Hint {
+  pub fn thinking(&self) -> bool { self.task.is_some() }
+}
+
+impl Default for Hint {
+  fn default() -> Self {
+    Hint { mv: None, step: None, at: Cube::default(), shown: Timer::from_seconds(HINT_TIME, TimerMode::Once), task: None }
+  }
+}
+
+#[derive(Resource, Default)]
+pub struct SolveHistory(pub Vec<SolveRecord>);
+
+impl SolveHistory {
+  // a missing file is an empty history, unreadable lines are skipped
+  fn load(path: &str) -> SolveHistory {
+    let Ok(text) = fs::read_to_string(path) else { return SolveHistory::default() };
+    SolveHistory(text.lines().filter(|line| !line.trim().is_empty()).filter_map(|line| {
+      line.parse().map_err(|e| warn!("skipping solve in {}: {}", path, e)).ok()
+    }).collect())
+  }
+
+  fn push(&mut self, path: &str, record: SolveRecord) {
+    let saved = OpenOptions::new().create(true).append(true).open(path)
+      .and_then(|mut file| writeln!(file, "{}", record));
+    if let Err(e) = saved { warn!("could not save solve to {}: {}", path, e); }
+    self.0.push(record);
+  }
 }
 
 #[derive(Component)]
@@ -110,6 +237,11 @@ struct ControlBinds {
 
   button_reset: Option<KeyCode>,
   button_scramble: Option<KeyCode>,
+  button_method: Option<KeyCode>,
+
+  // next move of a solution, and what the next beginner step is
+  button_hint: Option<KeyCode>,
+  button_hint_step: Option<KeyCode>,
 }
 
 impl Default for ControlBinds {
@@ -133,12 +265,16 @@ impl Default for ControlBinds {
 
       button_reset: Some(KeyCode::KeyR),
       button_scramble: Some(KeyCode::KeyT),
+      button_method: Some(KeyCode::KeyM),
+
+      button_hint: Some(KeyCode::Slash),
+      button_hint_step: Some(KeyCode::Period),
     }
   }
 }
 
 #[derive(Component, Default)]
-struct Block;
+pub struct Block;
 
 #[derive(Component)]
 struct Target {
@@ -166,7 +302,7 @@ impl Default for MovementNode {
 
 // used to reset cube positions
 #[derive(Component, Default)]
-struct DefaultPosition(Vec3);
+pub struct DefaultPosition(pub Vec3);
 
 
 
@@ -218,9 +354,9 @@ fn setup_cube(
   mut commands: Commands,
   assets: Res<AssetServer>,
 ) {
-  let cores = vec!["r", "b", "w", "o", "g", "y"];
+  let cores = ["r", "b", "w", "o", "g", "y"];
   let edges = vec!["rb", "yb", "ob", "wb", "rg", "yg", "og", "wg", "yr", "yo", "wr", "wo"];
-  let corners = vec!["wrb", "wrg", "wob", "wog", "yrb", "yrg", "yob", "yog"];
+  let corners = ["wrb", "wrg", "wob", "wog", "yrb", "yrg", "yob", "yog"];
 
   let all = corners.iter().chain(edges.iter().chain(cores.iter())).collect::<Vec<_>>();
 
@@ -234,7 +370,7 @@ fn setup_cube(
     let path = root.to_owned() + "/" + block + ".glb#Scene0";
     let part_handle = assets.load(path);
 
-    let (x_trans, y_trans, z_trans) = unpack_coords(&block, match root {
+    let (x_trans, y_trans, z_trans) = unpack_coords(block, match root {
       "edge" => UnpackBlocks::Edge,
       "corner" => UnpackBlocks::Corner,
       _ => UnpackBlocks::Center,
@@ -263,11 +399,16 @@ fn setup_cube(
 
 /* MARK: ROTATE CUBE
 */
+#[allow(clippy::too_many_arguments)]
 fn adjust_cube(
   kbd: Res<ButtonInput<KeyCode>>,
   mut cubes: Query<(&mut Transform, &Block, &mut MovementNode)>,
   mut agg_mov: ResMut<AggregateMovement>,
+  mut state: ResMut<CubeState>,
+  mut log: ResMut<SolveLog>,
   binds: Res<ControlSettings>,
+  game: Res<GameSettings>,
+  puzzle: Res<PuzzleType>,
 ) {
 
   if agg_mov.active { return };
@@ -282,24 +423,18 @@ fn adjust_cube(
 
   let button_prime = kbd.pressed(binds.settings.button_prime.unwrap());
 
-  let direction = if button_prime { 1.0 } else { -1.0 };
+  let amount = if agg_mov.double { 2 } else if button_prime { 3 } else { 1 };
   let axis = match (button_x, button_y, button_z) {
-    (true, false, false) => Vec3::X,
-    (false, true, false) => Vec3::Y,
-    (false, false, true) => Vec3::Z,
-    _ => Vec3::X
+    (true, false, false) => Axis::X,
+    (false, true, false) => Axis::Y,
+    (false, false, true) => Axis::Z,
+    _ => Axis::X
   };
-  
-  for (mut transform, _cube, mut move_node) in &mut cubes {
 
-    move_node.active = true;
-
-    let (tl, rt) = fetch_target(&mut transform, axis, direction, agg_mov.double);
-    move_node.target = Target { translation: tl, rotation: rt };
-  }
-
-  agg_mov.axis = axis;
-  agg_mov.direction = direction;
+  // a cuboid only turns over half way where its sides differ
+  let Some(mv) = puzzle.puzzle.fit(Move::new(Turn::Rotate(axis), amount)) else { agg_mov.active = false; return };
+  start_turn(&mut cubes, &mut agg_mov, &mut state, mv);
+  record_turn(&mut log, &game, mv);
 
   agg_mov.turn_timer.reset();
   agg_mov.double_timer.reset();
@@ -307,12 +442,17 @@ fn adjust_cube(
 
 /* MARK: REGULAR CTRL
  */
+#[allow(clippy::too_many_arguments)]
 fn cube_control(
   kbd: Res<ButtonInput<KeyCode>>,
   mut cubes: Query<(&mut Transform, &Block, &mut MovementNode)>,
   mut agg_mov: ResMut<AggregateMovement>,
+  mut state: ResMut<CubeState>,
+  mut log: ResMut<SolveLog>,
   binds: Res<ControlSettings>,
   mut game: ResMut<GameSettings>,
+  puzzle: Res<PuzzleType>,
+  bandage: Res<Bandage>,
 ) {
 
   if agg_mov.active { return }
@@ -322,56 +462,47 @@ fn cube_control(
     kbd.just_pressed(binds.settings.button_up_turn.unwrap()), kbd.just_pressed(binds.settings.button_down_turn.unwrap()),
     kbd.just_pressed(binds.settings.button_right_turn.unwrap()), kbd.just_pressed(binds.settings.button_left_turn.unwrap())
   );
-  if button_f || button_b || button_u || button_d || button_r || button_l { agg_mov.active = true; } else { return }
+  if !(button_f || button_b || button_u || button_d || button_r || button_l) { return }
 
-  if !game.pregame.finished() || !game.pregame.paused() { game.pregame.pause(); game.clock.reset(); }
-
-  let axis: Vec3;
-  let mut direction: f32;
-  let positive: bool;
-
-  if button_b || button_l || button_d { positive = false; } else { positive = true; }
-  let limit = if positive { 2.20 } else { -2.20 };
+  let positive = !(button_b || button_l || button_d);
+  let face = if button_f { Face::F } else if button_b { Face::B }
+    else if button_u { Face::U } else if button_d { Face::D }
+    else if button_r { Face::R } else { Face::L };
 
   let button_prime = kbd.pressed(binds.settings.button_prime.unwrap());
-  if button_prime { direction = -1.0 } else { direction = 1.0 };
-  
+  let amount = if agg_mov.double { 2 } else if button_prime { 3 } else { 1 };
+
   let button_wide = kbd.pressed(binds.settings.button_wide.unwrap());
   let button_middle = kbd.pressed(binds.settings.button_middle.unwrap());
-  if button_middle { 
-    direction *= -1.0; 
-    if button_b || button_f { direction *= -1.0; }
-  }
-  
-  // standardized speed cubing directions
-  if button_f || button_r || button_u { direction *= -1.0 };
-  
-  if button_f || button_b { axis = Vec3::Z; } else if button_u || button_d { axis = Vec3::Y; } else { axis = Vec3::X; }
 
-  for (mut transform, _cube, mut move_node) in &mut cubes {
+  // middle layer follows the positive face of the pressed axis
+  let mv = if button_middle {
+    let slice = match face.axis() { Axis::X => Slice::M, Axis::Y => Slice::E, Axis::Z => Slice::S };
+    let mv = Move::new(Turn::Slice(slice), amount);
+    if positive { mv } else { mv.inverse() }
+  }
+  else if button_wide { Move::new(Turn::Wide(face), amount) }
+  else { Move::new(Turn::Face(face), amount) };
 
-    let comparison = match axis {
-      Vec3::X => transform.translation.x,
-      Vec3::Y => transform.translation.y,
-      Vec3::Z => transform.translation.z,
-      _ => 0.0,
-    };
+  // cuboid sides that only fit back half way round turn 180°, slices a puzzle lacks do nothing
+  let Some(mv) = puzzle.puzzle.fit(mv) else { return };
+  agg_mov.active = true;
+  agg_mov.turn_timer.reset();
+  agg_mov.double_timer.reset();
 
-    if (!button_middle && comparison == limit) || ((button_wide || button_middle) && comparison == 0.0) {
-      move_node.active = true;
-      
-      let (tl, rt) = fetch_target(&mut transform, axis, direction, agg_mov.double);
-      move_node.target = Target { translation: tl, rotation: rt };
-    }
-    
+  // a turn that would split a bandaged block is refused, it neither counts nor starts the clock
+  if !bandage.allows(&state.cube, mv) {
+    start_jam(&mut cubes, &mut agg_mov, mv);
+    return
   }
 
-  agg_mov.axis = axis;
-  agg_mov.direction = direction;
-  agg_mov.positive = positive;
+  // first turn ends inspection and starts the clock
+  if !game.timing() { game.pregame.pause(); game.clock.reset(); }
 
-  agg_mov.turn_timer.reset();
-  agg_mov.double_timer.reset();
+  start_turn(&mut cubes, &mut agg_mov, &mut state, mv);
+  record_turn(&mut log, &game, mv);
+
+  agg_mov.positive = positive;
 }
 
 /* MARK: TOGGLE DOUBLE
@@ -382,7 +513,7 @@ fn toggle_double_turn(
   binds: Res<ControlSettings>,
 ) {
 
-  if agg_mov.scramble > 0 { return }
+  if !agg_mov.scramble.is_empty() { return }
   let mut double = agg_mov.double;
   let button_double = kbd.just_pressed(binds.settings.button_toggle_double.unwrap());
 
@@ -399,48 +530,159 @@ fn toggle_double_turn(
 
 /* MARK: SCRAMBLE CTRL
  */
+#[allow(clippy::too_many_arguments)]
 fn scramble_cube(
   kbd: Res<ButtonInput<KeyCode>>,
+  mut cubes: Query<(&mut Transform, &Block, &DefaultPosition, &mut MovementNode)>,
   mut agg_mov: ResMut<AggregateMovement>,
+  mut state: ResMut<CubeState>,
+  mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+  mut log: ResMut<SolveLog>,
+  mut trainer: ResMut<Trainer>,
+  mut fmc: ResMut<Fmc>,
+  blind: Res<Blind>,
+  supercube: Res<Supercube>,
+  puzzle: Res<PuzzleType>,
+  bandage: Res<Bandage>,
   binds: Res<ControlSettings>,
   game: Res<GameSettings>,
 ) {
   
   if binds.settings.button_scramble.map(|key| kbd.just_pressed(key)).unwrap_or(false) {
+    // trainer cases, fewest moves and blind scrambles are set up from solved, on the plain 3x3 only
+    let setup = if puzzle.puzzle == Puzzle::Cube && bandage.current().is_none() {
+      trainer.next_setup(&mut *rng)
+        .or_else(|| fmc.next_scramble(&mut *rng))
+        .or_else(|| blind.active.then(|| scramble::random_state(&mut *rng)))
+    } else {
+      None
+    };
+    let alg = match (setup, bandage.current()) {
+      (Some(setup), _) => {
+        *state = CubeState::default();
+        place_blocks(&mut cubes, &Alg::new());
+        setup
+      }
+      (None, _) if puzzle.puzzle != Puzzle::Cube => puzzle.puzzle.random_moves(&mut *rng, game.shuffle),
+      // only turns the blocks allow, from solved and long enough to leave it anywhere it can be
+      (None, Some(bandaging)) => {
+        *state = CubeState::default();
+        place_blocks(&mut cubes, &Alg::new());
+        bandaging.random_moves(&mut *rng, &Cube::solved(), game.shuffle.max(BANDAGE_SCRAMBLE_NUM))
+      }
+      // enough face turns to leave every centre twisted at random
+      (None, None) if supercube.active => scramble::random_moves(&mut *rng, game.shuffle.max(SUPERCUBE_SCRAMBLE_NUM)),
+      (None, None) => scramble::random_moves(&mut *rng, game.shuffle),
+    };
+    log.solving = false;
+    log.moves.clear();
+
     agg_mov.active = false;
-    agg_mov.scramble = game.shuffle;
+    agg_mov.jammed = false;
+    agg_mov.scramble = alg.into_iter().collect();
     agg_mov.speed = SCRAMBLE_SPEED;
+    agg_mov.pattern = false;
   }
   
 }
 
+// fewest moves undo and NISS jump the blocks to a new position once the turn in progress is done
+fn reposition_cube(
+  mut cubes: Query<(&mut Transform, &Block, &DefaultPosition, &mut MovementNode)>,
+  agg_mov: Res<AggregateMovement>,
+  mut state: ResMut<CubeState>,
+  mut fmc: ResMut<Fmc>,
+) {
+  if agg_mov.active || !agg_mov.scramble.is_empty() { return }
+  let Some(moves) = fmc.reposition.take() else { return };
+  place_blocks(&mut cubes, &moves);
+  *state = CubeState { cube: Cube::from_alg(&moves), centers: Centers::from_alg(&moves), moves };
+}
+
 /* MARK: RESET CTRL
  */
+#[allow(clippy::too_many_arguments)]
 fn reset_cube(
   kbd: Res<ButtonInput<KeyCode>>,
-  mut cubes: Query<(&mut Transform, &Block, &DefaultPosition, &mut MovementNode)>,
+  mut cubes: Query<(&mut Transform, &Block, &mut DefaultPosition, &mut MovementNode)>,
   mut agg_mov: ResMut<AggregateMovement>,
+  mut state: ResMut<CubeState>,
+  mut log: ResMut<SolveLog>,
+  mut trainer: ResMut<Trainer>,
+  mut demo: ResMut<Thistlethwaite>,
+  mut patterns: ResMut<Patterns>,
+  puzzle: Res<PuzzleType>,
+  bandage: Res<Bandage>,
+  supercube: Res<Supercube>,
   binds: Res<ControlSettings>,
 ) {
 
-  if !binds.settings.button_reset.map(|key| kbd.just_pressed(key)).unwrap_or(false) { return }
+  let pattern = patterns.pending.take();
+  // another puzzle, bandaging or supercube mode starts solved, with the blocks laid out for its dimensions
+  let switched = puzzle.is_changed() || bandage.is_changed() || supercube.is_changed();
+  if !binds.settings.button_reset.map(|key| kbd.just_pressed(key)).unwrap_or(false) && pattern.is_none() && !switched { return }
+
+  log.solving = false;
+  log.moves.clear();
+  trainer.stop();
+  demo.queue.clear();
 
   agg_mov.active = false;
-  agg_mov.scramble = 0;
+  agg_mov.jammed = false;
+  agg_mov.scramble.clear();
   agg_mov.speed = if agg_mov.double { DOUBLE_SPEED } else { TURN_SPEED };
   agg_mov.axis = Vec3::ZERO;
+  agg_mov.turn = None;
+  *state = CubeState::default();
 
-  for (mut transform, _cube, default, mut move_node) in &mut cubes {
+  for (mut transform, _cube, mut default, mut move_node) in &mut cubes {
+    if switched { default.0 = puzzle.home(default.0); }
     transform.translation = default.0;
     transform.rotation = Quat::IDENTITY;
 
     move_node.active = false;
   }
-  
+
+  // a chosen pattern plays out from solved like a scramble, unless the bandaging blocks it
+  if let Some(alg) = pattern {
+    let mut cube = Cube::solved();
+    for mv in &alg {
+      if !bandage.allows(&cube, *mv) { warn!("the bandaging blocks {} in the pattern", mv); return }
+      cube.apply_move(*mv);
+    }
+    agg_mov.scramble = puzzle.puzzle.fit_alg(&alg).into_iter().collect();
+    agg_mov.speed = SCRAMBLE_SPEED;
+    agg_mov.pattern = true;
+  }
 }
 
 
 
+/* MARK: DEMO TURN
+  solver turns played one after another with the same animation as the player's
+*/
+fn demo_turn(
+  mut cubes: Query<(&mut Transform, &Block, &mut MovementNode)>,
+  mut agg_mov: ResMut<AggregateMovement>,
+  mut state: ResMut<CubeState>,
+  mut demo: ResMut<Thistlethwaite>,
+  bandage: Res<Bandage>,
+) {
+  if agg_mov.active || !agg_mov.scramble.is_empty() { return }
+  let Some(mv) = demo.queue.pop_front() else { return };
+  // the demo solver knows nothing of bandaging, it stops at the first turn that is blocked
+  if !bandage.allows(&state.cube, mv) {
+    warn!("the bandaging blocks {}, stopping the demo", mv);
+    demo.queue.clear();
+    return
+  }
+
+  start_turn(&mut cubes, &mut agg_mov, &mut state, mv);
+  agg_mov.active = true;
+  agg_mov.turn_timer.reset();
+  agg_mov.double_timer.reset();
+}
+
 /* MARK: REGULAR TURN
  */
 fn rotate_cube(
@@ -449,19 +691,32 @@ fn rotate_cube(
   mut agg_mov: ResMut<AggregateMovement>,
 ) {
 
-  if !agg_mov.active || agg_mov.scramble > 0 { return }
+  if !agg_mov.active || !agg_mov.scramble.is_empty() { return }
 
   //agg_mov.speed = 420.0;
   let mut close = false;
 
   agg_mov.turn_timer.tick(time.delta());
   agg_mov.double_timer.tick(time.delta());
+  // the timers are set for quarter turns, or half turns in double mode, demo turns can be either
+  let quarters = agg_mov.turn.map(|mv| mv.quarter_turns().abs()).unwrap_or(1) as f32;
+  let scale = if agg_mov.double { quarters / 2.0 } else { quarters };
+
+  // a blocked layer rocks against the block holding it, dying away before the turn would have ended
+  let timer = if agg_mov.double { &agg_mov.double_timer } else { &agg_mov.turn_timer };
+  let jam = JAM_ANGLE.to_radians() * (timer.fraction() * std::f32::consts::TAU * 2.0).sin() * (1.0 - timer.fraction());
 
   for (mut transform, _cube, mut move_node) in &mut cubes {
 
     if move_node.active {
 
-      transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(agg_mov.axis, agg_mov.direction * agg_mov.speed.to_radians() * time.delta_seconds()));
+      if agg_mov.jammed {
+        transform.translation = move_node.target.translation;
+        transform.rotation = move_node.target.rotation;
+        transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(agg_mov.axis, jam));
+      } else {
+        transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(agg_mov.axis, agg_mov.direction * scale * agg_mov.speed.to_radians() * time.delta_seconds()));
+      }
 
       if (!agg_mov.double && agg_mov.turn_timer.just_finished()) ||
         (agg_mov.double && agg_mov.double_timer.just_finished()) 
@@ -475,7 +730,7 @@ fn rotate_cube(
     }
   }
 
-  if close { agg_mov.active = false; }
+  if close { agg_mov.active = false; agg_mov.jammed = false; }
     
 }
 
@@ -486,49 +741,33 @@ fn rotate_scramble(
   mut cubes: Query<(&mut Transform, &Block, &mut MovementNode)>,
   time: Res<Time>,
   mut agg_mov: ResMut<AggregateMovement>,
-  mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+  mut state: ResMut<CubeState>,
+  mut log: ResMut<SolveLog>,
   mut game: ResMut<GameSettings>,
+  mut trainer: ResMut<Trainer>,
 ) {
-  if agg_mov.scramble == 0 { return }
+  if agg_mov.scramble.is_empty() { return }
 
   if !agg_mov.active {
 
-    let (axis, positive, direction) = randomize_vars(&mut rng);
-    agg_mov.axis = axis;
-    agg_mov.direction = direction;
-    agg_mov.positive = positive;
-
-    let limit = if positive { 2.20 } else { -2.20 };
-
-    for (mut transform, _cube, mut move_node) in &mut cubes {
-
-      let comparison = match axis {
-        Vec3::X => transform.translation.x,
-        Vec3::Y => transform.translation.y,
-        _       => transform.translation.z
-      };
-      if comparison == limit {
-        let (tl, rt) = fetch_target(&mut transform, axis, direction, agg_mov.double);
-        move_node.target = Target { translation: tl, rotation: rt };
-        move_node.active = true;
-      }
-    }
+    let mv = *agg_mov.scramble.front().unwrap();
+    start_turn(&mut cubes, &mut agg_mov, &mut state, mv);
 
     agg_mov.active = true;
-    agg_mov.scramble -= 1;
     agg_mov.scramble_turn_timer.reset();
   } 
   
   if agg_mov.active {
-    let mut close = false;
     let mut stop_rotate = false;
 
     agg_mov.scramble_turn_timer.tick(time.delta());
+    // half turns cover twice the angle in the same delay
+    let quarters = agg_mov.turn.map(|mv| mv.quarter_turns().abs()).unwrap_or(1) as f32;
 
     for (mut transform, _cube, mut move_node) in &mut cubes {
 
       if move_node.active {
-        transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(agg_mov.axis, agg_mov.direction * agg_mov.speed.to_radians() * time.delta_seconds()));
+        transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(agg_mov.axis, agg_mov.direction * quarters * agg_mov.speed.to_radians() * time.delta_seconds()));
   
         if agg_mov.scramble_turn_timer.just_finished() {
           stop_rotate = true;
@@ -536,36 +775,240 @@ fn rotate_scramble(
   
           transform.translation = move_node.target.translation;
           transform.rotation = move_node.target.rotation;
-      
-          if agg_mov.scramble == 0 {
-            close = true;
-          }
         }
       }
     }
 
-    if stop_rotate { agg_mov.active = false; }
-    if close { 
+    if stop_rotate { 
       agg_mov.active = false; 
-      agg_mov.speed = TURN_SPEED; 
+      agg_mov.scramble.pop_front();
+    }
+    if stop_rotate && agg_mov.scramble.is_empty() { 
+      agg_mov.speed = if agg_mov.double { DOUBLE_SPEED } else { TURN_SPEED }; 
       agg_mov.axis = Vec3::ZERO;
       agg_mov.direction = 0.0;
       agg_mov.positive = true;
+      agg_mov.turn = None;
+      if std::mem::take(&mut agg_mov.pattern) { return }
 
       game.pregame.reset();
       game.pregame.unpause();
+      game.clock.reset();
+      game.clock.unpause();
+      // scrambles start from wherever the cube was left, so keep the full path from solved
+      log.scramble = state.moves.clone();
+      log.solving = true;
+      log.hints = 0;
+      trainer.scrambled(&state.cube);
     }
   }
     
 }
 
+/* MARK: REPLAY TURN
+  blocks follow the replay position: a single step forward is animated, anything else is placed directly
+*/
+#[derive(Default)]
+struct ReplayView {
+  // moves of the record currently on the blocks, None while the live cube is shown
+  shown: Option<usize>,
+  turn: Option<Move>,
+  timer: Timer,
+}
+
+fn replay_cube(
+  mut cubes: Query<(&mut Transform, &Block, &DefaultPosition, &mut MovementNode)>,
+  time: Res<Time>,
+  mut replay: ResMut<Replay>,
+  agg_mov: Res<AggregateMovement>,
+  state: Res<CubeState>,
+  mut view: Local<ReplayView>,
+) {
+
+  if replay.record.is_none() {
+    // closing the viewer puts the live cube back
+    if view.shown.take().is_some() {
+      view.turn = None;
+      place_blocks(&mut cubes, &state.moves);
+    }
+    return
+  }
+  // never cut into a turn or scramble that is still animating
+  if view.shown.is_none() && (agg_mov.active || !agg_mov.scramble.is_empty()) {
+    replay.record = None;
+    return
+  }
+
+  let Some(record) = &replay.record else { return };
+  let target = replay.current();
+
+  if let Some(mv) = view.turn {
+    view.timer.tick(time.delta().mul_f32(replay.speed));
+    let (axis, direction) = turn_axis(mv);
+    let quarters = mv.quarter_turns().abs() as f32;
+    // stepping back mid turn snaps it into place first
+    let finished = view.timer.finished() || view.shown.is_some_and(|shown| target < shown);
+
+    for (mut transform, _cube, _default, mut move_node) in &mut cubes {
+      if !move_node.active { continue }
+      if finished {
+        move_node.active = false;
+        transform.translation = move_node.target.translation;
+        transform.rotation = move_node.target.rotation;
+      } else {
+        transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(axis, direction * quarters * (TURN_SPEED * replay.speed).to_radians() * time.delta_seconds()));
+      }
+    }
+
+    if !finished { return }
+    view.turn = None;
+  }
+
+  match view.shown {
+    Some(shown) if shown == target => return,
+    Some(shown) if shown + 1 == target => {
+      let mv = record.moves[shown].mv;
+      let (axis, direction) = turn_axis(mv);
+      for (mut transform, _cube, _default, mut move_node) in &mut cubes {
+        if !in_turn(&transform, mv) { continue }
+        move_node.active = true;
+        let (tl, rt) = fetch_target(&mut transform, axis, direction, mv.quarter_turns().abs() == 2);
+        move_node.target = Target { translation: tl, rotation: rt };
+      }
+      view.turn = Some(mv);
+      view.timer = Timer::from_seconds(TURN_DELAY, TimerMode::Once);
+    }
+    _ => {
+      let moves: Alg = record.scramble.iter().copied().chain(record.moves[..target].iter().map(|m| m.mv)).collect::<Vec<_>>().into();
+      place_blocks(&mut cubes, &moves);
+    }
+  }
+  view.shown = Some(target);
+}
+
+// every block where `moves` from solved would leave it, without animating
+fn place_blocks(
+  cubes: &mut Query<(&mut Transform, &Block, &DefaultPosition, &mut MovementNode)>,
+  moves: &Alg,
+) {
+  for (mut transform, _cube, default, mut move_node) in cubes.iter_mut() {
+    transform.translation = default.0;
+    transform.rotation = Quat::IDENTITY;
+    move_node.active = false;
+  }
+
+  for mv in moves {
+    let (axis, direction) = turn_axis(*mv);
+    for (mut transform, _cube, _default, _move_node) in cubes.iter_mut() {
+      if !in_turn(&transform, *mv) { continue }
+      let (tl, rt) = fetch_target(&mut transform, axis, direction, mv.quarter_turns().abs() == 2);
+      transform.translation = tl;
+      transform.rotation = rt;
+    }
+  }
+}
+
+// next solving method, applies from the next finished solve
+fn change_method(
+  kbd: Res<ButtonInput<KeyCode>>,
+  binds: Res<ControlSettings>,
+  mut game: ResMut<GameSettings>,
+) {
+  if binds.settings.button_method.map(|key| kbd.just_pressed(key)).unwrap_or(false) {
+    game.method = game.method.next();
+  }
+}
+
+/* MARK: HINTS
+*/
+#[allow(clippy::too_many_arguments)]
+fn hint_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  binds: Res<ControlSettings>,
+  agg_mov: Res<AggregateMovement>,
+  state: Res<CubeState>,
+  time: Res<Time>,
+  mut log: ResMut<SolveLog>,
+  mut hint: ResMut<Hint>,
+  puzzle: Res<PuzzleType>,
+  bandage: Res<Bandage>,
+) {
+  hint.shown.tick(time.delta());
+  // a move hint stays until the cube moves, a step lasts as long as it is shown
+  if hint.shown.finished() { hint.step = None; }
+  if state.cube != hint.at { hint.mv = None; hint.task = None; }
+  if let Some(task) = &mut hint.task {
+    if let Some(solution) = block_on(poll_once(task)) {
+      hint.task = None;
+      match solution {
+        Ok(solution) => hint.mv = solution.iter().next().copied(),
+        Err(e) => warn!("no hint: {}", e),
+      }
+    }
+  }
+
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  let (next_move, next_step) = (pressed(binds.settings.button_hint), pressed(binds.settings.button_hint_step));
+  if !(next_move || next_step) || !agg_mov.scramble.is_empty() { return }
+  // the solver and the beginner steps only know the 3x3
+  if puzzle.puzzle != Puzzle::Cube { warn!("no hints for the {}", puzzle.puzzle.name()); return }
+
+  if next_move && !hint.thinking() {
+    // a bandaged cube needs a solution it can actually turn
+    let (cube, bandaging) = (state.cube, bandage.current().cloned());
+    hint.mv = None;
+    hint.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+      match bandaging {
+        Some(bandaging) => bandaging.solve(&cube),
+        None => solver::solve(&cube),
+      }
+    }));
+  }
+  if next_step {
+    hint.step = Some(match steps::next_step(&state.cube, Method::Beginner) {
+      Some((step, frame)) => format!("Next: {} ({} on the bottom)", step.goal(), color_name(frame.bottom)),
+      None => "Solved".to_string(),
+    });
+  }
+  hint.at = state.cube;
+  hint.shown.reset();
+  if log.solving { log.hints += 1; }
+}
+
+// outline of the layer to turn with an arrow around it the way it turns
+fn draw_hint(mut gizmos: Gizmos, hint: Res<Hint>, state: Res<CubeState>) {
+  let Some(mv) = hint.mv else { return };
+  if state.cube != hint.at { return }
+
+  let (axis, direction) = turn_axis(mv);
+  let layers = mv.layers();
+  let (Some(first), Some(last)) = (layers.iter().position(|l| *l), layers.iter().rposition(|l| *l)) else { return };
+  // blocks sit 2.2 apart, layer 0 on the negative side
+  let center = axis * (first + last) as f32 * 1.1 - axis * 2.2;
+  let depth = (last - first + 1) as f32 * 2.2 + 0.2;
+  let size = Vec3::splat(6.8) - axis * (6.8 - depth);
+  let color = Color::srgb(1.0, 0.9, 0.2);
+  gizmos.cuboid(Transform::from_translation(center).with_scale(size), color);
+
+  let angle = direction * mv.quarter_turns().abs() as f32 * std::f32::consts::FRAC_PI_2;
+  let start = axis.any_orthonormal_vector() * 4.6;
+  let points: Vec<Vec3> = (0..=24).map(|i| center + Quat::from_axis_angle(axis, angle * i as f32 / 24.0) * start).collect();
+  let head = points.len() - 3;
+  gizmos.linestrip(points[..=head].iter().copied(), color);
+  gizmos.arrow(points[head], points[points.len() - 1], color).with_tip_length(0.6);
+}
+
 fn pregame(
   mut game: ResMut<GameSettings>,
+  trainer: Res<Trainer>,
+  blind: Res<Blind>,
+  fmc: Res<Fmc>,
   time: Res<Time>,
 ) {
 
   if game.pregame.finished() || game.pregame.paused() { game.clock.tick(time.delta()); }
-  else { game.pregame.tick(time.delta()); }
+  // drills and blind memo have no inspection limit, the clock starts on the first turn
+  else if trainer.drilling.is_none() && !blind.active && !fmc.active { game.pregame.tick(time.delta()); }
 
   if game.pregame.just_finished() {
     game.pregame.pause();
@@ -574,43 +1017,131 @@ fn pregame(
   }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_cube(
-  cubes: Query<(&Transform, &Block, &DefaultPosition)>,
+  state: Res<CubeState>,
   agg_mov: Res<AggregateMovement>,
-  game: ResMut<GameSettings>,
+  mut game: ResMut<GameSettings>,
+  mut log: ResMut<SolveLog>,
+  mut history: ResMut<SolveHistory>,
+  mut trainer: ResMut<Trainer>,
+  mut blind: ResMut<Blind>,
+  fmc: Res<Fmc>,
+  supercube: Res<Supercube>,
+  shape: Res<PuzzleShape>,
+  puzzle: Res<PuzzleType>,
+  bandage: Res<Bandage>,
 ) {
-  
-  if agg_mov.active || game.clock.elapsed().as_secs_f32() == 0.0 { return }
-
-  for (transform, _cube, default) in &cubes {
-    let (rx, ry, rz) = transform.rotation.to_euler(EulerRot::XYZ);
-    if transform.translation != default.0 || rx != 0.0 || ry != 0.0 || rz != 0.0 {
-      println!("actual {}", transform.translation);
-      println!("recorded default {}", default.0);
-      println!("");
-      println!("actual rot {}", transform.rotation);
-      println!("default {}", Quat::IDENTITY);
-      println!("");
-      println!("");
-      return;
-    }
+  // a fewest moves attempt only ends on a submitted solution
+  if agg_mov.active || !log.solving || !game.timing() || fmc.attempting { return }
+  // trainer cases only look at the pieces, shape mods and supercubes are 3x3s
+  let solved = match puzzle.puzzle {
+    _ if trainer.mode.is_some() => trainer.solved(&state.cube),
+    Puzzle::Cube => shape.solved(&state) && (!supercube.active || state.centers.solved()),
+    other => other.solved(&state.cube),
+  };
+  if !solved { return }
+
+  game.clock.pause();
+  log.solving = false;
+  // drills and blind attempts only count towards their own statistics
+  let turns = Alg(log.moves.iter().map(|m| m.mv).collect()).metrics().htm;
+  if trainer.finish(game.clock.elapsed_secs(), turns) || blind.finish(game.clock.elapsed_secs(), true) {
+    log.moves.clear();
+    return
+  }
+  // the history, its splits and the replays are for the plain 3x3, other puzzles are only timed
+  let variant = if puzzle.puzzle != Puzzle::Cube { Some(puzzle.puzzle.name()) }
+    else if bandage.current().is_some() { Some("bandaged cube") }
+    else if supercube.active { Some("supercube") }
+    else if shape.shape != Shape::Standard { Some(shape.shape.name()) }
+    else { None };
+  if let Some(name) = variant {
+    log.moves.clear();
+    log.variant = Some((name, game.clock.elapsed_secs()));
+    return
   }
 
-  println!("WINNER WINNER CHICKEN DINNER, {}", game.clock.elapsed().as_secs());
+  log.variant = None;
+  let moves = std::mem::take(&mut log.moves);
+  let mut record = SolveRecord::new(log.scramble.clone(), moves, game.clock.elapsed_secs());
+  record.splits = steps::splits(&record, game.method);
+  record.hints = log.hints;
+  info!("solved: {:.2}s, {}, {:.2} TPS", record.time, record.metrics, record.tps(Metric::Etm));
+  history.push(HISTORY_FILE, record);
 }
 
 // MARK: UTIL
-fn randomize_vars(rng: &mut ResMut<GlobalEntropy<ChaCha8Rng>>) -> (Vec3, bool, f32) {
+fn axis_vec(axis: Axis) -> Vec3 {
+  match axis {
+    Axis::X => Vec3::X,
+    Axis::Y => Vec3::Y,
+    Axis::Z => Vec3::Z,
+  }
+}
 
-  let positive = rng.next_u32() % 2 == 0;
-  let axis = match rng.next_u32() % 3 {
-    0 => Vec3::X, 
-    1 => Vec3::Y, 
-    _ => Vec3::Z,
-  };
-  let direction = if rng.next_u32() % 2 == 0 { 1.0 } else { -1.0 };
+// rotations during inspection are kept at time zero so a replay starts from the same grip
+fn record_turn(log: &mut SolveLog, game: &GameSettings, mv: Move) {
+  if !log.solving { return }
+  log.moves.push(TimedMove { time: game.clock.elapsed_secs(), mv });
+}
+
+// rotation axis and direction of a turn on the blocks
+fn turn_axis(mv: Move) -> (Vec3, f32) {
+  (axis_vec(mv.axis()), mv.quarter_turns().signum() as f32)
+}
+
+// -2.2, 0.0, 2.2 along the axis -> layer 0, 1, 2, and -1.1, 1.1 -> 0, 2 on a side two layers deep
+fn in_turn(transform: &Transform, mv: Move) -> bool {
+  let along = transform.translation.dot(axis_vec(mv.axis()));
+  let layer = if along > 0.5 { 2 } else if along < -0.5 { 0 } else { 1 };
+  mv.layers()[layer]
+}
+
+/* MARK: START TURN
+  set targets for every block inside the turned layers, and record the move in the state model
+*/
+fn start_turn(
+  cubes: &mut Query<(&mut Transform, &Block, &mut MovementNode)>,
+  agg_mov: &mut AggregateMovement,
+  state: &mut CubeState,
+  mv: Move,
+) {
+  let (axis, direction) = turn_axis(mv);
+
+  for (mut transform, _cube, mut move_node) in cubes.iter_mut() {
+    if !in_turn(&transform, mv) { continue }
+
+    move_node.active = true;
+
+    let (tl, rt) = fetch_target(&mut transform, axis, direction, mv.quarter_turns().abs() == 2);
+    move_node.target = Target { translation: tl, rotation: rt };
+  }
+
+  agg_mov.axis = axis;
+  agg_mov.direction = direction;
+  agg_mov.turn = Some(mv);
+
+  state.cube.apply_move(mv);
+  state.centers.apply_move(mv);
+  state.moves.push(mv);
+  if state.cube.is_solved() && state.centers == Centers::default() { state.moves = Alg::new(); }
+}
 
-  (axis, positive, direction)
+// the layers of a blocked turn shake in place, their targets are where they already are
+fn start_jam(
+  cubes: &mut Query<(&mut Transform, &Block, &mut MovementNode)>,
+  agg_mov: &mut AggregateMovement,
+  mv: Move,
+) {
+  for (transform, _cube, mut move_node) in cubes.iter_mut() {
+    if !in_turn(&transform, mv) { continue }
+    move_node.active = true;
+    move_node.target = Target { translation: transform.translation, rotation: transform.rotation };
+  }
+  agg_mov.axis = turn_axis(mv).0;
+  agg_mov.turn = None;
+  agg_mov.jammed = true;
 }
 
 /* Use mutable transform to find exact translation and rotation after 90° rotation is completed in future (with time.delta()) */
@@ -623,7 +1154,7 @@ fn fetch_target(transform: &mut Transform, axis: Vec3, direction: f32, double: b
   let tl = transform.translation;
   let rt = transform.rotation;
   // return to same position as beginning of frame
-  transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(axis, direction * angle.to_radians() * -1.0));
+  transform.rotate_around(Vec3::ZERO, Quat::from_axis_angle(axis, -(direction * angle.to_radians())));
 
   ((tl * 10.0).round() / 10.0, rt)
 }
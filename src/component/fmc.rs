@@ -0,0 +1,263 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use rand_core::RngCore;
+
+use puzzle_cube_core::fmc::{self, Niss};
+use puzzle_cube_core::Alg;
+
+use crate::component::cube::SolveLog;
+use crate::component::focus::{Focus, TextFocus};
+use crate::component::replay::replay_closed;
+
+pub struct FmcComponent;
+
+impl Plugin for FmcComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Fmc::default());
+    app.insert_resource(FmcSettings::default());
+    app.add_systems(Startup, setup_fmc);
+    // typing happens before anything else reads the keyboard
+    app.add_systems(PreUpdate, solution_entry.after(InputSystem));
+    app.add_systems(Update, (
+      fmc_control.run_if(replay_closed),
+      update_fmc_text.run_if(any_with_component::<FmcText>),
+    ).chain());
+  }
+}
+
+/* MARK: FMC <RES>
+  an attempt runs from the scramble finishing until a solution is submitted or the hour is up.
+  turns are free exploration, kept on the side of the scramble being looked at
+*/
+#[derive(Resource)]
+pub struct Fmc {
+  pub active: bool,
+  pub attempting: bool,
+  pub scramble: Alg,
+  pub niss: Niss,
+  // counts down from the hour
+  pub clock: Timer,
+  // turns go in as premoves while set
+  pub premoves: bool,
+  // solution being typed
+  pub entry: Option<String>,
+  // moves from solved the blocks are to be put at, picked up by the cube
+  pub reposition: Option<Alg>,
+  // HTM of each attempt, None for a DNF, with why the last submission failed
+  pub results: Vec<Option<usize>>,
+  pub error: Option<String>,
+}
+
+impl Default for Fmc {
+  fn default() -> Self {
+    Fmc {
+      active: false,
+      attempting: false,
+      scramble: Alg::new(),
+      niss: Niss::default(),
+      clock: Timer::from_seconds(fmc::TIME_LIMIT, TimerMode::Once),
+      premoves: false,
+      entry: None,
+      reposition: None,
+      results: Vec::new(),
+      error: None,
+    }
+  }
+}
+
+impl Fmc {
+  // random state scramble for the next attempt, None outside fmc mode
+  pub fn next_scramble(&mut self, rng: &mut impl RngCore) -> Option<Alg> {
+    if !self.active { return None }
+    self.scramble = fmc::scramble(rng);
+    self.niss = Niss::default();
+    self.attempting = false;
+    self.entry = None;
+    Some(self.scramble.clone())
+  }
+
+  fn show_position(&mut self) { self.reposition = Some(self.niss.position(&self.scramble)); }
+
+  fn submit(&mut self, solution: &str) {
+    let result = fmc::score(&self.scramble, solution);
+    self.error = result.as_ref().err().map(|e| e.to_string());
+    self.results.push(result.ok());
+    self.attempting = false;
+    self.entry = None;
+  }
+}
+
+#[derive(Resource)]
+struct FmcSettings {
+  button_fmc: Option<KeyCode>,
+  button_undo: Option<KeyCode>,
+  button_niss: Option<KeyCode>,
+  button_premoves: Option<KeyCode>,
+  // opens the solution entry, Enter submits and Escape closes it
+  button_solution: Option<KeyCode>,
+}
+
+impl Default for FmcSettings {
+  fn default() -> Self {
+    FmcSettings {
+      button_fmc: Some(KeyCode::KeyG),
+      button_undo: Some(KeyCode::KeyZ),
+      button_niss: Some(KeyCode::KeyI),
+      button_premoves: Some(KeyCode::KeyH),
+      button_solution: Some(KeyCode::Tab),
+    }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn fmc_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  time: Res<Time>,
+  settings: Res<FmcSettings>,
+  mut fmc: ResMut<Fmc>,
+  mut log: ResMut<SolveLog>,
+  mut solving: Local<bool>,
+) {
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+
+  if pressed(settings.button_fmc) {
+    fmc.active = !fmc.active;
+    fmc.attempting = false;
+    fmc.entry = None;
+  }
+
+  // our scramble has just finished, rather than a trainer case
+  if fmc.active && log.solving && !*solving && log.scramble == fmc.scramble {
+    fmc.attempting = true;
+    fmc.clock.reset();
+    fmc.error = None;
+  }
+  *solving = log.solving;
+  // a reset or a new scramble drops the attempt
+  if !fmc.attempting || !log.solving {
+    fmc.attempting = false;
+    return
+  }
+
+  // turns are taken off the log as they are made, the attempt is never timed as a solve
+  for timed in std::mem::take(&mut log.moves) {
+    if fmc.premoves {
+      fmc.niss.premove(timed.mv);
+      fmc.show_position();
+    } else {
+      fmc.niss.push(timed.mv);
+    }
+  }
+
+  if pressed(settings.button_undo) && fmc.niss.undo().is_some() { fmc.show_position(); }
+  if pressed(settings.button_niss) {
+    fmc.niss.switch();
+    fmc.show_position();
+  }
+  if pressed(settings.button_premoves) { fmc.premoves = !fmc.premoves; }
+
+  fmc.clock.tick(time.delta());
+  if fmc.clock.just_finished() {
+    // time is up, whatever is typed is handed in
+    let entry = fmc.entry.take().unwrap_or_default();
+    fmc.submit(&entry);
+    log.solving = false;
+  }
+}
+
+/* MARK: SOLUTION ENTRY
+  text typed while the entry is open is kept from the rest of the controls
+*/
+fn solution_entry(
+  mut events: EventReader<KeyboardInput>,
+  mut kbd: ResMut<ButtonInput<KeyCode>>,
+  settings: Res<FmcSettings>,
+  mut focus: ResMut<TextFocus>,
+  mut fmc: ResMut<Fmc>,
+  mut log: ResMut<SolveLog>,
+) {
+  let open = settings.button_solution.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  let mut entering = fmc.entry.is_some();
+  match focus.enter(TextFocus::Fmc, &mut entering, open && fmc.attempting, &mut events, &mut kbd) {
+    Focus::Typing => {}
+    // starts out as the solution explored so far
+    Focus::Opened => { fmc.entry = Some(fmc.niss.solution().to_string()); return }
+    Focus::Elsewhere => return,
+  }
+
+  for event in events.read() {
+    if event.state != ButtonState::Pressed { continue }
+    let Some(entry) = fmc.entry.as_mut() else { break };
+    match &event.logical_key {
+      Key::Character(text) => entry.push_str(text),
+      Key::Space => entry.push(' '),
+      Key::Backspace => { entry.pop(); }
+      Key::Escape => fmc.entry = None,
+      Key::Enter => {
+        let solution = entry.clone();
+        fmc.submit(&solution);
+        log.solving = false;
+      }
+      _ => {}
+    }
+  }
+  kbd.reset_all();
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct FmcText;
+
+fn setup_fmc(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        left: Val::Px(12.0),
+        bottom: Val::Px(12.0),
+        ..default()
+      }),
+    FmcText,
+  ));
+}
+
+fn update_fmc_text(
+  fmc: Res<Fmc>,
+  mut text: Query<&mut Text, With<FmcText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if fmc.active {
+    lines.push("Fewest moves".to_string());
+    if fmc.attempting {
+      let left = fmc.clock.remaining_secs().ceil() as u32;
+      lines.push(format!("{}:{:02} left", left / 60, left % 60));
+      lines.push(format!("scramble {}", fmc.scramble));
+      let side = if fmc.niss.on_inverse { "inverse" } else { "normal" };
+      lines.push(format!("{} side (I to switch, Z to undo)", side));
+      lines.push(format!("normal {}", fmc.niss.normal));
+      lines.push(format!("inverse {}", fmc.niss.inverse));
+      lines.push(if fmc.premoves { "turns are premoves (H)" } else { "H for premoves" }.to_string());
+      match &fmc.entry {
+        Some(entry) => lines.push(format!("solution: {}_ (Enter to submit, Escape to close)", entry)),
+        None => lines.push("Tab to write the solution".to_string()),
+      }
+    } else {
+      lines.push("T to scramble".to_string());
+    }
+
+    if let Some(last) = fmc.results.last() {
+      let result = last.map_or("DNF".to_string(), |moves| format!("{} HTM", moves));
+      lines.push(format!("last {}", result));
+      if let Some(error) = &fmc.error { lines.push(error.clone()); }
+      if let Some(best) = fmc.results.iter().flatten().min() { lines.push(format!("best {} HTM", best)); }
+    }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
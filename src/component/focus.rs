@@ -0,0 +1,58 @@
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::*;
+
+/* MARK: TEXT FOCUS <RES>
+  the panel typed text goes to. only one holds it at a time, the others leave the keyboard alone
+  and do not open until it is given back
+*/
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFocus {
+  #[default]
+  None,
+  Fmc,
+  Library,
+  Patterns,
+  Analysis,
+  Commutator,
+  AlgGen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+  // another panel has the keyboard, or this one is closed
+  Elsewhere,
+  // opened this frame, the key that opened it is not typed
+  Opened,
+  Typing,
+}
+
+impl TextFocus {
+  /* first thing in a panel's entry system. a panel that closed itself gives the focus back, one
+    that is open or whose key is pressed takes it when nobody has it. keys only reach the panel
+    that has the focus
+  */
+  pub fn enter(
+    &mut self,
+    panel: TextFocus,
+    open: &mut bool,
+    pressed: bool,
+    events: &mut EventReader<KeyboardInput>,
+    kbd: &mut ButtonInput<KeyCode>,
+  ) -> Focus {
+    if *self == panel && !*open { *self = TextFocus::None; }
+    if *self == TextFocus::None && (*open || pressed) {
+      *self = panel;
+      if !*open {
+        *open = true;
+        events.clear();
+        kbd.reset_all();
+        return Focus::Opened
+      }
+    }
+    if *self != panel {
+      events.clear();
+      return Focus::Elsewhere
+    }
+    Focus::Typing
+  }
+}
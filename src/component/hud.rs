@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use puzzle_cube_core::{record, Alg, Metric, Metrics, SolveRecord};
+
+use crate::component::blind::Blind;
+use crate::component::cube::{GameSettings, Hint, SolveHistory, SolveLog};
+use crate::component::fmc::Fmc;
+use crate::component::trainer::Trainer;
+
+pub struct HudComponent;
+
+impl Plugin for HudComponent {
+  fn build(&self, app: &mut App) {
+    app.add_systems(Startup, setup_hud);
+    app.add_systems(Update, update_hud.run_if(any_with_component::<HudText>));
+  }
+}
+
+const HUD_FONT_SIZE: f32 = 20.0;
+const HUD_MARGIN: f32 = 12.0;
+
+#[derive(Component)]
+struct HudText;
+
+fn setup_hud(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: HUD_FONT_SIZE, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(HUD_MARGIN),
+        left: Val::Px(HUD_MARGIN),
+        ..default()
+      }),
+    HudText,
+  ));
+}
+
+/* MARK: UPDATE
+  inspection countdown, then live time and move counts, then the stats of the last solve
+*/
+#[allow(clippy::too_many_arguments)]
+fn update_hud(
+  mut text: Query<&mut Text, With<HudText>>,
+  game: Res<GameSettings>,
+  log: Res<SolveLog>,
+  history: Res<SolveHistory>,
+  trainer: Res<Trainer>,
+  blind: Res<Blind>,
+  fmc: Res<Fmc>,
+  hint: Res<Hint>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let content = if fmc.attempting {
+    let left = fmc.clock.remaining_secs().ceil() as u32;
+    format!("FMC {}:{:02}\n{} HTM explored", left / 60, left % 60, fmc.niss.solution().metrics().htm)
+  } else if log.solving && !game.timing() && trainer.drilling.is_some() {
+    "Ready, the first turn starts the clock".to_string()
+  } else if log.solving && !game.timing() && blind.active {
+    format!("Memo {:.2}", blind.memo.elapsed_secs())
+  } else if log.solving && !game.timing() {
+    format!("Inspection {:.0}", game.pregame.remaining_secs().ceil())
+  } else if log.solving {
+    let metrics = Alg(log.moves.iter().map(|m| m.mv).collect()).metrics();
+    stats(game.clock.elapsed_secs(), &metrics)
+  } else if let Some((name, time)) = log.variant {
+    format!("Solved the {} in {:.2}s", name, time)
+  } else if let Some(last) = history.0.last() {
+    format!("Solved {} (#{}){}{}", stats(last.time, &last.metrics), history.0.len(), hints(last.hints), splits(last))
+  } else {
+    String::new()
+  };
+  let mut content = format!("{}\n{}", game.method, content);
+  if hint.thinking() { content += "\nHint: thinking…"; }
+  if let Some(mv) = hint.mv { content += &format!("\nHint: {}", mv); }
+  if let Some(step) = &hint.step { content += &format!("\n{}", step); }
+
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
+
+fn stats(time: f32, metrics: &Metrics) -> String {
+  let counts: Vec<String> = Metric::ALL.iter()
+    .map(|m| format!("{} {} ({:.2} TPS)", metrics.get(*m), m.name(), record::tps(metrics.get(*m), time)))
+    .collect();
+  format!("{:.2}s\n{}", time, counts.join("\n"))
+}
+
+fn hints(count: usize) -> String {
+  match count {
+    0 => String::new(),
+    1 => "\nwith 1 hint".to_string(),
+    n => format!("\nwith {} hints", n),
+  }
+}
+
+// cumulative time, time and moves spent on each step
+fn splits(record: &SolveRecord) -> String {
+  let mut previous = 0.0;
+  record.splits.iter().map(|split| {
+    let line = format!("\n{:<6} {:>6.2}s (+{:.2}) {} HTM", split.step.to_string(), split.time, split.time - previous, split.metrics.htm);
+    previous = split.time;
+    line
+  }).collect()
+}
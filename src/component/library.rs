@@ -0,0 +1,411 @@
+use std::fs;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::render::camera::{ClearColorConfig, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+
+use puzzle_cube_core::cube::{sticker_coords, FACELETS};
+use puzzle_cube_core::library::{Entry, Library};
+use puzzle_cube_core::{Alg, Cube, Face, Move};
+
+use crate::component::cube::CubeState;
+use crate::component::focus::{Focus, TextFocus};
+use crate::component::trainer::face_color;
+
+pub struct LibraryComponent;
+
+impl Plugin for LibraryComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(AlgLibrary::load(LIBRARY_FILE));
+    app.insert_resource(LibrarySettings::default());
+    app.add_systems(Startup, (setup_library, setup_preview));
+    // typing happens before anything else reads the keyboard
+    app.add_systems(PreUpdate, library_entry.after(InputSystem));
+    app.add_systems(Update, (
+      animate_preview.run_if(resource_exists::<Preview>),
+      place_preview_camera.run_if(any_with_component::<PreviewCamera>),
+      update_library_text.run_if(any_with_component::<LibraryText>),
+    ).chain());
+  }
+}
+
+// the player's own algorithms and favourites, one per line
+const LIBRARY_FILE: &str = "algs.txt";
+
+// results listed around the selected one
+const LISTED_ALGS: usize = 12;
+
+// preview cube: quarter turns per second, seconds between loops, size on screen in pixels
+const PREVIEW_SPEED: f32 = 3.0;
+const PREVIEW_PAUSE: f32 = 1.2;
+const PREVIEW_SIZE: f32 = 240.0;
+const PREVIEW_LAYER: usize = 1;
+
+/* MARK: LIBRARY <RES>
+  searched by name or moves as they are typed, or by what finishes the step the cube is at
+*/
+#[derive(Resource)]
+pub struct AlgLibrary {
+  pub library: Library,
+  pub open: bool,
+  pub query: String,
+  pub recognising: bool,
+  // entries listed, with the U turn to do first when recognised
+  pub results: Vec<(usize, Option<Move>)>,
+  pub selected: usize,
+  pub message: Option<String>,
+}
+
+impl AlgLibrary {
+  // the bundled algorithms with the player's file on top, bad lines are skipped
+  fn load(path: &str) -> AlgLibrary {
+    let mut library = Library::bundled();
+    if let Ok(text) = fs::read_to_string(path) {
+      let (user, errors) = Library::parse(&text);
+      for (line, e) in errors { warn!("skipping algorithm on line {} of {}: {}", line, path, e); }
+      library.merge(user);
+    }
+    let mut loaded = AlgLibrary {
+      library, open: false, query: String::new(), recognising: false, results: Vec::new(), selected: 0, message: None,
+    };
+    loaded.refresh(&Cube::default());
+    loaded
+  }
+
+  fn save(&self, path: &str) {
+    let lines: String = self.library.user_entries().map(|entry| format!("{}\n", entry)).collect();
+    if let Err(e) = fs::write(path, lines) { warn!("could not save algorithms to {}: {}", path, e); }
+  }
+
+  fn refresh(&mut self, cube: &Cube) {
+    self.results = if self.recognising {
+      self.library.recognise(cube)
+    } else {
+      self.library.search(&self.query).into_iter().map(|i| (i, None)).collect()
+    };
+    self.selected = self.selected.min(self.results.len().saturating_sub(1));
+  }
+
+  // the selected algorithm with its U turn in front
+  fn selected_alg(&self) -> Option<Alg> {
+    let (i, auf) = self.results.get(self.selected)?;
+    Some(Alg(auf.iter().copied().collect()).concat(&self.library.entries[*i].alg))
+  }
+}
+
+#[derive(Resource)]
+struct LibrarySettings {
+  // opens the library, and closes it again
+  button_open: Option<KeyCode>,
+  button_favourite: Option<KeyCode>,
+  // adds what is typed, as "set name: moves"
+  button_add: Option<KeyCode>,
+}
+
+impl Default for LibrarySettings {
+  fn default() -> Self {
+    LibrarySettings {
+      button_open: Some(KeyCode::F8),
+      button_favourite: Some(KeyCode::F9),
+      button_add: Some(KeyCode::F10),
+    }
+  }
+}
+
+/* MARK: CONTROLS
+  while the library is open every key goes to it, the cube stays where it is
+*/
+fn library_entry(
+  mut events: EventReader<KeyboardInput>,
+  mut kbd: ResMut<ButtonInput<KeyCode>>,
+  settings: Res<LibrarySettings>,
+  state: Res<CubeState>,
+  mut focus: ResMut<TextFocus>,
+  mut library: ResMut<AlgLibrary>,
+  mut preview: Option<ResMut<Preview>>,
+) {
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  let (open, favourite, add) = (pressed(settings.button_open), pressed(settings.button_favourite), pressed(settings.button_add));
+  match focus.enter(TextFocus::Library, &mut library.open, open, &mut events, &mut kbd) {
+    Focus::Typing => {}
+    Focus::Opened => { library.refresh(&state.cube); return }
+    Focus::Elsewhere => return,
+  }
+
+  let mut changed = false;
+  for event in events.read() {
+    if event.state != ButtonState::Pressed { continue }
+    match &event.logical_key {
+      Key::Character(text) if !library.recognising => { library.query.push_str(text); changed = true; }
+      Key::Space if !library.recognising => { library.query.push(' '); changed = true; }
+      Key::Backspace => { library.query.pop(); changed = true; }
+      Key::ArrowUp => library.selected = library.selected.saturating_sub(1),
+      Key::ArrowDown => library.selected = (library.selected + 1).min(library.results.len().saturating_sub(1)),
+      Key::Tab => {
+        library.recognising = !library.recognising;
+        changed = true;
+      }
+      Key::Enter => {
+        if let (Some(alg), Some(preview)) = (library.selected_alg(), preview.as_mut()) { preview.play(alg); }
+      }
+      Key::Escape => library.open = false,
+      _ => {}
+    }
+  }
+  if changed { library.refresh(&state.cube); }
+
+  if favourite {
+    if let Some((i, _)) = library.results.get(library.selected).copied() {
+      let entry = &mut library.library.entries[i];
+      entry.favourite = !entry.favourite;
+      library.save(LIBRARY_FILE);
+    }
+  }
+  if add {
+    library.message = Some(match Entry::from_input(&library.query) {
+      Ok(entry) => {
+        let name = format!("{} {}", entry.set, entry.name);
+        match library.library.add(entry) {
+          Ok(_) => {
+            library.save(LIBRARY_FILE);
+            library.query = name.clone();
+            library.refresh(&state.cube);
+            format!("added {}", name)
+          }
+          Err(e) => e.to_string(),
+        }
+      }
+      Err(e) => e.to_string(),
+    });
+  }
+  if open {
+    library.open = false;
+    library.message = None;
+  }
+  kbd.reset_all();
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct LibraryText;
+
+fn setup_library(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Percent(25.0),
+        right: Val::Px(12.0),
+        ..default()
+      }),
+    LibraryText,
+  ));
+}
+
+fn update_library_text(
+  library: Res<AlgLibrary>,
+  mut text: Query<&mut Text, With<LibraryText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if library.open {
+    lines.push("Algorithms (F8 to close)".to_string());
+    lines.push(if library.recognising {
+      format!("{} finishing the step on the cube (Tab to search)", library.results.len())
+    } else {
+      format!("search: {}_ ({} found, Tab to recognise)", library.query, library.results.len())
+    });
+    let first = library.selected.saturating_sub(LISTED_ALGS / 2).min(library.results.len().saturating_sub(LISTED_ALGS));
+    for (at, (i, auf)) in library.results.iter().enumerate().skip(first).take(LISTED_ALGS) {
+      let entry = &library.library.entries[*i];
+      let marker = if at == library.selected { ">" } else { " " };
+      let star = if entry.favourite { "*" } else { " " };
+      let auf = auf.map_or(String::new(), |mv| format!("({}) ", mv));
+      lines.push(format!("{}{} {:<4} {:<10} {}{}", marker, star, entry.set.name(), entry.name, auf, entry.alg));
+    }
+    lines.push("Enter to preview, F9 favourite, F10 to add 'set name: moves'".to_string());
+    if let Some(message) = &library.message { lines.push(message.clone()); }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
+
+/* MARK: PREVIEW <RES>
+  a small cube in its own corner of the screen, set up so the algorithm solves it and
+  played on a loop
+*/
+#[derive(Resource)]
+struct Preview {
+  cube: Cube,
+  alg: Alg,
+  // move being animated and how far into it, 0 to 1
+  step: usize,
+  progress: f32,
+  pause: Timer,
+  colors: [Handle<StandardMaterial>; 6],
+}
+
+impl Preview {
+  fn play(&mut self, alg: Alg) {
+    self.cube = Cube::from_alg(&alg.inverse());
+    self.alg = alg;
+    self.step = 0;
+    self.progress = 0.0;
+    self.pause.reset();
+  }
+}
+
+#[derive(Component)]
+struct PreviewCamera;
+
+// cubie position, -1 to 1 along each axis, and where the piece rests
+#[derive(Component)]
+struct PreviewPiece {
+  position: [i8; 3],
+  home: Transform,
+}
+
+// facelet shown
+#[derive(Component)]
+struct PreviewSticker(usize);
+
+fn setup_preview(
+  mut commands: Commands,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+  let layer = RenderLayers::layer(PREVIEW_LAYER);
+  commands.spawn((
+    Camera3dBundle {
+      camera: Camera {
+        order: 1,
+        is_active: false,
+        clear_color: ClearColorConfig::Custom(Color::srgb(0.16, 0.17, 0.19)),
+        ..default()
+      },
+      transform: Transform::from_xyz(4.0, 4.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+      ..default()
+    },
+    layer.clone(),
+    PreviewCamera,
+  ));
+
+  let unlit = |color: Color| StandardMaterial { base_color: color, unlit: true, ..default() };
+  let body = meshes.add(Cuboid::new(0.96, 0.96, 0.96));
+  let black = materials.add(unlit(Color::srgb(0.05, 0.05, 0.05)));
+  for x in -1..=1i8 {
+    for y in -1..=1i8 {
+      for z in -1..=1i8 {
+        if (x, y, z) == (0, 0, 0) { continue }
+        let home = Transform::from_xyz(x as f32, y as f32, z as f32);
+        commands.spawn((
+          PbrBundle { mesh: body.clone(), material: black.clone(), transform: home, ..default() },
+          PreviewPiece { position: [x, y, z], home },
+          layer.clone(),
+        ));
+      }
+    }
+  }
+
+  let colors = Face::ALL.map(|face| materials.add(unlit(face_color(face))));
+  for index in 0..FACELETS {
+    let (position, normal) = sticker_coords(index);
+    let at = Vec3::from_array(position.map(|p| p as f32));
+    let out = Vec3::from_array(normal.map(|n| n as f32));
+    let size = Vec3::splat(0.84) - out.abs() * 0.8;
+    let home = Transform::from_translation(at + out * 0.49);
+    commands.spawn((
+      PbrBundle {
+        mesh: meshes.add(Cuboid::from_size(size)),
+        material: colors[index / 9].clone(),
+        transform: home,
+        ..default()
+      },
+      PreviewPiece { position, home },
+      PreviewSticker(index),
+      layer.clone(),
+    ));
+  }
+
+  commands.insert_resource(Preview {
+    cube: Cube::default(),
+    alg: Alg::new(),
+    step: 0,
+    progress: 0.0,
+    pause: Timer::from_seconds(PREVIEW_PAUSE, TimerMode::Once),
+    colors,
+  });
+}
+
+fn animate_preview(
+  time: Res<Time>,
+  library: Res<AlgLibrary>,
+  mut preview: ResMut<Preview>,
+  mut pieces: Query<(&mut Transform, &PreviewPiece)>,
+  mut stickers: Query<(&PreviewSticker, &mut Handle<StandardMaterial>)>,
+) {
+  if !library.open || preview.alg.is_empty() { return }
+
+  let turning = preview.alg.0.get(preview.step).copied();
+  match turning {
+    Some(mv) => {
+      let quarters = mv.quarter_turns().unsigned_abs().max(1) as f32;
+      preview.progress += time.delta_seconds() * PREVIEW_SPEED / quarters;
+      if preview.progress >= 1.0 {
+        preview.cube.apply_move(mv);
+        preview.step += 1;
+        preview.progress = 0.0;
+      }
+    }
+    None => {
+      preview.pause.tick(time.delta());
+      if preview.pause.finished() {
+        let alg = preview.alg.clone();
+        preview.play(alg);
+      }
+    }
+  }
+
+  // the layers of the move under way, turned part of the way
+  let turn = preview.alg.0.get(preview.step).filter(|_| preview.progress > 0.0).map(|mv| {
+    let angle = mv.quarter_turns() as f32 * std::f32::consts::FRAC_PI_2 * preview.progress;
+    let axis = mv.axis().index();
+    (*mv, Quat::from_axis_angle(Vec3::AXES[axis], angle), axis)
+  });
+  for (mut transform, piece) in &mut pieces {
+    *transform = piece.home;
+    let Some((mv, rotation, axis)) = turn else { continue };
+    if mv.layers()[(piece.position[axis] + 1) as usize] { transform.rotate_around(Vec3::ZERO, rotation); }
+  }
+
+  let colors = preview.cube.home_colors();
+  for (sticker, mut material) in &mut stickers {
+    let color = &preview.colors[colors[sticker.0].index()];
+    if *material != *color { *material = color.clone(); }
+  }
+}
+
+// bottom right corner of the window, only drawn while something is being previewed
+fn place_preview_camera(
+  library: Res<AlgLibrary>,
+  preview: Option<Res<Preview>>,
+  windows: Query<&Window, With<PrimaryWindow>>,
+  mut cameras: Query<&mut Camera, With<PreviewCamera>>,
+) {
+  let Ok(mut camera) = cameras.get_single_mut() else { return };
+  let Ok(window) = windows.get_single() else { return };
+  camera.is_active = library.open && preview.is_some_and(|preview| !preview.alg.is_empty());
+  if !camera.is_active { return }
+
+  let scale = window.scale_factor();
+  let size = UVec2::splat((PREVIEW_SIZE * scale) as u32).min(window.physical_size());
+  let margin = (12.0 * scale) as u32;
+  let corner = window.physical_size().saturating_sub(size + UVec2::splat(margin));
+  camera.viewport = Some(Viewport { physical_position: corner, physical_size: size, ..default() });
+}
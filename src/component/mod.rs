@@ -1,2 +1,19 @@
 pub mod cube;
-pub mod camera;
\ No newline at end of file
+pub mod camera;
+pub mod hud;
+pub mod replay;
+pub mod trainer;
+pub mod blind;
+pub mod fmc;
+pub mod optimal;
+pub mod thistlethwaite;
+pub mod library;
+pub mod patterns;
+pub mod analysis;
+pub mod commutator;
+pub mod alggen;
+pub mod focus;
+pub mod supercube;
+pub mod shapes;
+pub mod puzzles;
+pub mod bandage;
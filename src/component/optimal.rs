@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy::time::Stopwatch;
+
+use puzzle_cube_core::solver::optimal::{self, Progress};
+use puzzle_cube_core::solver::SolveError;
+use puzzle_cube_core::{Alg, Cube, Metric};
+
+use crate::component::cube::CubeState;
+use crate::component::replay::replay_closed;
+
+pub struct OptimalComponent;
+
+impl Plugin for OptimalComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(OptimalSolver::default());
+    app.insert_resource(OptimalSettings::default());
+    app.add_systems(Startup, setup_optimal);
+    app.add_systems(Update, (
+      optimal_control.run_if(replay_closed),
+      poll_optimal,
+      update_optimal_text.run_if(any_with_component::<OptimalText>),
+    ).chain());
+  }
+}
+
+// search running in the background and the handle to follow or cancel it
+type Search = (Task<Result<Alg, SolveError>>, Arc<Progress>);
+
+/* MARK: OPTIMAL <RES>
+  shortest solution of the cube as it stands, searched on a background thread. the first
+  search also builds the pattern databases, which takes a while
+*/
+#[derive(Resource)]
+pub struct OptimalSolver {
+  pub metric: Metric,
+  task: Option<Search>,
+  pub elapsed: Stopwatch,
+  // position the last search was for, and what it found
+  pub position: Cube,
+  pub result: Option<Result<Alg, SolveError>>,
+}
+
+impl Default for OptimalSolver {
+  fn default() -> Self {
+    OptimalSolver { metric: Metric::Htm, task: None, elapsed: Stopwatch::new(), position: Cube::default(), result: None }
+  }
+}
+
+impl OptimalSolver {
+  pub fn running(&self) -> bool { self.task.is_some() }
+
+  fn start(&mut self, cube: Cube) {
+    let progress = Arc::new(Progress::default());
+    let shared = progress.clone();
+    let metric = self.metric;
+    let task = AsyncComputeTaskPool::get().spawn(async move { optimal::solve(&cube, metric, &shared) });
+    self.task = Some((task, progress));
+    self.elapsed.reset();
+    self.position = cube;
+    self.result = None;
+  }
+
+  // the search stops at its next check, the task is kept until it has
+  fn cancel(&self) {
+    if let Some((_, progress)) = &self.task { progress.cancel(); }
+  }
+}
+
+#[derive(Resource)]
+struct OptimalSettings {
+  // starts a search, or cancels the one running
+  button_solve: Option<KeyCode>,
+  button_metric: Option<KeyCode>,
+}
+
+impl Default for OptimalSettings {
+  fn default() -> Self {
+    OptimalSettings {
+      button_solve: Some(KeyCode::F5),
+      button_metric: Some(KeyCode::F6),
+    }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn optimal_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<OptimalSettings>,
+  state: Res<CubeState>,
+  mut solver: ResMut<OptimalSolver>,
+) {
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+
+  if pressed(settings.button_solve) {
+    if solver.running() { solver.cancel(); } else { solver.start(state.cube); }
+  }
+  if pressed(settings.button_metric) && !solver.running() {
+    solver.metric = if solver.metric == Metric::Htm { Metric::Qtm } else { Metric::Htm };
+  }
+}
+
+fn poll_optimal(time: Res<Time>, mut solver: ResMut<OptimalSolver>) {
+  let Some((task, _)) = &mut solver.task else { return };
+  let Some(result) = block_on(poll_once(task)) else {
+    solver.elapsed.tick(time.delta());
+    return
+  };
+  solver.task = None;
+  solver.result = Some(result);
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct OptimalText;
+
+fn setup_optimal(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(12.0),
+        left: Val::Percent(35.0),
+        ..default()
+      }),
+    OptimalText,
+  ));
+}
+
+fn update_optimal_text(
+  solver: Res<OptimalSolver>,
+  state: Res<CubeState>,
+  mut text: Query<&mut Text, With<OptimalText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let metric = solver.metric.name();
+  let content = if let Some((_, progress)) = &solver.task {
+    let elapsed = solver.elapsed.elapsed_secs();
+    if progress.cancelled() {
+      "Optimal, cancelling".to_string()
+    } else if progress.databases() < 1.0 {
+      format!("Optimal {}, building tables {:.0}% ({:.0}s)\nF5 to cancel", metric, progress.databases() * 100.0, elapsed)
+    } else {
+      format!("Optimal {}, searching {} moves, {} positions ({:.0}s)\nF5 to cancel", metric, progress.depth(), progress.nodes(), elapsed)
+    }
+  } else {
+    // a result only stands while the cube is still in that position
+    match &solver.result {
+      Some(Ok(solution)) if state.cube == solver.position => {
+        format!("Optimal {} {} ({:.1}s)\n{}", solution.count(solver.metric), metric, solver.elapsed.elapsed_secs(), solution)
+      }
+      Some(Err(e)) if state.cube == solver.position => format!("Optimal {}: {}", metric, e),
+      _ => String::new(),
+    }
+  };
+
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+
+use puzzle_cube_core::patterns::PATTERNS;
+use puzzle_cube_core::solver::{self, SolveError};
+use puzzle_cube_core::Alg;
+
+use crate::component::cube::CubeState;
+use crate::component::focus::{Focus, TextFocus};
+
+pub struct PatternsComponent;
+
+impl Plugin for PatternsComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Patterns::load(PATTERNS_FILE));
+    app.insert_resource(PatternsSettings::default());
+    app.add_systems(Startup, setup_patterns);
+    // typing happens before anything else reads the keyboard
+    app.add_systems(PreUpdate, pattern_entry.after(InputSystem));
+    app.add_systems(Update, (
+      poll_save,
+      update_patterns_text.run_if(any_with_component::<PatternsText>),
+    ).chain());
+  }
+}
+
+// patterns saved by the player, name and moves tab separated
+const PATTERNS_FILE: &str = "patterns.txt";
+
+/* MARK: PATTERNS <RES>
+  a chosen pattern is picked up by the cube, which resets and plays it out at scramble speed
+*/
+#[derive(Resource, Default)]
+pub struct Patterns {
+  pub open: bool,
+  pub selected: usize,
+  // name being typed to save the cube under
+  pub name: String,
+  pub pending: Option<Alg>,
+  // the player's own, listed after the well known ones
+  pub saved: Vec<(String, Alg)>,
+  pub message: Option<String>,
+  // the cube being saved, by name, while its setup is worked out in the background
+  task: Option<Task<(String, Result<Alg, SolveError>)>>,
+}
+
+impl Patterns {
+  // a missing file is no saved patterns, unreadable lines are skipped
+  fn load(path: &str) -> Patterns {
+    let Ok(text) = fs::read_to_string(path) else { return Patterns::default() };
+    let saved = text.lines().filter(|line| !line.trim().is_empty()).filter_map(|line| {
+      let (name, alg) = line.split_once('\t')?;
+      alg.parse().map_err(|e| warn!("skipping pattern {} in {}: {}", name, path, e)).ok().map(|alg| (name.to_string(), alg))
+    }).collect();
+    Patterns { saved, ..default() }
+  }
+
+  fn save(&self, path: &str) {
+    let lines: String = self.saved.iter().map(|(name, alg)| format!("{}\t{}\n", name, alg)).collect();
+    if let Err(e) = fs::write(path, lines) { warn!("could not save patterns to {}: {}", path, e); }
+  }
+
+  pub fn list(&self) -> Vec<(String, Alg)> {
+    PATTERNS.iter()
+      .map(|pattern| (pattern.name.to_string(), pattern.alg.parse().unwrap_or_default()))
+      .chain(self.saved.iter().cloned())
+      .collect()
+  }
+
+  // the cube as it is, as the turns that set it up from solved, found by poll_save
+  fn save_cube(&mut self, state: &CubeState) -> String {
+    if self.task.is_some() { return "still saving the last one".to_string() }
+    let name = self.name.trim().to_string();
+    if state.cube.is_solved_any_orientation() { return "the cube is solved, there is nothing to save".to_string() }
+    let cube = state.cube.normalized();
+    let message = format!("saving {}…", name);
+    self.task = Some(AsyncComputeTaskPool::get().spawn(async move { (name, solver::solve(&cube)) }));
+    self.name.clear();
+    message
+  }
+
+  fn finish_save(&mut self, name: String, solution: Result<Alg, SolveError>) -> String {
+    match solution {
+      Ok(solution) => {
+        self.saved.retain(|(saved, _)| *saved != name);
+        self.saved.push((name.clone(), solution.inverse()));
+        self.save(PATTERNS_FILE);
+        format!("saved {}", name)
+      }
+      Err(e) => format!("could not save {}: {}", name, e),
+    }
+  }
+}
+
+#[derive(Resource)]
+struct PatternsSettings {
+  // opens the gallery, and closes it again
+  button_open: Option<KeyCode>,
+}
+
+impl Default for PatternsSettings {
+  fn default() -> Self {
+    PatternsSettings { button_open: Some(KeyCode::Backquote) }
+  }
+}
+
+/* MARK: CONTROLS
+  while the gallery is open every key goes to it. Enter plays the selected pattern, or saves
+  the cube once a name is typed
+*/
+fn pattern_entry(
+  mut events: EventReader<KeyboardInput>,
+  mut kbd: ResMut<ButtonInput<KeyCode>>,
+  settings: Res<PatternsSettings>,
+  state: Res<CubeState>,
+  mut focus: ResMut<TextFocus>,
+  mut patterns: ResMut<Patterns>,
+) {
+  let open = settings.button_open.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+  if focus.enter(TextFocus::Patterns, &mut patterns.open, open, &mut events, &mut kbd) != Focus::Typing { return }
+
+  let count = patterns.list().len();
+  for event in events.read() {
+    if event.state != ButtonState::Pressed { continue }
+    match &event.logical_key {
+      Key::Character(text) if !open => patterns.name.push_str(text),
+      Key::Space => patterns.name.push(' '),
+      Key::Backspace => { patterns.name.pop(); }
+      Key::ArrowUp => patterns.selected = patterns.selected.saturating_sub(1),
+      Key::ArrowDown => patterns.selected = (patterns.selected + 1).min(count.saturating_sub(1)),
+      Key::Enter if patterns.name.trim().is_empty() => {
+        patterns.pending = patterns.list().get(patterns.selected).map(|(_, alg)| alg.clone());
+        patterns.open = false;
+      }
+      Key::Enter => patterns.message = Some(patterns.save_cube(&state)),
+      Key::Escape => patterns.open = false,
+      _ => {}
+    }
+  }
+  if open { patterns.open = false; }
+  if !patterns.open {
+    patterns.name.clear();
+    patterns.message = None;
+  }
+  kbd.reset_all();
+}
+
+fn poll_save(mut patterns: ResMut<Patterns>) {
+  let Some(task) = &mut patterns.task else { return };
+  let Some((name, solution)) = block_on(poll_once(task)) else { return };
+  patterns.task = None;
+  let message = patterns.finish_save(name, solution);
+  patterns.message = Some(message);
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct PatternsText;
+
+fn setup_patterns(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Percent(25.0),
+        left: Val::Percent(35.0),
+        ..default()
+      }),
+    PatternsText,
+  ));
+}
+
+fn update_patterns_text(
+  patterns: Res<Patterns>,
+  mut text: Query<&mut Text, With<PatternsText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if patterns.open {
+    lines.push("Patterns (` to close)".to_string());
+    for (i, (name, alg)) in patterns.list().iter().enumerate() {
+      let marker = if i == patterns.selected { ">" } else { " " };
+      lines.push(format!("{} {:<26} {}", marker, name, alg));
+    }
+    lines.push("Enter to play, or type a name and Enter to save the cube".to_string());
+    if !patterns.name.is_empty() { lines.push(format!("save as: {}_", patterns.name)); }
+    if let Some(message) = &patterns.message { lines.push(message.clone()); }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use puzzle_cube_core::puzzle::{void_parity, Puzzle};
+use puzzle_cube_core::Face;
+
+use crate::component::cube::{Block, CubeState, DefaultPosition};
+use crate::component::replay::replay_closed;
+
+pub struct PuzzlesComponent;
+
+impl Plugin for PuzzlesComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(PuzzleType::default());
+    app.insert_resource(PuzzleSettings::default());
+    app.add_systems(Startup, setup_puzzle_text);
+    app.add_systems(Update, (
+      puzzle_control.run_if(replay_closed),
+      show_pieces,
+      update_puzzle_text.run_if(any_with_component::<PuzzleText>),
+    ).chain());
+  }
+}
+
+/* MARK: PUZZLE <RES>
+  which puzzle the blocks make up. switching resets the cube, lays the blocks out for the new
+  dimensions and hides the ones the puzzle has no piece for
+*/
+#[derive(Resource, Default)]
+pub struct PuzzleType {
+  pub puzzle: Puzzle,
+}
+
+impl PuzzleType {
+  // where a block sits on the solved puzzle, a side two layers deep has its blocks touching
+  pub fn home(&self, position: Vec3) -> Vec3 {
+    let dimensions = self.puzzle.dimensions();
+    let slot = slot(position);
+    Vec3::from_array(std::array::from_fn(|k| slot[k] as f32 * if dimensions[k] == 2 { 1.1 } else { 2.2 }))
+  }
+}
+
+// cubie position of the 3x3 a block stands for, however far apart the layers are drawn
+fn slot(position: Vec3) -> [i8; 3] {
+  position.to_array().map(|v| if v > 0.5 { 1 } else if v < -0.5 { -1 } else { 0 })
+}
+
+#[derive(Resource)]
+struct PuzzleSettings {
+  button_puzzle: Option<KeyCode>,
+}
+
+impl Default for PuzzleSettings {
+  fn default() -> Self {
+    PuzzleSettings { button_puzzle: Some(KeyCode::Quote) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn puzzle_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<PuzzleSettings>,
+  mut puzzle: ResMut<PuzzleType>,
+) {
+  if settings.button_puzzle.map(|key| kbd.just_pressed(key)).unwrap_or(false) {
+    puzzle.puzzle = puzzle.puzzle.next();
+    info!("puzzle: {}", puzzle.puzzle.name());
+  }
+}
+
+// blocks the puzzle has no piece for stay out of sight, they still turn along unseen
+fn show_pieces(
+  puzzle: Res<PuzzleType>,
+  mut blocks: Query<(&DefaultPosition, &mut Visibility), With<Block>>,
+  added: Query<(), Added<Block>>,
+) {
+  if !puzzle.is_changed() && added.is_empty() { return }
+  for (default, mut visibility) in &mut blocks {
+    *visibility = if puzzle.puzzle.has_piece(slot(default.0)) { Visibility::Visible } else { Visibility::Hidden };
+  }
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct PuzzleText;
+
+fn setup_puzzle_text(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Percent(8.0),
+        left: Val::Percent(35.0),
+        ..default()
+      }),
+    PuzzleText,
+  ));
+}
+
+// nothing for the 3x3, the sides that only turn half way for cuboids and the parity of a void cube
+fn update_puzzle_text(
+  puzzle: Res<PuzzleType>,
+  state: Res<CubeState>,
+  mut text: Query<&mut Text, With<PuzzleText>>,
+) {
+  if !puzzle.is_changed() && !state.is_changed() { return }
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let content = match puzzle.puzzle {
+    Puzzle::Cube => String::new(),
+    Puzzle::Void if void_parity(&state.cube) => "Void cube, edges a swap off from the corners, a slice quarter turn fixes it".to_string(),
+    Puzzle::Void => "Void cube".to_string(),
+    cuboid => {
+      let halves: Vec<String> = Face::ALL.iter().filter(|face| cuboid.step(face.axis()) == 2).map(Face::to_string).collect();
+      format!("{} cuboid, {} turn 180° only", cuboid.name(), halves.join(" "))
+    }
+  };
+
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -0,0 +1,370 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+use puzzle_cube_core::SolveRecord;
+
+use crate::component::cube::{SolveHistory, SolveLog};
+
+pub struct ReplayComponent;
+
+impl Plugin for ReplayComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Replay::default());
+    app.insert_resource(ReplaySettings::default());
+    app.add_systems(Startup, setup_replay);
+    app.add_systems(Update, (
+      open_replay,
+      (replay_keys, replay_buttons, scrub_replay, advance_replay).run_if(replay_open),
+      (update_replay_ui, update_move_list).run_if(any_with_component::<ReplayPanel>),
+    ).chain());
+  }
+}
+
+// playback speeds the speed buttons step through
+const SPEEDS: [f32; 7] = [0.1, 0.25, 0.5, 1.0, 1.5, 2.0, 4.0];
+const DEFAULT_SPEED: usize = 3;
+
+const PANEL_COLOR: Color = Color::srgba(0.1, 0.1, 0.12, 0.8);
+const TRACK_COLOR: Color = Color::srgb(0.25, 0.25, 0.28);
+const FILL_COLOR: Color = Color::srgb(0.85, 0.65, 0.2);
+const MOVE_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const DONE_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const CURRENT_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const FONT_SIZE: f32 = 18.0;
+
+/* MARK: REPLAY <RES>
+  a recorded solve played back on the cube, the cube controls are off while one is open
+*/
+#[derive(Resource)]
+pub struct Replay {
+  pub record: Option<SolveRecord>,
+  // index into the solve history of the open record
+  pub index: usize,
+  // seconds into the solve
+  pub position: f32,
+  pub playing: bool,
+  pub speed: f32,
+}
+
+impl Default for Replay {
+  fn default() -> Self {
+    Replay { record: None, index: 0, position: 0.0, playing: false, speed: SPEEDS[DEFAULT_SPEED] }
+  }
+}
+
+impl Replay {
+  // number of moves made by the current position
+  pub fn current(&self) -> usize {
+    self.record.as_ref().map_or(0, |record| record.moves.iter().take_while(|m| m.time <= self.position).count())
+  }
+
+  pub fn duration(&self) -> f32 {
+    self.record.as_ref().map_or(0.0, |record| record.moves.last().map_or(0.0, |m| m.time).max(record.time))
+  }
+
+  fn open(&mut self, history: &SolveHistory, index: usize) {
+    let Some(record) = history.0.get(index) else { return };
+    self.record = Some(record.clone());
+    self.index = index;
+    self.position = 0.0;
+    self.playing = true;
+  }
+
+  // jump to just after the given number of moves
+  fn seek_move(&mut self, count: usize) {
+    let Some(record) = &self.record else { return };
+    self.position = match count {
+      0 => 0.0,
+      n => record.moves[n.min(record.moves.len()) - 1].time,
+    };
+    self.playing = false;
+  }
+
+  fn change_speed(&mut self, faster: bool) {
+    let index = SPEEDS.iter().position(|s| *s >= self.speed).unwrap_or(DEFAULT_SPEED);
+    let index = if faster { (index + 1).min(SPEEDS.len() - 1) } else { index.saturating_sub(1) };
+    self.speed = SPEEDS[index];
+  }
+}
+
+pub fn replay_open(replay: Res<Replay>) -> bool { replay.record.is_some() }
+
+pub fn replay_closed(replay: Res<Replay>) -> bool { replay.record.is_none() }
+
+#[derive(Resource)]
+struct ReplaySettings {
+  button_toggle: Option<KeyCode>,
+  button_play: Option<KeyCode>,
+  button_step_back: Option<KeyCode>,
+  button_step_forward: Option<KeyCode>,
+  button_slower: Option<KeyCode>,
+  button_faster: Option<KeyCode>,
+  button_previous_solve: Option<KeyCode>,
+  button_next_solve: Option<KeyCode>,
+}
+
+// arrow keys are free to reuse, cube rotations are off during a replay
+impl Default for ReplaySettings {
+  fn default() -> Self {
+    ReplaySettings {
+      button_toggle: Some(KeyCode::KeyV),
+      button_play: Some(KeyCode::Space),
+      button_step_back: Some(KeyCode::ArrowLeft),
+      button_step_forward: Some(KeyCode::ArrowRight),
+      button_slower: Some(KeyCode::Minus),
+      button_faster: Some(KeyCode::Equal),
+      button_previous_solve: Some(KeyCode::BracketLeft),
+      button_next_solve: Some(KeyCode::BracketRight),
+    }
+  }
+}
+
+/* MARK: UI SETUP
+  move list on the right, transport bar (play, speed, scrub bar, time) along the bottom
+*/
+#[derive(Component)]
+struct ReplayPanel;
+
+#[derive(Component)]
+struct MoveList;
+
+#[derive(Component)]
+struct ScrubBar;
+
+#[derive(Component)]
+struct ScrubFill;
+
+// labels on the transport bar that follow the replay
+#[derive(Component)]
+enum ReplayLabel { Time, Play }
+
+#[derive(Component, Clone, Copy)]
+enum ReplayButton { Play, Slower, Faster, Close }
+
+fn text_style() -> TextStyle {
+  TextStyle { font_size: FONT_SIZE, color: MOVE_COLOR, ..default() }
+}
+
+fn setup_replay(mut commands: Commands) {
+  commands.spawn((
+    NodeBundle {
+      style: Style {
+        display: Display::None,
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        ..default()
+      },
+      ..default()
+    },
+    ReplayPanel,
+  )).with_children(|root| {
+    root.spawn((
+      TextBundle::from_section("", text_style()).with_style(Style {
+        position_type: PositionType::Absolute,
+        right: Val::Px(0.0),
+        top: Val::Px(0.0),
+        bottom: Val::Px(56.0),
+        width: Val::Px(240.0),
+        padding: UiRect::all(Val::Px(12.0)),
+        ..default()
+      }).with_background_color(PANEL_COLOR),
+      MoveList,
+    ));
+
+    root.spawn(NodeBundle {
+      style: Style {
+        position_type: PositionType::Absolute,
+        left: Val::Px(0.0),
+        right: Val::Px(0.0),
+        bottom: Val::Px(0.0),
+        height: Val::Px(56.0),
+        padding: UiRect::all(Val::Px(10.0)),
+        column_gap: Val::Px(10.0),
+        align_items: AlignItems::Center,
+        ..default()
+      },
+      background_color: PANEL_COLOR.into(),
+      ..default()
+    }).with_children(|bar| {
+      for (button, label) in [(ReplayButton::Play, "Play"), (ReplayButton::Slower, "-"), (ReplayButton::Faster, "+")] {
+        spawn_button(bar, button, label);
+      }
+
+      bar.spawn((
+        ButtonBundle {
+          style: Style { flex_grow: 1.0, height: Val::Px(12.0), ..default() },
+          background_color: TRACK_COLOR.into(),
+          ..default()
+        },
+        RelativeCursorPosition::default(),
+        ScrubBar,
+      )).with_children(|track| {
+        track.spawn((
+          NodeBundle {
+            style: Style { width: Val::Percent(0.0), height: Val::Percent(100.0), ..default() },
+            background_color: FILL_COLOR.into(),
+            ..default()
+          },
+          ScrubFill,
+        ));
+      });
+
+      bar.spawn((TextBundle::from_section("", text_style()), ReplayLabel::Time));
+      spawn_button(bar, ReplayButton::Close, "Close");
+    });
+  });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, button: ReplayButton, label: &str) {
+  parent.spawn((
+    ButtonBundle {
+      style: Style {
+        min_width: Val::Px(36.0),
+        padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+        justify_content: JustifyContent::Center,
+        ..default()
+      },
+      background_color: TRACK_COLOR.into(),
+      ..default()
+    },
+    button,
+  )).with_children(|b| {
+    let mut text = b.spawn(TextBundle::from_section(label, text_style()));
+    if let ReplayButton::Play = button { text.insert(ReplayLabel::Play); }
+  });
+}
+
+/* MARK: CONTROLS
+*/
+// the latest solve opens, but not while one is being timed
+fn open_replay(
+  kbd: Res<ButtonInput<KeyCode>>,
+  mut replay: ResMut<Replay>,
+  log: Res<SolveLog>,
+  history: Res<SolveHistory>,
+  settings: Res<ReplaySettings>,
+) {
+  if !settings.button_toggle.map(|key| kbd.just_pressed(key)).unwrap_or(false) { return }
+
+  if replay.record.is_some() { replay.record = None; }
+  else if !log.solving && !history.0.is_empty() { replay.open(&history, history.0.len() - 1); }
+}
+
+fn replay_keys(
+  kbd: Res<ButtonInput<KeyCode>>,
+  mut replay: ResMut<Replay>,
+  history: Res<SolveHistory>,
+  settings: Res<ReplaySettings>,
+) {
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+
+  if pressed(settings.button_play) { toggle_play(&mut replay); }
+  if pressed(settings.button_step_back) { let current = replay.current(); replay.seek_move(current.saturating_sub(1)); }
+  if pressed(settings.button_step_forward) { let current = replay.current(); replay.seek_move(current + 1); }
+  if pressed(settings.button_slower) { replay.change_speed(false); }
+  if pressed(settings.button_faster) { replay.change_speed(true); }
+  if pressed(settings.button_previous_solve) && replay.index > 0 { let index = replay.index - 1; replay.open(&history, index); }
+  if pressed(settings.button_next_solve) { let index = replay.index + 1; replay.open(&history, index); }
+}
+
+// play from the start again once the end is reached
+fn toggle_play(replay: &mut Replay) {
+  replay.playing = !replay.playing;
+  if replay.playing && replay.position >= replay.duration() { replay.position = 0.0; }
+}
+
+fn replay_buttons(
+  buttons: Query<(&Interaction, &ReplayButton), Changed<Interaction>>,
+  mut replay: ResMut<Replay>,
+) {
+  for (interaction, button) in &buttons {
+    if *interaction != Interaction::Pressed { continue }
+    match button {
+      ReplayButton::Play => toggle_play(&mut replay),
+      ReplayButton::Slower => replay.change_speed(false),
+      ReplayButton::Faster => replay.change_speed(true),
+      ReplayButton::Close => replay.record = None,
+    }
+  }
+}
+
+fn scrub_replay(
+  bar: Query<(&Interaction, &RelativeCursorPosition), With<ScrubBar>>,
+  mut replay: ResMut<Replay>,
+) {
+  for (interaction, cursor) in &bar {
+    if *interaction != Interaction::Pressed { continue }
+    let Some(position) = cursor.normalized else { continue };
+    replay.position = position.x.clamp(0.0, 1.0) * replay.duration();
+    replay.playing = false;
+  }
+}
+
+fn advance_replay(
+  time: Res<Time>,
+  mut replay: ResMut<Replay>,
+) {
+  if !replay.playing { return }
+  replay.position += time.delta_seconds() * replay.speed;
+  let duration = replay.duration();
+  if replay.position >= duration {
+    replay.position = duration;
+    replay.playing = false;
+  }
+}
+
+/* MARK: UI UPDATE
+*/
+fn update_replay_ui(
+  replay: Res<Replay>,
+  mut panel: Query<&mut Style, (With<ReplayPanel>, Without<ScrubFill>)>,
+  mut fill: Query<&mut Style, (With<ScrubFill>, Without<ReplayPanel>)>,
+  mut labels: Query<(&mut Text, &ReplayLabel)>,
+) {
+  if !replay.is_changed() { return }
+
+  let Ok(mut panel) = panel.get_single_mut() else { return };
+  panel.display = if replay.record.is_some() { Display::Flex } else { Display::None };
+
+  let duration = replay.duration();
+  if let Ok(mut fill) = fill.get_single_mut() {
+    let progress = if duration > 0.0 { replay.position / duration } else { 1.0 };
+    fill.width = Val::Percent(progress * 100.0);
+  }
+  for (mut text, label) in &mut labels {
+    text.sections[0].value = match label {
+      ReplayLabel::Time => format!("{:.2} / {:.2}s  x{}", replay.position, duration, replay.speed),
+      ReplayLabel::Play => if replay.playing { "Pause" } else { "Play" }.to_string(),
+    };
+  }
+}
+
+// rebuilt for a new record, otherwise only the highlight moves
+fn update_move_list(
+  replay: Res<Replay>,
+  mut list: Query<&mut Text, With<MoveList>>,
+  mut listed: Local<Option<usize>>,
+) {
+  if !replay.is_changed() { return }
+  let Ok(mut list) = list.get_single_mut() else { return };
+  let Some(record) = &replay.record else { *listed = None; return };
+
+  if *listed != Some(replay.index) {
+    let mut sections = vec![TextSection::new(format!("Solve {}\n", replay.index + 1), text_style())];
+    sections.extend(record.moves.iter().map(|m| TextSection::new(format!("{} ", m.mv), text_style())));
+    list.sections = sections;
+    *listed = Some(replay.index);
+  }
+
+  let current = replay.current();
+  for (i, section) in list.sections.iter_mut().skip(1).enumerate() {
+    section.style.color = match (i + 1).cmp(&current) {
+      Ordering::Less => DONE_COLOR,
+      Ordering::Equal => CURRENT_COLOR,
+      Ordering::Greater => MOVE_COLOR,
+    };
+  }
+}
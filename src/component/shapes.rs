@@ -0,0 +1,267 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+
+use puzzle_cube_core::{Axis, Face};
+
+use crate::component::cube::{Block, CubeState, DefaultPosition};
+use crate::component::replay::replay_closed;
+use crate::component::supercube::CenterMark;
+use crate::component::trainer::face_color;
+
+pub struct ShapesComponent;
+
+impl Plugin for ShapesComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(PuzzleShape::default());
+    app.insert_resource(ShapeSettings::default());
+    app.add_systems(Update, (
+      shape_control.run_if(replay_closed),
+      build_pieces,
+      hide_models,
+    ).chain());
+  }
+}
+
+// half the gap left between neighbouring pieces
+const GAP: f32 = 0.05;
+// cut planes sit halfway between the block positions
+const CUT: f32 = 1.1;
+// how far each side of a mirror blocks cube reaches, R U F L D B
+const MIRROR_EXTENTS: [f32; 6] = [2.4, 3.0, 2.7, 3.4, 1.8, 2.1];
+const MIRROR_COLOR: Color = Color::srgb(0.8, 0.8, 0.82);
+// half the size of the axis cube, enough for every corner piece to reach the outside
+const AXIS_SIZE: f32 = 3.1;
+const INSIDE_COLOR: Color = Color::srgb(0.08, 0.08, 0.08);
+
+/* MARK: SHAPE <RES>
+  shape mods keep the 3x3 mechanism and change the outside. each piece is the slot its block
+  starts in cut down to the outer shell, so pieces differ in size (mirror blocks) or have
+  slanted sides (axis cube) and can only be told apart by where they belong
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shape {
+  #[default]
+  Standard,
+  MirrorBlocks,
+  Axis,
+}
+
+impl Shape {
+  pub const ALL: [Shape; 3] = [Shape::Standard, Shape::MirrorBlocks, Shape::Axis];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Shape::Standard => "3x3",
+      Shape::MirrorBlocks => "Mirror Blocks",
+      Shape::Axis => "Axis cube",
+    }
+  }
+
+  pub fn next(self) -> Shape { Shape::ALL[(self as usize + 1) % Shape::ALL.len()] }
+
+  // a centre turned in place changes the shape of an axis cube, not of mirror blocks
+  pub fn centers_matter(self) -> bool { self == Shape::Axis }
+
+  // planes of the outer shell, outward normal and distance from the middle, with the face
+  // each one is coloured as
+  fn shell(self) -> Vec<(Vec3, f32, Face)> {
+    let sides = [Face::R, Face::U, Face::F, Face::L, Face::D, Face::B];
+    match self {
+      Shape::Standard => Vec::new(),
+      Shape::MirrorBlocks => sides.iter().zip(MIRROR_EXTENTS).map(|(face, extent)| (face_normal(*face), extent, *face)).collect(),
+      Shape::Axis => {
+        // turned an eighth about two axes, so every cut runs across the faces at a slant
+        let rotation = Quat::from_rotation_x(FRAC_PI_4) * Quat::from_rotation_z(FRAC_PI_4);
+        sides.iter().map(|face| (rotation * face_normal(*face), AXIS_SIZE, *face)).collect()
+      }
+    }
+  }
+}
+
+#[derive(Resource, Default)]
+pub struct PuzzleShape {
+  pub shape: Shape,
+}
+
+impl PuzzleShape {
+  // every piece back where it belongs, whatever its colour
+  pub fn solved(&self, state: &CubeState) -> bool {
+    state.cube.pieces_home() && (!self.shape.centers_matter() || state.centers.solved())
+  }
+}
+
+#[derive(Resource)]
+struct ShapeSettings {
+  button_shape: Option<KeyCode>,
+}
+
+impl Default for ShapeSettings {
+  fn default() -> Self {
+    ShapeSettings { button_shape: Some(KeyCode::Semicolon) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn shape_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<ShapeSettings>,
+  mut shape: ResMut<PuzzleShape>,
+) {
+  if settings.button_shape.map(|key| kbd.just_pressed(key)).unwrap_or(false) {
+    shape.shape = shape.shape.next();
+    info!("puzzle shape: {}", shape.shape.name());
+  }
+}
+
+/* MARK: PIECES
+*/
+#[derive(Component)]
+struct ShapeMesh;
+
+// a fresh set of pieces whenever the shape changes, none for the standard models
+fn build_pieces(
+  mut commands: Commands,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut materials: ResMut<Assets<StandardMaterial>>,
+  shape: Res<PuzzleShape>,
+  blocks: Query<(Entity, &DefaultPosition), With<Block>>,
+  added: Query<(), Added<Block>>,
+  pieces: Query<Entity, With<ShapeMesh>>,
+) {
+  if !shape.is_changed() && added.is_empty() { return }
+  for piece in &pieces { commands.entity(piece).despawn_recursive(); }
+  if shape.shape == Shape::Standard { return }
+
+  let metallic = if shape.shape == Shape::MirrorBlocks { 0.7 } else { 0.0 };
+  let material = materials.add(StandardMaterial { base_color: Color::WHITE, metallic, perceptual_roughness: 0.35, ..default() });
+  let shell = shape.shape.shell();
+  let mirror = shape.shape == Shape::MirrorBlocks;
+  for (entity, position) in &blocks {
+    let slot = (position.0 / 2.2).round().to_array().map(|v| v as i8);
+    let Some(mesh) = piece_mesh(&shell, slot, position.0, mirror) else { continue };
+    let piece = commands.spawn((
+      PbrBundle { mesh: meshes.add(mesh), material: material.clone(), ..default() },
+      ShapeMesh,
+    )).id();
+    commands.entity(entity).add_child(piece);
+  }
+}
+
+// the block models stay loaded but out of sight while a shape mod is shown
+fn hide_models(
+  shape: Res<PuzzleShape>,
+  blocks: Query<&Children, With<Block>>,
+  mut models: Query<&mut Visibility, (Without<ShapeMesh>, Without<CenterMark>)>,
+) {
+  let wanted = if shape.shape == Shape::Standard { Visibility::Inherited } else { Visibility::Hidden };
+  for children in &blocks {
+    for child in children {
+      let Ok(mut visibility) = models.get_mut(*child) else { continue };
+      if *visibility != wanted { *visibility = wanted; }
+    }
+  }
+}
+
+/* MARK: GEOMETRY
+  a piece is clipped out of a large box one plane at a time, each plane closing the hole it
+  cuts with a new side. every side stays convex, wound anticlockwise seen from outside
+*/
+#[derive(Clone, Copy)]
+enum Side { Sticker(Face), Cut }
+
+struct Polygon {
+  points: Vec<Vec3>,
+  normal: Vec3,
+  side: Side,
+}
+
+fn face_normal(face: Face) -> Vec3 { Vec3::from_array(face.normal().map(|v| v as f32)) }
+
+fn axis_vec(axis: Axis) -> Vec3 { Vec3::from_array(Face::from_axis(axis, true).normal().map(|v| v as f32)) }
+
+// sides of a box reaching `size` from the middle every way
+fn box_polygons(size: f32) -> Vec<Polygon> {
+  Face::ALL.iter().map(|face| {
+    let normal = face_normal(*face);
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+    let points = [u + v, -u + v, -u - v, u - v].iter().map(|corner| (normal + *corner) * size).collect();
+    Polygon { points, normal, side: Side::Cut }
+  }).collect()
+}
+
+// keeps what lies on the inside of `normal . p <= offset`
+fn clip(polygons: Vec<Polygon>, normal: Vec3, offset: f32, side: Side) -> Vec<Polygon> {
+  let distance = |p: Vec3| normal.dot(p) - offset;
+  let mut cap: Vec<Vec3> = Vec::new();
+  let mut kept = Vec::new();
+  for polygon in polygons {
+    let mut points = Vec::new();
+    for (i, a) in polygon.points.iter().enumerate() {
+      let b = polygon.points[(i + 1) % polygon.points.len()];
+      let (da, db) = (distance(*a), distance(b));
+      if da <= 0.0 { points.push(*a); }
+      if (da <= 0.0) != (db <= 0.0) {
+        let crossing = *a + (b - *a) * (da / (da - db));
+        points.push(crossing);
+        cap.push(crossing);
+      }
+    }
+    if points.len() >= 3 { kept.push(Polygon { points, ..polygon }); }
+  }
+
+  if cap.len() >= 3 {
+    let center = cap.iter().copied().sum::<Vec3>() / cap.len() as f32;
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+    cap.sort_by(|a, b| {
+      let angle = |p: &Vec3| (*p - center).dot(v).atan2((*p - center).dot(u));
+      angle(a).total_cmp(&angle(b))
+    });
+    cap.dedup_by(|a, b| a.distance(*b) < 1e-4);
+    if cap.len() >= 3 { kept.push(Polygon { points: cap, normal, side }); }
+  }
+  kept
+}
+
+// the slot's share of the shell, around the block's own position
+fn piece_mesh(shell: &[(Vec3, f32, Face)], slot: [i8; 3], origin: Vec3, mirror: bool) -> Option<Mesh> {
+  let mut polygons = box_polygons(10.0);
+  for (normal, offset, face) in shell {
+    polygons = clip(polygons, *normal, *offset, Side::Sticker(*face));
+  }
+  for axis in [Axis::X, Axis::Y, Axis::Z] {
+    let along = axis_vec(axis);
+    let layer = slot[axis.index()];
+    if layer <= 0 { polygons = clip(polygons, along, if layer < 0 { -CUT - GAP } else { CUT - GAP }, Side::Cut); }
+    if layer >= 0 { polygons = clip(polygons, -along, if layer > 0 { -CUT - GAP } else { CUT - GAP }, Side::Cut); }
+  }
+  if polygons.len() < 4 { return None }
+
+  let (mut positions, mut normals, mut colors, mut indices) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+  for polygon in &polygons {
+    let color = match polygon.side {
+      Side::Cut => INSIDE_COLOR,
+      Side::Sticker(_) if mirror => MIRROR_COLOR,
+      Side::Sticker(face) => face_color(face),
+    };
+    let first = positions.len() as u32;
+    for point in &polygon.points {
+      positions.push((*point - origin).to_array());
+      normals.push(polygon.normal.to_array());
+      colors.push(color.to_linear().to_f32_array());
+    }
+    for i in 1..polygon.points.len() as u32 - 1 { indices.extend([first, first + i, first + i + 1]); }
+  }
+
+  let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+  mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+  mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+  mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+  mesh.insert_indices(Indices::U32(indices));
+  Some(mesh)
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use puzzle_cube_core::supercube::{self, home_mark};
+use puzzle_cube_core::{Axis, Face};
+
+use crate::component::cube::{Block, CubeState};
+use crate::component::replay::replay_closed;
+
+pub struct SupercubeComponent;
+
+impl Plugin for SupercubeComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Supercube::default());
+    app.insert_resource(SupercubeSettings::default());
+    app.add_systems(Startup, setup_supercube);
+    app.add_systems(Update, (
+      supercube_control.run_if(replay_closed),
+      mark_centers,
+      show_marks,
+      update_supercube_text.run_if(any_with_component::<SupercubeText>),
+    ).chain());
+  }
+}
+
+/* MARK: SUPERCUBE <RES>
+  centres carry a marking that turns with them, and a solve only ends with every marking
+  the right way round. switching it resets the cube, and its scrambles are long enough to leave
+  the centres twisted at random
+*/
+#[derive(Resource, Default)]
+pub struct Supercube {
+  pub active: bool,
+}
+
+#[derive(Resource)]
+struct SupercubeSettings {
+  button_supercube: Option<KeyCode>,
+}
+
+impl Default for SupercubeSettings {
+  fn default() -> Self {
+    SupercubeSettings { button_supercube: Some(KeyCode::KeyU) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn supercube_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<SupercubeSettings>,
+  mut supercube: ResMut<Supercube>,
+) {
+  if settings.button_supercube.map(|key| kbd.just_pressed(key)).unwrap_or(false) {
+    supercube.active = !supercube.active;
+  }
+}
+
+/* MARK: MARKINGS
+  a bar from the middle of each centre sticker to its top edge, a child of the block so it
+  turns with it
+*/
+#[derive(Component)]
+pub struct CenterMark;
+
+// centre blocks sit 2.2 out along a single axis
+fn center_face(translation: Vec3) -> Option<Face> {
+  let axes = [(Axis::X, translation.x), (Axis::Y, translation.y), (Axis::Z, translation.z)];
+  let outside: Vec<&(Axis, f32)> = axes.iter().filter(|(_, v)| v.abs() > 1.0).collect();
+  match outside.as_slice() {
+    [(axis, v)] => Some(Face::from_axis(*axis, *v > 0.0)),
+    _ => None,
+  }
+}
+
+fn mark_centers(
+  mut commands: Commands,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut materials: ResMut<Assets<StandardMaterial>>,
+  blocks: Query<(Entity, &Transform), Added<Block>>,
+  supercube: Res<Supercube>,
+) {
+  let mut material = None;
+  for (entity, transform) in &blocks {
+    let Some(face) = center_face(transform.translation) else { continue };
+    let material = material.get_or_insert_with(|| materials.add(StandardMaterial { base_color: Color::srgb(0.08, 0.08, 0.08), unlit: true, ..default() })).clone();
+    let normal = Vec3::from_array(face.normal().map(|v| v as f32));
+    let mark = Vec3::from_array(home_mark(face).map(|v| v as f32));
+    let across = normal.cross(mark).abs();
+    let size = normal.abs() * 0.04 + mark.abs() * 0.8 + across * 0.22;
+    commands.entity(entity).with_children(|parent| {
+      parent.spawn((
+        PbrBundle {
+          mesh: meshes.add(Cuboid::from_size(size)),
+          material,
+          transform: Transform::from_translation(normal * 1.02 + mark * 0.4),
+          visibility: if supercube.active { Visibility::Inherited } else { Visibility::Hidden },
+          ..default()
+        },
+        CenterMark,
+      ));
+    });
+  }
+}
+
+fn show_marks(supercube: Res<Supercube>, mut marks: Query<&mut Visibility, With<CenterMark>>) {
+  if !supercube.is_changed() { return }
+  for mut visibility in &mut marks {
+    *visibility = if supercube.active { Visibility::Inherited } else { Visibility::Hidden };
+  }
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct SupercubeText;
+
+fn setup_supercube(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Percent(2.0),
+        left: Val::Percent(35.0),
+        ..default()
+      }),
+    SupercubeText,
+  ));
+}
+
+// centres turned in place, clockwise as seen from outside
+fn update_supercube_text(
+  supercube: Res<Supercube>,
+  state: Res<CubeState>,
+  mut text: Query<&mut Text, With<SupercubeText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut content = String::new();
+  if supercube.active {
+    let twists = state.centers.twists();
+    let twisted: Vec<String> = Face::ALL.iter().zip(twists).filter(|(_, t)| *t != 0)
+      .map(|(face, t)| format!("{}{}", face, ["", "", "2", "'"][t as usize]))
+      .collect();
+    content = if twisted.is_empty() { "Supercube, centres solved".to_string() } else { format!("Supercube, centres turned {}", twisted.join(" ")) };
+    if !supercube::consistent(&state.cube, &state.centers) { content += "\ncentre parity does not match the pieces"; }
+  }
+
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+
+use puzzle_cube_core::solver::thistlethwaite::{self, STAGES};
+use puzzle_cube_core::solver::SolveError;
+use puzzle_cube_core::{Alg, Cube, Move};
+
+use crate::component::cube::{CubeState, SolveLog};
+use crate::component::replay::replay_closed;
+
+pub struct ThistlethwaiteComponent;
+
+impl Plugin for ThistlethwaiteComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Thistlethwaite::default());
+    app.insert_resource(ThistlethwaiteSettings::default());
+    app.add_systems(Startup, setup_thistlethwaite);
+    app.add_systems(Update, (
+      thistlethwaite_control.run_if(replay_closed),
+      poll_thistlethwaite,
+      update_thistlethwaite_text.run_if(any_with_component::<ThistlethwaiteText>),
+    ).chain());
+  }
+}
+
+/* MARK: THISTLETHWAITE <RES>
+  the cube solved stage by stage through G0 -> G4 with the regular turn animation,
+  while the panel follows which group it is in and the turns that keep it there. the stages
+  are searched on a background thread
+*/
+#[derive(Resource, Default)]
+pub struct Thistlethwaite {
+  pub active: bool,
+  pub stages: Vec<Alg>,
+  // turns still to animate, taken one at a time by the cube
+  pub queue: VecDeque<Move>,
+  task: Option<Task<Result<Vec<Alg>, SolveError>>>,
+  // position the search is for, the solve is dropped if the cube moved on meanwhile
+  position: Cube,
+}
+
+impl Thistlethwaite {
+  pub fn solving(&self) -> bool { self.task.is_some() }
+}
+
+#[derive(Resource)]
+struct ThistlethwaiteSettings {
+  // solves the cube as it is, pressed again it stops and closes the panel
+  button_solve: Option<KeyCode>,
+}
+
+impl Default for ThistlethwaiteSettings {
+  fn default() -> Self {
+    ThistlethwaiteSettings { button_solve: Some(KeyCode::F7) }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn thistlethwaite_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  settings: Res<ThistlethwaiteSettings>,
+  state: Res<CubeState>,
+  mut demo: ResMut<Thistlethwaite>,
+) {
+  if !settings.button_solve.map(|key| kbd.just_pressed(key)).unwrap_or(false) { return }
+
+  // closing drops a search still running along with the demo
+  if demo.active || demo.solving() {
+    *demo = Thistlethwaite::default();
+    return
+  }
+  let cube = state.cube;
+  demo.task = Some(AsyncComputeTaskPool::get().spawn(async move { thistlethwaite::solve(&cube) }));
+  demo.position = cube;
+}
+
+fn poll_thistlethwaite(state: Res<CubeState>, mut log: ResMut<SolveLog>, mut demo: ResMut<Thistlethwaite>) {
+  let Some(task) = &mut demo.task else { return };
+  let Some(result) = block_on(poll_once(task)) else { return };
+  demo.task = None;
+  let stages = match result {
+    Ok(stages) => stages,
+    Err(e) => { warn!("no thistlethwaite solution: {}", e); return }
+  };
+  if state.cube != demo.position { return }
+  // the cube is solved for the player, whatever solve was running is dropped
+  log.solving = false;
+  log.moves.clear();
+  demo.queue = stages.iter().flatten().copied().collect();
+  demo.stages = stages;
+  demo.active = true;
+}
+
+/* MARK: PANEL
+*/
+#[derive(Component)]
+struct ThistlethwaiteText;
+
+fn setup_thistlethwaite(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Percent(40.0),
+        left: Val::Px(12.0),
+        ..default()
+      }),
+    ThistlethwaiteText,
+  ));
+}
+
+fn update_thistlethwaite_text(
+  demo: Res<Thistlethwaite>,
+  state: Res<CubeState>,
+  mut text: Query<&mut Text, With<ThistlethwaiteText>>,
+) {
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let mut lines = Vec::new();
+  if demo.solving() { lines.push("Thistlethwaite: searching... (F7 to cancel)".to_string()); }
+  if demo.active {
+    let group = thistlethwaite::group_of(&state.cube);
+    lines.push("Thistlethwaite (F7 to close)".to_string());
+    lines.push(format!("in G{}, turns {}", group, thistlethwaite::allowed_moves(&state.cube, group)));
+    for (stage, moves) in demo.stages.iter().enumerate().take(STAGES) {
+      let marker = if stage == group { ">" } else { " " };
+      lines.push(format!("{} G{} -> G{}  {:>2} moves  {}", marker, stage, stage + 1, moves.len(), moves));
+    }
+  }
+
+  let content = lines.join("\n");
+  if text.sections[0].value != content { text.sections[0].value = content; }
+}
@@ -0,0 +1,631 @@
+use std::fs;
+
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy::{prelude::*, time::Stopwatch};
+use rand_core::RngCore;
+
+use puzzle_cube_core::cases::{self, Case, Slot};
+use puzzle_cube_core::puzzle::Puzzle;
+use puzzle_cube_core::solver::pieces;
+use puzzle_cube_core::{scramble, Alg, Cube, Face};
+
+use crate::component::bandage::Bandage;
+use crate::component::cube::{GameSettings, SolveLog};
+use crate::component::puzzles::PuzzleType;
+use crate::component::replay::replay_closed;
+
+pub struct TrainerComponent;
+
+impl Plugin for TrainerComponent {
+  fn build(&self, app: &mut App) {
+    app.insert_resource(Trainer::load(STATS_FILE));
+    app.insert_resource(TrainerSettings::default());
+    app.add_systems(Startup, setup_trainer);
+    app.add_systems(Update, (
+      (trainer_control, pick_case, recognition_clock).run_if(replay_closed),
+      poll_solutions,
+      build_picker.run_if(any_with_component::<CasePicker>),
+      update_trainer_text.run_if(any_with_component::<TrainerText>),
+    ).chain());
+  }
+}
+
+// case statistics and learning sets, one case per line
+const STATS_FILE: &str = "trainer.txt";
+
+// with focus on, cases are drawn from this many of the slowest
+const FOCUS_CASES: usize = 5;
+// slowest cases listed in the panel
+const LISTED_CASES: usize = 8;
+// optimal crosses listed when drilling a single colour
+const CROSS_SOLUTIONS: usize = 3;
+
+const ICON_STICKER: f32 = 9.0;
+const PICKER_COLOR: Color = Color::srgba(0.1, 0.1, 0.12, 0.9);
+const CASE_COLOR: Color = Color::srgb(0.25, 0.25, 0.28);
+const LEARNING_COLOR: Color = Color::srgb(0.2, 0.45, 0.3);
+const UNORIENTED_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+
+/* MARK: TRAINER <RES>
+  drills one case set at a time, T sets up the next case instead of a scramble.
+  the cross mode has no cases, it scrambles to a random state from solved and stops once the
+  cross is in
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrainerMode { Pll, Oll, F2l, Cross }
+
+impl TrainerMode {
+  pub const ALL: [TrainerMode; 4] = [TrainerMode::Pll, TrainerMode::Oll, TrainerMode::F2l, TrainerMode::Cross];
+
+  pub fn cases(self) -> &'static [Case] {
+    match self {
+      TrainerMode::Pll => &cases::PLL,
+      TrainerMode::Oll => &cases::OLL,
+      TrainerMode::F2l => &cases::F2L,
+      TrainerMode::Cross => &[],
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self { TrainerMode::Pll => "PLL", TrainerMode::Oll => "OLL", TrainerMode::F2l => "F2L", TrainerMode::Cross => "Cross" }
+  }
+
+  fn from_name(name: &str) -> Option<TrainerMode> {
+    TrainerMode::ALL.into_iter().find(|mode| mode.name() == name)
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseStats {
+  pub attempts: usize,
+  // seconds from the case appearing to the first turn
+  pub recognition: f32,
+  // seconds from the first turn to solved
+  pub execution: f32,
+  pub best: Option<f32>,
+  // part of the learning set, drills only pick from it when it is not empty
+  pub learning: bool,
+}
+
+impl CaseStats {
+  pub fn mean(&self) -> Option<(f32, f32)> {
+    if self.attempts == 0 { return None }
+    Some((self.recognition / self.attempts as f32, self.execution / self.attempts as f32))
+  }
+
+  fn add(&mut self, recognition: f32, execution: f32) {
+    self.attempts += 1;
+    self.recognition += recognition;
+    self.execution += execution;
+    let total = recognition + execution;
+    self.best = Some(self.best.map_or(total, |best| best.min(total)));
+  }
+}
+
+#[derive(Resource)]
+pub struct Trainer {
+  pub mode: Option<TrainerMode>,
+  pub random_y: bool,
+  // draw cases from the slowest instead of all of them
+  pub focus: bool,
+  // case set up on the cube and not solved yet
+  pub drilling: Option<usize>,
+  // runs from the case appearing until the first turn
+  pub recognition: Stopwatch,
+  // case, recognition and execution of the last finished drill
+  pub last: Option<(usize, f32, f32)>,
+  // F2L only: a second slot scrambled along with the front right one
+  pub extra_slot: Option<Slot>,
+  // cross only: the colour to solve, any of them when None
+  pub cross_color: Option<Face>,
+  // cross only: a scramble is waiting for its cross
+  crossing: bool,
+  // cross only: HTM and seconds of the last cross
+  pub cross_result: Option<(usize, f32)>,
+  // F2L and cross: shortest solutions of what was on the cube, shown once it is solved
+  pub solutions: Vec<(&'static str, Alg)>,
+  // the search for them, run in the background while the case is set up
+  task: Option<Task<Vec<(&'static str, Alg)>>>,
+  // turns that set up the case being drilled
+  setup: Alg,
+  // per mode, in the order of TrainerMode::ALL
+  pub stats: [Vec<CaseStats>; 4],
+  pub picking: bool,
+}
+
+impl Trainer {
+  pub fn cases(&self, mode: TrainerMode) -> &[CaseStats] { &self.stats[mode as usize] }
+
+  // setup moves for the next case or cross scramble, None outside trainer mode
+  pub fn next_setup(&mut self, rng: &mut impl RngCore) -> Option<Alg> {
+    let mode = self.mode?;
+    if mode == TrainerMode::Cross {
+      self.crossing = false;
+      self.solutions.clear();
+      self.task = None;
+      return Some(scramble::random_state(rng))
+    }
+    let pool = self.pool(mode);
+    let case = pool[rng.next_u32() as usize % pool.len()];
+    self.drilling = Some(case);
+    self.recognition.reset();
+    let case = &mode.cases()[case];
+    if mode != TrainerMode::F2l {
+      self.solutions.clear();
+      return Some(cases::drill_setup(rng, case, self.random_y))
+    }
+    self.setup = cases::f2l_setup(rng, case, self.extra_slot, self.random_y);
+    self.solutions.clear();
+    let (setup, extra) = (self.setup.clone(), self.extra_slot);
+    self.task = Some(AsyncComputeTaskPool::get().spawn(async move { cases::f2l_solutions(&setup, extra) }));
+    Some(self.setup.clone())
+  }
+
+  // the cube was reset, whatever was being drilled is dropped
+  pub fn stop(&mut self) {
+    self.drilling = None;
+    self.crossing = false;
+    self.task = None;
+  }
+
+  // a scramble finished, in cross mode the optimal crosses are worked out for after the attempt
+  pub fn scrambled(&mut self, cube: &Cube) {
+    if self.mode != Some(TrainerMode::Cross) { return }
+    self.crossing = true;
+    self.cross_result = None;
+    let (cube, cross_color) = (*cube, self.cross_color);
+    self.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+      match cross_color {
+        Some(color) => pieces::crosses(&cube, color, CROSS_SOLUTIONS).into_iter().map(|alg| (color_name(color), alg)).collect(),
+        None => Face::ALL.into_iter().filter_map(|color| Some((color_name(color), pieces::crosses(&cube, color, 1).pop()?))).collect(),
+      }
+    }));
+  }
+
+  // F2L drills end with the pair in and cross drills with the cross, whatever the rest looks like
+  pub fn solved(&self, cube: &Cube) -> bool {
+    match self.mode {
+      Some(TrainerMode::F2l) if self.drilling.is_some() => cases::f2l_done(cube, &self.setup, self.extra_slot),
+      Some(TrainerMode::Cross) if self.crossing => match self.cross_color {
+        Some(color) => pieces::cross_solved(cube, color),
+        None => Face::ALL.into_iter().any(|color| pieces::cross_solved(cube, color)),
+      },
+      _ => cube.is_solved_any_orientation(),
+    }
+  }
+
+  // a solve of the case on the cube is done, `turns` in HTM
+  pub fn finish(&mut self, execution: f32, turns: usize) -> bool {
+    if self.mode == Some(TrainerMode::Cross) && self.crossing {
+      self.crossing = false;
+      self.cross_result = Some((turns, execution));
+      return true
+    }
+    let (Some(mode), Some(case)) = (self.mode, self.drilling.take()) else { return false };
+    let recognition = self.recognition.elapsed_secs();
+    self.stats[mode as usize][case].add(recognition, execution);
+    self.last = Some((case, recognition, execution));
+    self.save(STATS_FILE);
+    true
+  }
+
+  // untried cases count as the slowest
+  pub fn slowest(&self, mode: TrainerMode) -> Vec<usize> {
+    let stats = self.cases(mode);
+    let mean = |case: &usize| stats[*case].mean().map_or(f32::INFINITY, |(r, e)| r + e);
+    let mut cases: Vec<usize> = (0..stats.len()).collect();
+    cases.sort_by(|a, b| mean(b).total_cmp(&mean(a)));
+    cases
+  }
+
+  fn pool(&self, mode: TrainerMode) -> Vec<usize> {
+    let learning: Vec<usize> = (0..mode.cases().len()).filter(|c| self.cases(mode)[*c].learning).collect();
+    let pool = if learning.is_empty() { (0..mode.cases().len()).collect() } else { learning };
+    if !self.focus { return pool }
+    self.slowest(mode).into_iter().filter(|c| pool.contains(c)).take(FOCUS_CASES).collect()
+  }
+
+  fn set_mode(&mut self, mode: Option<TrainerMode>) {
+    self.mode = mode;
+    self.drilling = None;
+    self.crossing = false;
+    self.cross_result = None;
+    self.last = None;
+    self.task = None;
+    self.picking &= mode.is_some();
+  }
+
+  /* MARK: SAVING
+    mode, case, attempts, recognition and execution totals, best, learning
+  */
+  fn load(path: &str) -> Trainer {
+    let mut trainer = Trainer {
+      mode: None,
+      random_y: false,
+      focus: false,
+      drilling: None,
+      recognition: Stopwatch::new(),
+      last: None,
+      extra_slot: None,
+      cross_color: None,
+      crossing: false,
+      cross_result: None,
+      solutions: Vec::new(),
+      task: None,
+      setup: Alg::new(),
+      stats: TrainerMode::ALL.map(|mode| vec![CaseStats::default(); mode.cases().len()]),
+      picking: false,
+    };
+    let Ok(text) = fs::read_to_string(path) else { return trainer };
+    for line in text.lines() {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      let [mode, name, attempts, recognition, execution, best, learning] = fields.as_slice() else { continue };
+      let Some(mode) = TrainerMode::from_name(mode) else { continue };
+      let Some(case) = mode.cases().iter().position(|c| c.name == *name) else { continue };
+      let stats = &mut trainer.stats[mode as usize][case];
+      stats.attempts = attempts.parse().unwrap_or(0);
+      stats.recognition = recognition.parse().unwrap_or(0.0);
+      stats.execution = execution.parse().unwrap_or(0.0);
+      stats.best = best.parse().ok();
+      stats.learning = *learning == "1";
+    }
+    trainer
+  }
+
+  fn save(&self, path: &str) {
+    let mut lines = Vec::new();
+    for mode in TrainerMode::ALL {
+      for (case, stats) in mode.cases().iter().zip(self.cases(mode)) {
+        if stats.attempts == 0 && !stats.learning { continue }
+        let best = stats.best.map_or("-".to_string(), |best| format!("{:.3}", best));
+        lines.push(format!("{} {} {} {:.3} {:.3} {} {}",
+          mode.name(), case.name, stats.attempts, stats.recognition, stats.execution, best, stats.learning as u8));
+      }
+    }
+    if let Err(e) = fs::write(path, lines.join("\n") + "\n") { warn!("could not save trainer stats to {}: {}", path, e); }
+  }
+}
+
+#[derive(Resource)]
+struct TrainerSettings {
+  button_pll: Option<KeyCode>,
+  button_oll: Option<KeyCode>,
+  button_f2l: Option<KeyCode>,
+  button_cross: Option<KeyCode>,
+  // F2L second slot, cross colour
+  button_variant: Option<KeyCode>,
+  button_random_y: Option<KeyCode>,
+  button_focus: Option<KeyCode>,
+  button_picker: Option<KeyCode>,
+}
+
+impl Default for TrainerSettings {
+  fn default() -> Self {
+    TrainerSettings {
+      button_pll: Some(KeyCode::KeyP),
+      button_oll: Some(KeyCode::KeyO),
+      button_f2l: Some(KeyCode::KeyL),
+      button_cross: Some(KeyCode::KeyX),
+      button_variant: Some(KeyCode::KeyJ),
+      button_random_y: Some(KeyCode::KeyY),
+      button_focus: Some(KeyCode::KeyF),
+      button_picker: Some(KeyCode::KeyC),
+    }
+  }
+}
+
+/* MARK: CONTROLS
+*/
+fn trainer_control(
+  kbd: Res<ButtonInput<KeyCode>>,
+  mut trainer: ResMut<Trainer>,
+  settings: Res<TrainerSettings>,
+  puzzle: Res<PuzzleType>,
+  bandage: Res<Bandage>,
+) {
+  let pressed = |key: Option<KeyCode>| key.map(|key| kbd.just_pressed(key)).unwrap_or(false);
+
+  // drills are set up on the plain 3x3, switching away ends them
+  let plain = puzzle.puzzle == Puzzle::Cube && bandage.current().is_none();
+  if (puzzle.is_changed() || bandage.is_changed()) && !plain && trainer.mode.is_some() { trainer.set_mode(None); }
+
+  let modes = [
+    (settings.button_pll, TrainerMode::Pll),
+    (settings.button_oll, TrainerMode::Oll),
+    (settings.button_f2l, TrainerMode::F2l),
+    (settings.button_cross, TrainerMode::Cross),
+  ];
+  for (button, mode) in modes {
+    if !pressed(button) { continue }
+    if !plain { warn!("the trainer is only for the plain {}", Puzzle::Cube.name()); continue }
+    let mode = if trainer.mode == Some(mode) { None } else { Some(mode) };
+    trainer.set_mode(mode);
+  }
+  if pressed(settings.button_variant) && trainer.mode == Some(TrainerMode::F2l) {
+    // none, then each slot other than the front right one
+    trainer.extra_slot = match trainer.extra_slot {
+      None => Some(Slot::Fl),
+      Some(Slot::Fl) => Some(Slot::Bl),
+      Some(Slot::Bl) => Some(Slot::Br),
+      Some(_) => None,
+    };
+    trainer.drilling = None;
+  }
+  if pressed(settings.button_variant) && trainer.mode == Some(TrainerMode::Cross) {
+    // any colour, then each one in turn
+    trainer.cross_color = match trainer.cross_color {
+      None => Some(Face::ALL[0]),
+      Some(color) if color.index() + 1 < Face::ALL.len() => Some(Face::from_index(color.index() + 1)),
+      Some(_) => None,
+    };
+    trainer.crossing = false;
+  }
+  if pressed(settings.button_random_y) { trainer.random_y = !trainer.random_y; }
+  if pressed(settings.button_focus) { trainer.focus = !trainer.focus; }
+  if pressed(settings.button_picker) && trainer.mode.is_some_and(|mode| !mode.cases().is_empty()) {
+    trainer.picking = !trainer.picking;
+  }
+}
+
+// recognition runs while the case waits for its first turn
+fn recognition_clock(
+  time: Res<Time>,
+  game: Res<GameSettings>,
+  log: Res<SolveLog>,
+  mut trainer: ResMut<Trainer>,
+) {
+  if trainer.drilling.is_none() || !log.solving || game.timing() { return }
+  trainer.recognition.tick(time.delta());
+}
+
+fn poll_solutions(mut trainer: ResMut<Trainer>) {
+  let Some(task) = &mut trainer.task else { return };
+  let Some(solutions) = block_on(poll_once(task)) else { return };
+  trainer.task = None;
+  trainer.solutions = solutions;
+}
+
+/* MARK: CASE PICKER
+  every case of the mode with its icon, clicking one adds it to or removes it from the learning set
+*/
+#[derive(Component)]
+struct CasePicker;
+
+#[derive(Component)]
+struct PickCase(usize);
+
+pub fn face_color(face: Face) -> Color {
+  // matches the block models: red top, blue front, white right
+  match face {
+    Face::U => Color::srgb(0.8, 0.1, 0.1),
+    Face::D => Color::srgb(1.0, 0.5, 0.0),
+    Face::R => Color::srgb(0.95, 0.95, 0.95),
+    Face::L => Color::srgb(0.95, 0.85, 0.1),
+    Face::F => Color::srgb(0.1, 0.3, 0.9),
+    Face::B => Color::srgb(0.1, 0.6, 0.2),
+  }
+}
+
+pub fn color_name(face: Face) -> &'static str {
+  match face { Face::U => "red", Face::D => "orange", Face::R => "white", Face::L => "yellow", Face::F => "blue", Face::B => "green" }
+}
+
+// OLL only cares which stickers face up, PLL shows the side colours,
+// F2L greys the last layer so the pair stands out
+fn icon_color(mode: TrainerMode, face: Face) -> Color {
+  match mode {
+    TrainerMode::Oll if face != Face::U => UNORIENTED_COLOR,
+    TrainerMode::F2l if face == Face::U => UNORIENTED_COLOR,
+    _ => face_color(face),
+  }
+}
+
+// last layer cases from above, F2L cases from the front right corner
+fn case_icon(mode: TrainerMode, case: &Case) -> Vec<Vec<Option<Face>>> {
+  match mode {
+    TrainerMode::F2l => case.front_right().iter().map(|row| row.to_vec()).collect(),
+    _ => case.top_layer().iter().map(|row| row.to_vec()).collect(),
+  }
+}
+
+fn build_picker(
+  mut commands: Commands,
+  trainer: Res<Trainer>,
+  mut picker: Query<(Entity, &mut Style), With<CasePicker>>,
+  mut buttons: Query<(&PickCase, &mut BackgroundColor)>,
+  mut shown: Local<Option<TrainerMode>>,
+) {
+  if !trainer.is_changed() { return }
+  let Ok((entity, mut style)) = picker.get_single_mut() else { return };
+
+  let mode = trainer.mode.filter(|_| trainer.picking);
+  style.display = if mode.is_some() { Display::Flex } else { Display::None };
+  let Some(mode) = mode else { return };
+
+  if *shown == Some(mode) {
+    for (pick, mut background) in &mut buttons {
+      *background = case_background(trainer.cases(mode)[pick.0].learning).into();
+    }
+    return
+  }
+  *shown = Some(mode);
+
+  commands.entity(entity).despawn_descendants().with_children(|picker| {
+    for (index, case) in mode.cases().iter().enumerate() {
+      picker.spawn((
+        ButtonBundle {
+          style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+          },
+          background_color: case_background(trainer.cases(mode)[index].learning).into(),
+          ..default()
+        },
+        PickCase(index),
+      )).with_children(|button| {
+        for row in case_icon(mode, case) {
+          button.spawn(NodeBundle::default()).with_children(|line| {
+            for sticker in row {
+              line.spawn(NodeBundle {
+                style: Style {
+                  width: Val::Px(ICON_STICKER),
+                  height: Val::Px(ICON_STICKER),
+                  margin: UiRect::all(Val::Px(0.5)),
+                  ..default()
+                },
+                background_color: sticker.map_or(Color::NONE, |face| icon_color(mode, face)).into(),
+                ..default()
+              });
+            }
+          });
+        }
+        button.spawn(TextBundle::from_section(case.name, TextStyle { font_size: 14.0, color: Color::WHITE, ..default() }));
+      });
+    }
+  });
+}
+
+fn case_background(learning: bool) -> Color {
+  if learning { LEARNING_COLOR } else { CASE_COLOR }
+}
+
+fn pick_case(
+  buttons: Query<(&Interaction, &PickCase), Changed<Interaction>>,
+  mut trainer: ResMut<Trainer>,
+) {
+  let Some(mode) = trainer.mode.filter(|_| trainer.picking) else { return };
+  let mut changed = false;
+  for (interaction, pick) in &buttons {
+    if *interaction != Interaction::Pressed { continue }
+    let stats = &mut trainer.stats[mode as usize][pick.0];
+    stats.learning = !stats.learning;
+    changed = true;
+  }
+  if changed { trainer.save(STATS_FILE); }
+}
+
+/* MARK: PANEL
+  the case name only shows once it is solved, recognising it is part of the drill
+*/
+#[derive(Component)]
+struct TrainerText;
+
+fn setup_trainer(mut commands: Commands) {
+  commands.spawn((
+    TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+      .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(12.0),
+        right: Val::Px(12.0),
+        ..default()
+      }),
+    TrainerText,
+  ));
+
+  commands.spawn((
+    NodeBundle {
+      style: Style {
+        display: Display::None,
+        position_type: PositionType::Absolute,
+        left: Val::Px(12.0),
+        right: Val::Px(280.0),
+        bottom: Val::Px(12.0),
+        max_height: Val::Percent(70.0),
+        flex_wrap: FlexWrap::Wrap,
+        column_gap: Val::Px(4.0),
+        row_gap: Val::Px(4.0),
+        padding: UiRect::all(Val::Px(8.0)),
+        overflow: Overflow::clip_y(),
+        ..default()
+      },
+      background_color: PICKER_COLOR.into(),
+      ..default()
+    },
+    CasePicker,
+  ));
+}
+
+fn update_trainer_text(
+  trainer: Res<Trainer>,
+  mut text: Query<&mut Text, With<TrainerText>>,
+) {
+  if !trainer.is_changed() { return }
+  let Ok(mut text) = text.get_single_mut() else { return };
+
+  let Some(mode) = trainer.mode else {
+    text.sections[0].value = String::new();
+    return
+  };
+  if mode == TrainerMode::Cross {
+    text.sections[0].value = cross_text(&trainer);
+    return
+  }
+  let cases = mode.cases();
+  let stats = trainer.cases(mode);
+  let on_off = |on: bool| if on { "on" } else { "off" };
+  let learning = stats.iter().filter(|s| s.learning).count();
+
+  let mut lines = vec![
+    format!("{} trainer", mode.name()),
+    format!("random y {}, focus {}", on_off(trainer.random_y), on_off(trainer.focus)),
+    if learning == 0 { "learning all cases".to_string() } else { format!("learning {} cases", learning) },
+  ];
+  if mode == TrainerMode::F2l {
+    lines.push(match trainer.extra_slot {
+      Some(slot) => format!("FR and {} slots (J to change)", slot.name()),
+      None => "FR slot only (J for a second slot)".to_string(),
+    });
+  }
+  lines.push(match (trainer.drilling, trainer.last) {
+    (Some(_), _) => "case ?".to_string(),
+    (None, Some((case, recognition, execution))) => {
+      format!("case {} {:.2}s (recognition {:.2}, execution {:.2})", cases[case].name, recognition + execution, recognition, execution)
+    }
+    (None, None) => "T for a case, C to pick cases".to_string(),
+  });
+  if trainer.drilling.is_none() && trainer.last.is_some() {
+    for (moves, alg) in &trainer.solutions {
+      lines.push(format!("  {}: {} ({})", moves, alg, alg.len()));
+    }
+  }
+
+  lines.push("slowest (recognition + execution)".to_string());
+  for case in trainer.slowest(mode).into_iter().take(LISTED_CASES) {
+    let times = match (stats[case].mean(), stats[case].best) {
+      (Some((recognition, execution)), Some(best)) => {
+        format!("{:.2} + {:.2} best {:.2} ({})", recognition, execution, best, stats[case].attempts)
+      }
+      _ => "untried".to_string(),
+    };
+    lines.push(format!("{:<3} {}", cases[case].name, times));
+  }
+  text.sections[0].value = lines.join("\n");
+}
+
+// the optimal crosses stay hidden until the attempt is over, planning them is the drill
+fn cross_text(trainer: &Trainer) -> String {
+  let mut lines = vec![
+    "Cross trainer".to_string(),
+    match trainer.cross_color {
+      Some(color) => format!("{} cross (J to change)", color_name(color)),
+      None => "any colour (J to pick one)".to_string(),
+    },
+  ];
+  match trainer.cross_result {
+    _ if trainer.crossing => lines.push("plan it during inspection, the clock stops with the cross".to_string()),
+    Some((turns, time)) if trainer.task.is_some() => {
+      lines.push(format!("cross {} HTM in {:.2}s, optimal still being worked out", turns, time));
+    }
+    Some((turns, time)) => {
+      let optimal = trainer.solutions.iter().map(|(_, alg)| alg.len()).min().unwrap_or(0);
+      lines.push(format!("cross {} HTM in {:.2}s, optimal {} (+{})", turns, time, optimal, turns.saturating_sub(optimal)));
+      for (color, alg) in &trainer.solutions {
+        lines.push(format!("  {}: {} ({})", color, alg, alg.len()));
+      }
+    }
+    None => lines.push("T to scramble".to_string()),
+  }
+  lines.join("\n")
+}
@@ -1,15 +1,33 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 use bevy::prelude::*;
 // use bevy::time::Stopwatch;
+use component::alggen::AlgGenComponent;
+use component::analysis::AnalysisComponent;
+use component::bandage::BandageComponent;
+use component::blind::BlindComponent;
 use component::cube::CubeModels;
 use component::camera::CameraComponent;
+use component::commutator::CommutatorComponent;
+use component::fmc::FmcComponent;
+use component::hud::HudComponent;
+use component::library::LibraryComponent;
+use component::optimal::OptimalComponent;
+use component::patterns::PatternsComponent;
+use component::puzzles::PuzzlesComponent;
+use component::replay::ReplayComponent;
+use component::shapes::ShapesComponent;
+use component::supercube::SupercubeComponent;
+use component::thistlethwaite::ThistlethwaiteComponent;
+use component::trainer::TrainerComponent;
 
 pub mod component;
 
 fn main() {
   App::new()
     .add_plugins(DefaultPlugins)
-    .add_plugins((CameraComponent, CubeModels, VisualStyles))
+    .add_plugins((CameraComponent, CubeModels, HudComponent, PuzzlesComponent, ReplayComponent, ShapesComponent, SupercubeComponent, VisualStyles))
+    // modes and tools, add_plugins takes at most fifteen at a time
+    .add_plugins((AlgGenComponent, AnalysisComponent, BandageComponent, BlindComponent, CommutatorComponent, FmcComponent, LibraryComponent, OptimalComponent, PatternsComponent, ThistlethwaiteComponent, TrainerComponent))
     .run();
 }
 